@@ -0,0 +1,25 @@
+use circularbuf::Buffer;
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+
+fn write(c: &mut Criterion) {
+  let mut group = c.benchmark_group("write");
+  for len in [8usize, 64, 512, 8192] {
+    let src = vec![0xAB; len];
+    group.throughput(Throughput::Bytes(len as u64));
+
+    group.bench_with_input(BenchmarkId::new("pow2", len), &src, |b, src| {
+      let mut buf = Buffer::new(vec![0u8; 4096]);
+      b.iter(|| buf.write(black_box(src.as_slice())));
+    });
+
+    group.bench_with_input(BenchmarkId::new("non_pow2", len), &src, |b, src| {
+      let mut buf = Buffer::new(vec![0u8; 4000]);
+      b.iter(|| buf.write(black_box(src.as_slice())));
+    });
+  }
+  group.finish();
+}
+
+criterion_group!(benches, write);
+criterion_main!(benches);
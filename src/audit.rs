@@ -0,0 +1,115 @@
+use std::io;
+
+use crate::Buffer;
+
+/// A circular buffer which archives every unread byte into a sink just before it is
+/// overwritten, turning the ring into a hot tail in memory over a complete archive.
+///
+/// Created by [`Buffer::with_audit_sink`]. The archived bytes are written in order, from
+/// the oldest to the newest, so the sink followed by the unread bytes of the ring is
+/// exactly what was written. Bytes already consumed by [`read`](AuditBuffer::read) are
+/// not archived.
+///
+/// ```rust
+/// use circularbuf::Buffer;
+///
+/// let mut buf = Buffer::new([0u8; 4]).with_audit_sink(Vec::new());
+/// buf.write(b"hello").unwrap();
+/// buf.write(b" world").unwrap();
+///
+/// let (buf, archive) = buf.into_parts();
+/// assert_eq!(archive, b"hello w");
+/// assert_eq!(buf.read_to_bytes().as_ref(), b"orld");
+/// ```
+#[derive(Debug, Clone)]
+pub struct AuditBuffer<B, W> {
+  buf: Buffer<B>,
+  sink: W,
+}
+
+impl<B> Buffer<B> {
+  /// Turns the buffer into an [`AuditBuffer`] archiving the overwritten bytes into `sink`.
+  #[inline]
+  pub fn with_audit_sink<W>(self, sink: W) -> AuditBuffer<B, W>
+  where
+    W: io::Write,
+  {
+    AuditBuffer { buf: self, sink }
+  }
+}
+
+impl<B, W> AuditBuffer<B, W>
+where
+  B: AsRef<[u8]> + AsMut<[u8]>,
+  W: io::Write,
+{
+  /// Writes up to len(buf) bytes to the internal ring, archiving the unread bytes
+  /// it overwrites into the sink first.
+  ///
+  /// If the sink fails, the error is returned and the ring is left untouched, so the
+  /// write can be retried. The sink may have received a part of the archived bytes.
+  pub fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    let size = self.buf.size();
+    let evicted = self.buf.overwritten_by(buf.len(), size);
+    let (front, back) = self.buf.head(evicted);
+    self.sink.write_all(front)?;
+    self.sink.write_all(back)?;
+    // The leading part of a write larger than the ring is overwritten immediately
+    self
+      .sink
+      .write_all(&buf[..buf.len().saturating_sub(size)])?;
+    Ok(self.buf.write(buf))
+  }
+}
+
+impl<B, W> AuditBuffer<B, W> {
+  /// Reads up to `dst.len()` of the oldest unread bytes into `dst` and consumes them,
+  /// returns number of bytes readed.
+  #[inline]
+  pub fn read(&mut self, dst: &mut [u8]) -> usize
+  where
+    B: AsRef<[u8]>,
+  {
+    self.buf.read(dst)
+  }
+
+  /// Returns the underlying buffer.
+  #[inline]
+  pub const fn as_buffer(&self) -> &Buffer<B> {
+    &self.buf
+  }
+
+  /// Returns a reference to the sink.
+  #[inline]
+  pub const fn sink(&self) -> &W {
+    &self.sink
+  }
+
+  /// Returns a mutable reference to the sink.
+  #[inline]
+  pub const fn sink_mut(&mut self) -> &mut W {
+    &mut self.sink
+  }
+
+  /// Consumes the audit buffer and returns the underlying buffer and the sink.
+  #[inline]
+  pub fn into_parts(self) -> (Buffer<B>, W) {
+    (self.buf, self.sink)
+  }
+}
+
+impl<B, W> io::Write for AuditBuffer<B, W>
+where
+  B: AsRef<[u8]> + AsMut<[u8]>,
+  W: io::Write,
+{
+  #[inline]
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    AuditBuffer::write(self, buf)
+  }
+
+  #[inline]
+  fn flush(&mut self) -> io::Result<()> {
+    self.sink.flush()
+  }
+}
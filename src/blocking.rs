@@ -0,0 +1,489 @@
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+use core::task::Poll;
+use core::task::Waker;
+use std::{
+  io,
+  sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+  vec::Vec,
+};
+
+use crate::Buffer;
+
+/// A bounded pipe between threads, backed by a circular buffer.
+///
+/// Unlike [`SyncBuffer`](crate::SyncBuffer), unread data is never overwritten:
+/// [`write`](BlockingBuffer::write) blocks until a reader frees enough space, and
+/// [`read`](BlockingBuffer::read) blocks until some data is available.
+/// Cloning a `BlockingBuffer` yields a handle to the same pipe.
+///
+/// Once [`close`](BlockingBuffer::close)d, writes are rejected and reads return `0`
+/// after the remaining data is drained, like the end of a file.
+///
+/// With the `tokio` or `future` feature, the async I/O traits are implemented as well,
+/// and apply backpressure the same way: writes are pending while the buffer is full,
+/// reads while it is empty. Shutting down the writer closes the pipe.
+///
+/// ```rust
+/// use circularbuf::BlockingBuffer;
+/// use std::io::Read;
+///
+/// let pipe = BlockingBuffer::new([0u8; 4]);
+/// let writer = pipe.clone();
+/// let handle = std::thread::spawn(move || {
+///   writer.write(b"hello world");
+///   writer.close();
+/// });
+///
+/// let mut out = String::new();
+/// (&pipe).read_to_string(&mut out).unwrap();
+/// assert_eq!(out, "hello world");
+/// handle.join().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct BlockingBuffer<B> {
+  inner: Arc<Inner<B>>,
+}
+
+#[derive(Debug)]
+struct Inner<B> {
+  state: Mutex<State<B>>,
+  readable: Condvar,
+  writable: Condvar,
+}
+
+#[derive(Debug)]
+struct State<B> {
+  buf: Buffer<B>,
+  closed: bool,
+  readers: Vec<Waker>,
+  writers: Vec<Waker>,
+}
+
+impl<B> Inner<B> {
+  fn wake_readers(&self, state: &mut State<B>) {
+    self.readable.notify_all();
+    state.readers.drain(..).for_each(Waker::wake);
+  }
+
+  fn wake_writers(&self, state: &mut State<B>) {
+    self.writable.notify_all();
+    state.writers.drain(..).for_each(Waker::wake);
+  }
+}
+
+impl<B> Clone for BlockingBuffer<B> {
+  #[inline]
+  fn clone(&self) -> Self {
+    Self {
+      inner: self.inner.clone(),
+    }
+  }
+}
+
+impl<B> From<Buffer<B>> for BlockingBuffer<B> {
+  #[inline]
+  fn from(buf: Buffer<B>) -> Self {
+    Self {
+      inner: Arc::new(Inner {
+        state: Mutex::new(State {
+          buf,
+          closed: false,
+          readers: Vec::new(),
+          writers: Vec::new(),
+        }),
+        readable: Condvar::new(),
+        writable: Condvar::new(),
+      }),
+    }
+  }
+}
+
+impl<B> BlockingBuffer<B> {
+  /// Creates a new pipe with the given data.
+  #[inline]
+  pub fn new(data: B) -> Self {
+    Self::from(Buffer::new(data))
+  }
+
+  #[inline]
+  fn lock(&self) -> MutexGuard<'_, State<B>> {
+    self
+      .inner
+      .state
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+  }
+
+  /// Writes all of `buf`, blocking the current thread while the buffer is full,
+  /// returns the number of elements written.
+  ///
+  /// Fewer elements than `buf.len()` are written only if the pipe is closed meanwhile,
+  /// or if the buffer has a size of zero.
+  pub fn write<T>(&self, mut buf: &[T]) -> usize
+  where
+    B: AsRef<[T]> + AsMut<[T]>,
+    T: Copy,
+  {
+    let mut state = self.lock();
+    if state.buf.size() == 0 {
+      return 0;
+    }
+
+    let mut total = 0;
+    while !state.closed {
+      match state.buf.try_write(buf) {
+        Ok(n) => {
+          total += n;
+          break;
+        }
+        Err(full) => {
+          total += full.written();
+          buf = &buf[full.written()..];
+          if full.written() != 0 {
+            self.inner.wake_readers(&mut state);
+          }
+          state = self
+            .inner
+            .writable
+            .wait(state)
+            .unwrap_or_else(PoisonError::into_inner);
+        }
+      }
+    }
+
+    self.inner.wake_readers(&mut state);
+    total
+  }
+
+  /// Reads up to `dst.len()` of the oldest unread elements into `dst` and consumes them,
+  /// blocking the current thread until some data is available, returns number of
+  /// elements readed.
+  ///
+  /// Returns `0` once the pipe is closed and drained, or if `dst` is empty.
+  pub fn read<T>(&self, dst: &mut [T]) -> usize
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    if dst.is_empty() {
+      return 0;
+    }
+
+    let mut state = self.lock();
+    while state.buf.unread() == 0 && !state.closed {
+      state = self
+        .inner
+        .readable
+        .wait(state)
+        .unwrap_or_else(PoisonError::into_inner);
+    }
+
+    let n = state.buf.read(dst);
+    self.inner.wake_writers(&mut state);
+    n
+  }
+
+  /// Closes the pipe: blocked and future writes stop writing, and reads return `0`
+  /// once the remaining data is drained.
+  pub fn close(&self) {
+    let mut state = self.lock();
+    state.closed = true;
+    self.inner.wake_readers(&mut state);
+    self.inner.wake_writers(&mut state);
+  }
+
+  /// Returns `true` if the pipe was [`close`](BlockingBuffer::close)d.
+  #[inline]
+  pub fn is_closed(&self) -> bool {
+    self.lock().closed
+  }
+
+  /// Returns how many elements are pending.
+  #[inline]
+  pub fn unread(&self) -> usize {
+    self.lock().buf.unread()
+  }
+
+  /// Returns the total number of elements written to the buffer
+  #[inline]
+  pub fn written(&self) -> usize {
+    self.lock().buf.written()
+  }
+}
+
+impl<B> io::Write for &BlockingBuffer<B>
+where
+  B: AsRef<[u8]> + AsMut<[u8]>,
+{
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    match BlockingBuffer::write(self, buf) {
+      0 if !buf.is_empty() => Err(io::ErrorKind::BrokenPipe.into()),
+      n => Ok(n),
+    }
+  }
+
+  #[inline]
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+impl<B> io::Write for BlockingBuffer<B>
+where
+  B: AsRef<[u8]> + AsMut<[u8]>,
+{
+  #[inline]
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    io::Write::write(&mut &*self, buf)
+  }
+
+  #[inline]
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+impl<B> io::Read for &BlockingBuffer<B>
+where
+  B: AsRef<[u8]>,
+{
+  #[inline]
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    Ok(BlockingBuffer::read(self, buf))
+  }
+}
+
+impl<B> io::Read for BlockingBuffer<B>
+where
+  B: AsRef<[u8]>,
+{
+  #[inline]
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    Ok(BlockingBuffer::read(self, buf))
+  }
+}
+
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+impl<B> BlockingBuffer<B> {
+  /// Writes what fits of `buf`, or registers the task to be woken once there is room.
+  fn poll_write_some(&self, cx: &mut core::task::Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>>
+  where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+  {
+    if buf.is_empty() {
+      return Poll::Ready(Ok(0));
+    }
+
+    let mut state = self.lock();
+    if state.closed || state.buf.size() == 0 {
+      return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+    }
+
+    let n = match state.buf.try_write(buf) {
+      Ok(n) => n,
+      Err(full) if full.written() != 0 => full.written(),
+      Err(_) => {
+        register(&mut state.writers, cx.waker());
+        return Poll::Pending;
+      }
+    };
+    self.inner.wake_readers(&mut state);
+    Poll::Ready(Ok(n))
+  }
+
+  /// Reads what is available, or registers the task to be woken once there is data.
+  fn poll_read_some(&self, cx: &mut core::task::Context<'_>, dst: &mut [u8]) -> Poll<usize>
+  where
+    B: AsRef<[u8]>,
+  {
+    if dst.is_empty() {
+      return Poll::Ready(0);
+    }
+
+    let mut state = self.lock();
+    if state.buf.unread() == 0 && !state.closed {
+      register(&mut state.readers, cx.waker());
+      return Poll::Pending;
+    }
+
+    let n = state.buf.read(dst);
+    self.inner.wake_writers(&mut state);
+    Poll::Ready(n)
+  }
+}
+
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+fn register(wakers: &mut Vec<Waker>, waker: &Waker) {
+  if !wakers.iter().any(|w| w.will_wake(waker)) {
+    wakers.push(waker.clone());
+  }
+}
+
+#[cfg(feature = "tokio")]
+const _: () = {
+  use core::{pin::Pin, task::Context};
+  use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+  impl<B> AsyncRead for &BlockingBuffer<B>
+  where
+    B: AsRef<[u8]>,
+  {
+    fn poll_read(
+      self: Pin<&mut Self>,
+      cx: &mut Context<'_>,
+      buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+      let n = core::task::ready!(self.poll_read_some(cx, buf.initialize_unfilled()));
+      buf.advance(n);
+      Poll::Ready(Ok(()))
+    }
+  }
+
+  impl<B> AsyncRead for BlockingBuffer<B>
+  where
+    B: AsRef<[u8]>,
+  {
+    #[inline]
+    fn poll_read(
+      self: Pin<&mut Self>,
+      cx: &mut Context<'_>,
+      buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+      Pin::new(&mut &*self).poll_read(cx, buf)
+    }
+  }
+
+  impl<B> AsyncWrite for &BlockingBuffer<B>
+  where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+  {
+    #[inline]
+    fn poll_write(
+      self: Pin<&mut Self>,
+      cx: &mut Context<'_>,
+      buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+      self.poll_write_some(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+      Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+      self.close();
+      Poll::Ready(Ok(()))
+    }
+  }
+
+  impl<B> AsyncWrite for BlockingBuffer<B>
+  where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+  {
+    #[inline]
+    fn poll_write(
+      self: Pin<&mut Self>,
+      cx: &mut Context<'_>,
+      buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+      self.poll_write_some(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+      Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+      self.close();
+      Poll::Ready(Ok(()))
+    }
+  }
+};
+
+#[cfg(feature = "futures-io")]
+const _: () = {
+  use core::{pin::Pin, task::Context};
+  use futures_io::{AsyncRead, AsyncWrite};
+
+  impl<B> AsyncRead for &BlockingBuffer<B>
+  where
+    B: AsRef<[u8]>,
+  {
+    #[inline]
+    fn poll_read(
+      self: Pin<&mut Self>,
+      cx: &mut Context<'_>,
+      buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+      self.poll_read_some(cx, buf).map(Ok)
+    }
+  }
+
+  impl<B> AsyncRead for BlockingBuffer<B>
+  where
+    B: AsRef<[u8]>,
+  {
+    #[inline]
+    fn poll_read(
+      self: Pin<&mut Self>,
+      cx: &mut Context<'_>,
+      buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+      self.poll_read_some(cx, buf).map(Ok)
+    }
+  }
+
+  impl<B> AsyncWrite for &BlockingBuffer<B>
+  where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+  {
+    #[inline]
+    fn poll_write(
+      self: Pin<&mut Self>,
+      cx: &mut Context<'_>,
+      buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+      self.poll_write_some(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+      Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+      self.close();
+      Poll::Ready(Ok(()))
+    }
+  }
+
+  impl<B> AsyncWrite for BlockingBuffer<B>
+  where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+  {
+    #[inline]
+    fn poll_write(
+      self: Pin<&mut Self>,
+      cx: &mut Context<'_>,
+      buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+      self.poll_write_some(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+      Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+      self.close();
+      Poll::Ready(Ok(()))
+    }
+  }
+};
@@ -0,0 +1,121 @@
+use std::vec::Vec;
+
+use crate::{Buffer, TooLarge, wrap};
+
+/// The length of the fixed part of a checkpoint: capacity, cursor, written and unread
+/// length, as little-endian `u64`s.
+const HEADER: usize = 32;
+
+/// The complete state of a byte [`Buffer`] in a compact byte representation,
+/// created by [`Buffer::checkpoint`] and restored by [`Buffer::restore`].
+///
+/// Only the unread bytes are stored, along with the cursor and the written count,
+/// so a restored buffer has identical read semantics. The consumed bytes are left out,
+/// the history reachable by [`read_since`](Buffer::read_since) starts at the unread ones.
+///
+/// ```rust
+/// use circularbuf::{Buffer, Checkpoint};
+///
+/// let mut buf = Buffer::new([0u8; 8]);
+/// buf.write(b"hello world");
+///
+/// // Hand the bytes over to another process, or persist them
+/// let bytes = buf.checkpoint().into_bytes();
+///
+/// let checkpoint = Checkpoint::from_bytes(&bytes).unwrap();
+/// let restored = Buffer::restore([0u8; 8], &checkpoint).unwrap();
+/// assert_eq!(restored, buf);
+/// assert_eq!(restored.written(), 11);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Checkpoint {
+  bytes: Vec<u8>,
+}
+
+impl Checkpoint {
+  /// Parses a checkpoint from the bytes returned by [`as_bytes`](Checkpoint::as_bytes),
+  /// returns `None` if they are not a valid checkpoint.
+  pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+    let this = Self {
+      bytes: bytes.to_vec(),
+    };
+    let (capacity, cursor, written, len) = this.fields()?;
+    let valid = len <= capacity
+      && len <= written
+      && (cursor < capacity || capacity == 0 && cursor == 0)
+      && bytes.len() - HEADER == len;
+    valid.then_some(this)
+  }
+
+  /// Returns the byte representation of the checkpoint.
+  #[inline]
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.bytes
+  }
+
+  /// Consumes the checkpoint and returns its byte representation.
+  #[inline]
+  pub fn into_bytes(self) -> Vec<u8> {
+    self.bytes
+  }
+
+  /// Returns the unread bytes, from the oldest to the newest.
+  #[inline]
+  pub fn data(&self) -> &[u8] {
+    &self.bytes[HEADER..]
+  }
+
+  fn fields(&self) -> Option<(usize, usize, usize, usize)> {
+    let field = |i: usize| {
+      let bytes = self.bytes.get(i * 8..i * 8 + 8)?;
+      usize::try_from(u64::from_le_bytes(bytes.try_into().ok()?)).ok()
+    };
+    Some((field(0)?, field(1)?, field(2)?, field(3)?))
+  }
+}
+
+impl<B> Buffer<B> {
+  /// Captures the complete state of the buffer into a [`Checkpoint`].
+  pub fn checkpoint(&self) -> Checkpoint
+  where
+    B: AsRef<[u8]>,
+  {
+    let (front, back) = self.as_slices();
+    let mut bytes = Vec::with_capacity(HEADER + self.len);
+    for field in [self.size(), self.write_cursor, self.written, self.len] {
+      bytes.extend_from_slice(&(field as u64).to_le_bytes());
+    }
+    bytes.extend_from_slice(front);
+    bytes.extend_from_slice(back);
+    Checkpoint { bytes }
+  }
+
+  /// Rebuilds the buffer captured by `checkpoint` on top of `data`.
+  ///
+  /// If `data` has the same size as the captured buffer, the cursor is restored as is.
+  /// Otherwise the unread bytes are laid out from the start of the storage. Either way,
+  /// they are the oldest bytes [`read_since`](Buffer::read_since) returns.
+  /// Returns an error if `data` is too small to hold the unread bytes.
+  pub fn restore(data: B, checkpoint: &Checkpoint) -> Result<Self, TooLarge>
+  where
+    B: AsMut<[u8]>,
+  {
+    let mut buf = Self::new(data);
+    let size = buf.data.as_mut().len();
+    let src = checkpoint.data();
+    if src.len() > size {
+      return Err(TooLarge::new(src.len(), size));
+    }
+
+    // Validated when the checkpoint was parsed or created.
+    let (capacity, cursor, written, _) = checkpoint.fields().unwrap_or_default();
+    if size != 0 {
+      let end = if size == capacity { cursor } else { src.len() };
+      buf.write_cursor = wrap(end + size - src.len(), size);
+      // Also marks the unread bytes as the only retained history
+      buf.store(src);
+    }
+    buf.written = written;
+    Ok(buf)
+  }
+}
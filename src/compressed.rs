@@ -0,0 +1,189 @@
+use std::{collections::VecDeque, vec::Vec};
+
+use miniz_oxide::{deflate::compress_to_vec, inflate::decompress_to_vec_with_limit};
+
+use crate::Buffer;
+
+/// The deflate compression level of the history, from `0` to `10`.
+const LEVEL: u8 = 6;
+
+/// A run of evicted bytes, compressed with deflate.
+#[derive(Debug, Clone)]
+struct Block {
+  data: Vec<u8>,
+  len: usize,
+}
+
+impl Block {
+  fn decompress(&self) -> Vec<u8> {
+    decompress_to_vec_with_limit(&self.data, self.len)
+      .expect("history blocks are compressed by the buffer itself")
+  }
+}
+
+/// A circular buffer which keeps the data it overwrites compressed, so the same memory
+/// budget retains a much longer history.
+///
+/// Created by [`Buffer::with_compressed_history`]. The newest data lives uncompressed in
+/// the ring. The bytes overwritten by a write are collected until they add up to the size
+/// of the ring, and are then compressed with deflate into a block of history. Once the
+/// blocks exceed the compressed budget, the oldest ones are dropped. The history is
+/// decompressed transparently on export.
+///
+/// Deflate needs some context to be effective, so the ring should be at least a few KiB.
+///
+/// ```rust
+/// use circularbuf::Buffer;
+///
+/// let mut buf = Buffer::with_capacity(1024).with_compressed_history(4096);
+/// for i in 0..10_000 {
+///   buf.write(format!("request {i} served in 3ms\n").as_bytes());
+/// }
+///
+/// // Much more than the 5 KiB of memory is retained
+/// assert!(buf.len() > 16 * 1024);
+/// assert!(buf.compressed_len() <= 4096);
+///
+/// let history = buf.to_vec();
+/// assert!(history.ends_with(b"request 9999 served in 3ms\n"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompressedBuffer<B> {
+  buf: Buffer<B>,
+  /// The evicted bytes which are not compressed yet.
+  staging: Vec<u8>,
+  /// The compressed history, from the oldest to the newest.
+  blocks: VecDeque<Block>,
+  /// The total compressed size of the blocks.
+  compressed: usize,
+  budget: usize,
+}
+
+impl<B> Buffer<B> {
+  /// Turns the buffer into a [`CompressedBuffer`], which keeps up to `budget` bytes of
+  /// compressed history on top of the ring.
+  #[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+  pub fn with_compressed_history(self, budget: usize) -> CompressedBuffer<B> {
+    CompressedBuffer {
+      buf: self,
+      staging: Vec::new(),
+      blocks: VecDeque::new(),
+      compressed: 0,
+      budget,
+    }
+  }
+}
+
+impl<B> CompressedBuffer<B>
+where
+  B: AsRef<[u8]> + AsMut<[u8]>,
+{
+  /// Writes up to len(buf) bytes to the internal ring, moving the overwritten bytes
+  /// to the compressed history.
+  pub fn write(&mut self, buf: &[u8]) -> usize {
+    let staging = &mut self.staging;
+    let n = self
+      .buf
+      .write_with_evict(buf, |evicted| staging.extend_from_slice(evicted));
+
+    if !self.staging.is_empty() && self.staging.len() >= self.buf.size() {
+      self.compress_staging();
+    }
+
+    while self.compressed > self.budget {
+      match self.blocks.pop_front() {
+        Some(block) => self.compressed -= block.data.len(),
+        None => break,
+      }
+    }
+    n
+  }
+
+  /// Compresses the staged bytes into a new block of history.
+  ///
+  /// Small blocks compress poorly, so the staged bytes are merged into the newest block
+  /// until it reaches a quarter of the budget, which bounds the history dropped at once.
+  fn compress_staging(&mut self) {
+    let mut raw = core::mem::take(&mut self.staging);
+    if self
+      .blocks
+      .back()
+      .is_some_and(|block| block.data.len() < self.budget / 4)
+    {
+      if let Some(block) = self.blocks.pop_back() {
+        self.compressed -= block.data.len();
+        let mut merged = block.decompress();
+        merged.append(&mut raw);
+        raw = merged;
+      }
+    }
+
+    let data = compress_to_vec(&raw, LEVEL);
+    self.compressed += data.len();
+    self.blocks.push_back(Block {
+      data,
+      len: raw.len(),
+    });
+    // Reuses the allocation for the next staged bytes
+    raw.clear();
+    self.staging = raw;
+  }
+
+  /// Returns the whole retained data, from the oldest to the newest,
+  /// with the history decompressed.
+  pub fn to_vec(&self) -> Vec<u8> {
+    let mut out = Vec::with_capacity(self.len());
+    for block in &self.blocks {
+      out.extend_from_slice(&block.decompress());
+    }
+    out.extend_from_slice(&self.staging);
+    let (front, back) = self.buf.as_slices();
+    out.extend_from_slice(front);
+    out.extend_from_slice(back);
+    out
+  }
+
+  /// Writes the whole retained data into `w`, from the oldest to the newest, returns the
+  /// number of bytes written.
+  ///
+  /// The history is decompressed one block at a time.
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(all(feature = "deflate", feature = "std"))))]
+  pub fn copy_to_writer<W>(&self, w: &mut W) -> std::io::Result<usize>
+  where
+    W: std::io::Write + ?Sized,
+  {
+    for block in &self.blocks {
+      w.write_all(&block.decompress())?;
+    }
+    w.write_all(&self.staging)?;
+    self.buf.copy_to_writer(w)?;
+    Ok(self.len())
+  }
+}
+
+impl<B> CompressedBuffer<B> {
+  /// Returns the number of bytes retained, in the history and in the ring.
+  #[inline]
+  pub fn len(&self) -> usize {
+    self.blocks.iter().map(|block| block.len).sum::<usize>() + self.staging.len() + self.buf.len
+  }
+
+  /// Returns `true` if no bytes are retained.
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns the size of the compressed history, which never exceeds the budget.
+  #[inline]
+  pub const fn compressed_len(&self) -> usize {
+    self.compressed
+  }
+
+  /// Returns the underlying buffer, holding the newest data.
+  #[inline]
+  pub const fn as_buffer(&self) -> &Buffer<B> {
+    &self.buf
+  }
+}
@@ -0,0 +1,107 @@
+use core::fmt::{self, Write};
+
+use crate::Buffer;
+
+/// The default maximum number of the newest bytes shown by the [`Display`](fmt::Display)
+/// implementation.
+const CAP: usize = 1024;
+
+/// The marker written in place of the bytes left out by the cap.
+const TRUNCATED: &str = "…";
+
+/// Renders the unread bytes as UTF-8, with invalid sequences replaced by `U+FFFD`,
+/// as `String::from_utf8_lossy` does.
+///
+/// Only the newest 1024 bytes are shown, the older ones are replaced by a leading `…`.
+/// The precision overrides this cap, e.g. `{:.64}` shows the newest 64 bytes, which never
+/// splits a character. Nothing is consumed.
+///
+/// ```rust
+/// use circularbuf::Buffer;
+///
+/// let mut buf = Buffer::new([0u8; 16]);
+/// buf.write("[info] café\n[warn] low disk\n".as_bytes());
+///
+/// assert_eq!(buf.to_string(), "[warn] low disk\n");
+/// assert_eq!(format!("{buf:.9}"), "…low disk\n");
+/// ```
+impl<B> fmt::Display for Buffer<B>
+where
+  B: AsRef<[u8]>,
+{
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let (mut front, mut back) = self.as_slices();
+    let cap = f.precision().unwrap_or(CAP);
+    if self.len > cap {
+      f.write_str(TRUNCATED)?;
+      // Starts at the next character, rather than in the middle of one
+      let byte = |i: usize| match front.get(i) {
+        Some(&b) => b,
+        None => back[i - front.len()],
+      };
+      let mut skip = self.len - cap;
+      for _ in 0..3 {
+        if skip < self.len && is_continuation(byte(skip)) {
+          skip += 1;
+        }
+      }
+
+      if skip < front.len() {
+        front = &front[skip..];
+      } else {
+        back = &back[skip - front.len()..];
+        front = &[];
+      }
+    }
+
+    // A character split by the wrap point is stitched back together
+    let tail = incomplete_tail(front);
+    let (head, tail) = front.split_at(front.len() - tail);
+    write_lossy(f, head)?;
+    let mut stitch = [0u8; 4];
+    let need = tail
+      .first()
+      .map_or(0, |&lead| width(lead) - tail.len())
+      .min(back.len());
+    stitch[..tail.len()].copy_from_slice(tail);
+    stitch[tail.len()..tail.len() + need].copy_from_slice(&back[..need]);
+    write_lossy(f, &stitch[..tail.len() + need])?;
+    write_lossy(f, &back[need..])
+  }
+}
+
+/// Writes `bytes` as UTF-8, with invalid sequences replaced by `U+FFFD`.
+fn write_lossy(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+  for chunk in bytes.utf8_chunks() {
+    f.write_str(chunk.valid())?;
+    if !chunk.invalid().is_empty() {
+      f.write_char(char::REPLACEMENT_CHARACTER)?;
+    }
+  }
+  Ok(())
+}
+
+/// Returns the length of the incomplete character at the end of `bytes`, if any.
+fn incomplete_tail(bytes: &[u8]) -> usize {
+  for (i, &b) in bytes.iter().rev().take(3).enumerate() {
+    if !is_continuation(b) {
+      return if width(b) > i + 1 { i + 1 } else { 0 };
+    }
+  }
+  0
+}
+
+/// Returns the length of the character starting with the byte `lead`.
+const fn width(lead: u8) -> usize {
+  match lead {
+    0xc2..=0xdf => 2,
+    0xe0..=0xef => 3,
+    0xf0..=0xf4 => 4,
+    _ => 1,
+  }
+}
+
+#[inline]
+const fn is_continuation(b: u8) -> bool {
+  b & 0xc0 == 0x80
+}
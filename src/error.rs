@@ -0,0 +1,217 @@
+/// An error returned by [`Buffer::try_read_into`](crate::Buffer::try_read_into)
+/// when the destination is too small to hold the retained data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ReadError {
+  required: usize,
+  available: usize,
+}
+
+impl ReadError {
+  #[inline]
+  pub(crate) const fn new(required: usize, available: usize) -> Self {
+    Self {
+      required,
+      available,
+    }
+  }
+
+  /// Returns the number of elements the destination must be able to hold.
+  #[inline]
+  pub const fn required(&self) -> usize {
+    self.required
+  }
+
+  /// Returns the length of the destination.
+  #[inline]
+  pub const fn available(&self) -> usize {
+    self.available
+  }
+}
+
+impl core::fmt::Display for ReadError {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(
+      f,
+      "destination too small: required {} but only {} available",
+      self.required, self.available
+    )
+  }
+}
+
+impl core::error::Error for ReadError {}
+
+/// An error returned when a reader fell behind the writer, and some data
+/// was overwritten before it could be read.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Lagged(pub(crate) usize);
+
+impl Lagged {
+  /// Returns the number of elements which were overwritten before they could be read.
+  #[inline]
+  pub const fn missed(&self) -> usize {
+    self.0
+  }
+}
+
+impl core::fmt::Display for Lagged {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(
+      f,
+      "reader lagged behind, {} elements were overwritten",
+      self.0
+    )
+  }
+}
+
+impl core::error::Error for Lagged {}
+
+/// An error returned by [`Buffer::read_since`](crate::Buffer::read_since) when some of
+/// the data written since the requested offset has already been overwritten.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Gap {
+  missed: usize,
+  oldest: usize,
+}
+
+impl Gap {
+  #[inline]
+  pub(crate) const fn new(missed: usize, oldest: usize) -> Self {
+    Self { missed, oldest }
+  }
+
+  /// Returns the number of elements which were overwritten.
+  #[inline]
+  pub const fn missed(&self) -> usize {
+    self.missed
+  }
+
+  /// Returns the stream offset of the oldest retained element, from which reading can resume.
+  #[inline]
+  pub const fn oldest(&self) -> usize {
+    self.oldest
+  }
+}
+
+impl core::fmt::Display for Gap {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(
+      f,
+      "{} elements were overwritten, the oldest retained offset is {}",
+      self.missed, self.oldest
+    )
+  }
+}
+
+impl core::error::Error for Gap {}
+
+/// An error returned when a record or a [`Checkpoint`](crate::Checkpoint) can never fit
+/// in a buffer, even if it was empty.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TooLarge {
+  required: usize,
+  capacity: usize,
+}
+
+impl TooLarge {
+  #[inline]
+  pub(crate) const fn new(required: usize, capacity: usize) -> Self {
+    Self { required, capacity }
+  }
+
+  /// Returns the number of bytes the data would occupy.
+  #[inline]
+  pub const fn required(&self) -> usize {
+    self.required
+  }
+
+  /// Returns the capacity of the buffer.
+  #[inline]
+  pub const fn capacity(&self) -> usize {
+    self.capacity
+  }
+}
+
+impl core::fmt::Display for TooLarge {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(
+      f,
+      "{} bytes exceed the buffer capacity of {} bytes",
+      self.required, self.capacity
+    )
+  }
+}
+
+impl core::error::Error for TooLarge {}
+
+/// An error returned by [`Buffer::try_write`](crate::Buffer::try_write) when the input
+/// does not fit in the free space, and some of it was rejected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Full {
+  written: usize,
+  rejected: usize,
+}
+
+impl Full {
+  #[inline]
+  pub(crate) const fn new(written: usize, rejected: usize) -> Self {
+    Self { written, rejected }
+  }
+
+  /// Returns the number of leading elements of the input which were written.
+  #[inline]
+  pub const fn written(&self) -> usize {
+    self.written
+  }
+
+  /// Returns the number of trailing elements of the input which were rejected.
+  #[inline]
+  pub const fn rejected(&self) -> usize {
+    self.rejected
+  }
+}
+
+impl core::fmt::Display for Full {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(
+      f,
+      "buffer full: {} elements written, {} rejected",
+      self.written, self.rejected
+    )
+  }
+}
+
+impl core::error::Error for Full {}
+
+/// An error returned by [`Buffer::from_hex`](crate::Buffer::from_hex) when the input is
+/// not a valid hex string.
+#[cfg(feature = "hex")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hex")))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct InvalidHex {
+  position: usize,
+}
+
+#[cfg(feature = "hex")]
+impl InvalidHex {
+  #[inline]
+  pub(crate) const fn new(position: usize) -> Self {
+    Self { position }
+  }
+
+  /// Returns the position of the offending character, which is the length of the input
+  /// if it has an odd number of digits.
+  #[inline]
+  pub const fn position(&self) -> usize {
+    self.position
+  }
+}
+
+#[cfg(feature = "hex")]
+impl core::fmt::Display for InvalidHex {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(f, "invalid hex digit at position {}", self.position)
+  }
+}
+
+#[cfg(feature = "hex")]
+impl core::error::Error for InvalidHex {}
@@ -0,0 +1,211 @@
+use std::{fs::File, io, path::Path};
+
+use crate::header::{self, Header};
+
+/// A circular buffer which writes through to a fixed-size file, using positional
+/// writes instead of a memory map.
+///
+/// The file starts with a small header holding the cursor, which is updated with
+/// a write-then-fsync ordering: retained bytes are first dropped from the header,
+/// then the new data is written and synced, and only then the header is updated to
+/// include it. The header alternates between two checksummed slots, so a crash at any
+/// point leaves a consistent buffer to recover on [`open`](FileBuffer::open), at the
+/// cost of a few `fsync`s per operation.
+///
+/// ```rust,no_run
+/// use circularbuf::FileBuffer;
+///
+/// let mut buf = FileBuffer::open("recorder.bin", 4096)?;
+/// buf.write(b"hello world")?;
+/// drop(buf);
+///
+/// let buf = FileBuffer::open("recorder.bin", 4096)?;
+/// assert_eq!(buf.to_vec()?, b"hello world");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct FileBuffer {
+  file: File,
+  /// The state of the buffer, `seq` is the sequence of the last stored header.
+  state: Header,
+}
+
+impl FileBuffer {
+  /// Opens the buffer stored in the file at `path`, or creates it with room for
+  /// `capacity` bytes if the file does not exist, is empty or its header was never
+  /// written.
+  ///
+  /// Fails with [`io::ErrorKind::InvalidData`] if the file is not a valid buffer,
+  /// or was created with another capacity.
+  pub fn open(path: impl AsRef<Path>, capacity: usize) -> io::Result<Self> {
+    let (file, fresh) = header::open(path.as_ref(), capacity)?;
+    if fresh {
+      let mut this = Self {
+        file,
+        state: Header {
+          seq: 0,
+          capacity,
+          cursor: 0,
+          written: 0,
+          len: 0,
+        },
+      };
+      this.store(0)?;
+      return Ok(this);
+    }
+
+    let mut raw = [0; header::LEN];
+    read_exact_at(&file, &mut raw, 0)?;
+    let state = header::recover(&raw, capacity)?;
+    Ok(Self { file, state })
+  }
+
+  /// Writes `buf` into the ring, overwriting the oldest data if necessary,
+  /// returns the number of bytes written.
+  pub fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    let Header {
+      capacity, cursor, ..
+    } = self.state;
+    let src = &buf[buf.len().saturating_sub(capacity)..];
+
+    // Drop the bytes about to be overwritten from the header first.
+    let keep = capacity - src.len();
+    if self.state.len > keep {
+      self.store(keep)?;
+      self.state.len = keep;
+    }
+
+    let (front, back) = src.split_at(src.len().min(capacity - cursor));
+    write_all_at(&self.file, front, (header::LEN + cursor) as u64)?;
+    write_all_at(&self.file, back, header::LEN as u64)?;
+    self.file.sync_data()?;
+
+    if capacity != 0 {
+      self.state.cursor = (cursor + src.len()) % capacity;
+    }
+    self.state.written += buf.len();
+    self.state.len += src.len();
+    self.store(self.state.len)?;
+    Ok(buf.len())
+  }
+
+  /// Reads up to `dst.len()` of the oldest unread bytes into `dst` and consumes them,
+  /// returns the number of bytes read.
+  pub fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+    let n = dst.len().min(self.state.len);
+    self.read_oldest(&mut dst[..n])?;
+    self.state.len -= n;
+    self.store(self.state.len)?;
+    Ok(n)
+  }
+
+  /// Returns a copy of the unread bytes, from the oldest to the newest.
+  pub fn to_vec(&self) -> io::Result<Vec<u8>> {
+    let mut out = vec![0; self.state.len];
+    self.read_oldest(&mut out)?;
+    Ok(out)
+  }
+
+  /// Resets the buffer so it has no content.
+  pub fn reset(&mut self) -> io::Result<()> {
+    self.store(0)?;
+    self.state.cursor = 0;
+    self.state.written = 0;
+    self.state.len = 0;
+    self.store(0)
+  }
+
+  /// Returns how many bytes are unread.
+  #[inline]
+  pub const fn unread(&self) -> usize {
+    self.state.len
+  }
+
+  /// Returns the total number of bytes ever written.
+  #[inline]
+  pub const fn written(&self) -> usize {
+    self.state.written
+  }
+
+  /// Returns the capacity of the buffer.
+  #[inline]
+  pub const fn capacity(&self) -> usize {
+    self.state.capacity
+  }
+
+  /// Reads the `dst.len()` oldest unread bytes into `dst`, without consuming them.
+  fn read_oldest(&self, dst: &mut [u8]) -> io::Result<()> {
+    let Header {
+      capacity,
+      cursor,
+      len,
+      ..
+    } = self.state;
+    if dst.is_empty() {
+      return Ok(());
+    }
+
+    let start = (cursor + capacity - len) % capacity;
+    let (front, back) = dst.split_at_mut(dst.len().min(capacity - start));
+    read_exact_at(&self.file, front, (header::LEN + start) as u64)?;
+    read_exact_at(&self.file, back, header::LEN as u64)
+  }
+
+  /// Durably stores a header describing the buffer with `len` retained bytes.
+  fn store(&mut self, len: usize) -> io::Result<()> {
+    let header = Header {
+      seq: self.state.seq + 1,
+      len,
+      ..self.state
+    };
+    write_all_at(&self.file, &header.encode(), header.slot() as u64)?;
+    self.file.sync_data()?;
+    self.state.seq = header.seq;
+    Ok(())
+  }
+}
+
+impl io::Write for FileBuffer {
+  #[inline]
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    self.write(buf)
+  }
+
+  #[inline]
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+impl io::Read for FileBuffer {
+  #[inline]
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    self.read(buf)
+  }
+}
+
+#[cfg(unix)]
+fn write_all_at(file: &File, buf: &[u8], offset: u64) -> io::Result<()> {
+  std::os::unix::fs::FileExt::write_all_at(file, buf, offset)
+}
+
+#[cfg(unix)]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+  std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+}
+
+#[cfg(not(unix))]
+fn write_all_at(mut file: &File, buf: &[u8], offset: u64) -> io::Result<()> {
+  use std::io::{Seek, SeekFrom, Write};
+
+  file.seek(SeekFrom::Start(offset))?;
+  file.write_all(buf)
+}
+
+#[cfg(not(unix))]
+fn read_exact_at(mut file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+  use std::io::{Read, Seek, SeekFrom};
+
+  file.seek(SeekFrom::Start(offset))?;
+  file.read_exact(buf)
+}
@@ -0,0 +1,102 @@
+//! A panic hook dumping the retained bytes of a buffer, turning it into a flight recorder.
+//!
+//! ```rust,no_run
+//! use circularbuf::{SyncBuffer, flight_recorder};
+//! use std::io::Write;
+//!
+//! let buf = SyncBuffer::new([0u8; 4096]);
+//! flight_recorder::install(buf.clone(), "crash.log");
+//!
+//! (&buf).write_all(b"about to do something risky\n").unwrap();
+//! panic!("it went wrong");
+//! ```
+
+use std::{
+  fs::File,
+  io::{self, Write},
+  panic,
+  path::{Path, PathBuf},
+  string::String,
+  sync::TryLockError,
+};
+
+use crate::SyncBuffer;
+
+/// Where the retained bytes are dumped on panic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Dump {
+  /// Writes the bytes to the standard error.
+  Stderr,
+  /// Creates, or truncates, the file at the path and writes the bytes into it.
+  File(PathBuf),
+}
+
+impl From<PathBuf> for Dump {
+  #[inline]
+  fn from(path: PathBuf) -> Self {
+    Self::File(path)
+  }
+}
+
+impl From<&Path> for Dump {
+  #[inline]
+  fn from(path: &Path) -> Self {
+    Self::File(path.into())
+  }
+}
+
+impl From<&str> for Dump {
+  #[inline]
+  fn from(path: &str) -> Self {
+    Self::File(path.into())
+  }
+}
+
+impl From<String> for Dump {
+  #[inline]
+  fn from(path: String) -> Self {
+    Self::File(path.into())
+  }
+}
+
+/// Installs a panic hook which dumps the unread bytes of `buffer` to `to` on panic,
+/// then runs the previously installed hook.
+///
+/// If the buffer is locked by the panicking thread, nothing can be dumped, and a note
+/// is written instead. If an earlier panic poisoned the buffer, its bytes are dumped
+/// after a note saying so. Errors while dumping are ignored.
+pub fn install<B>(buffer: SyncBuffer<B>, to: impl Into<Dump>)
+where
+  B: AsRef<[u8]> + Send + 'static,
+{
+  let to = to.into();
+  let previous = panic::take_hook();
+  panic::set_hook(std::boxed::Box::new(move |info| {
+    let _ = dump(&buffer, &to);
+    previous(info);
+  }));
+}
+
+fn dump<B: AsRef<[u8]>>(buffer: &SyncBuffer<B>, to: &Dump) -> io::Result<()> {
+  let mut out: std::boxed::Box<dyn Write> = match to {
+    Dump::Stderr => std::boxed::Box::new(io::stderr().lock()),
+    Dump::File(path) => std::boxed::Box::new(File::create(path)?),
+  };
+
+  let buf = match buffer.try_lock() {
+    Ok(buf) => buf,
+    Err(TryLockError::Poisoned(err)) => {
+      out.write_all(b"<flight recorder: buffer poisoned by an earlier panic>\n")?;
+      err.into_inner()
+    }
+    Err(TryLockError::WouldBlock) => {
+      out.write_all(b"<flight recorder: buffer locked while panicking>\n")?;
+      return out.flush();
+    }
+  };
+
+  let (front, back) = buf.as_slices();
+  out.write_all(front)?;
+  out.write_all(back)?;
+  out.flush()
+}
@@ -0,0 +1,86 @@
+use crate::{Buffer, View};
+
+/// The length prefix of the frames parsed by [`Buffer::frames`].
+///
+/// The prefix encodes the length of the payload which follows it, excluding the prefix.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LengthPrefix {
+  /// A big-endian `u16` prefix.
+  U16Be,
+  /// A little-endian `u16` prefix.
+  U16Le,
+  /// A big-endian `u32` prefix.
+  U32Be,
+  /// A little-endian `u32` prefix.
+  U32Le,
+}
+
+impl LengthPrefix {
+  /// Returns the number of bytes of the prefix.
+  #[inline]
+  pub const fn width(self) -> usize {
+    match self {
+      Self::U16Be | Self::U16Le => 2,
+      Self::U32Be | Self::U32Le => 4,
+    }
+  }
+
+  /// Returns the end of the frame starting at `at` in `view`, if it is complete.
+  fn frame_end(self, view: &View<'_, u8>, at: usize) -> Option<usize> {
+    let mut b = [0u8; 4];
+    for (i, byte) in b[..self.width()].iter_mut().enumerate() {
+      *byte = *view.get(at + i)?;
+    }
+
+    let len = match self {
+      Self::U16Be => u16::from_be_bytes([b[0], b[1]]) as u64,
+      Self::U16Le => u16::from_le_bytes([b[0], b[1]]) as u64,
+      Self::U32Be => u32::from_be_bytes(b) as u64,
+      Self::U32Le => u32::from_le_bytes(b) as u64,
+    };
+    let end = usize::try_from(len).ok()?.checked_add(at + self.width())?;
+    (end <= view.len()).then_some(end)
+  }
+}
+
+impl<B> Buffer<B> {
+  /// Returns an iterator over the payloads of the length-prefixed frames in the unread
+  /// bytes, from the oldest to the newest, without allocating.
+  ///
+  /// Once older data has been overwritten, the unread bytes usually start in the middle
+  /// of a frame. This torn frame is skipped: parsing starts at the first offset holding
+  /// a complete frame, and stops at the first frame which is not complete, e.g. the newest
+  /// one if it is only partially written. The torn bytes cannot always be told apart from
+  /// a frame, so they may yield a spurious one if they happen to look like a complete frame.
+  /// Frames spanning the wrap point are yielded as a single [`View`]. Nothing is consumed.
+  ///
+  /// ```rust
+  /// use circularbuf::{Buffer, LengthPrefix};
+  ///
+  /// let mut buf = Buffer::new([0u8; 10]);
+  /// buf.write(b"\x00\x03abc");
+  /// buf.write(b"\x00\x02de");
+  /// buf.write(b"\x00\x01f");
+  ///
+  /// // The first frame was partially overwritten
+  /// let frames = buf.frames(LengthPrefix::U16Be).collect::<Vec<_>>();
+  /// assert_eq!(frames, [&b"de"[..], b"f"]);
+  /// ```
+  pub fn frames(&self, prefix: LengthPrefix) -> impl Iterator<Item = View<'_, u8>> + '_
+  where
+    B: AsRef<[u8]>,
+  {
+    let (front, back) = self.as_slices();
+    let view = View::new(front, back);
+    let mut at = (0..view.len())
+      .find(|&at| prefix.frame_end(&view, at).is_some())
+      .unwrap_or(view.len());
+
+    core::iter::from_fn(move || {
+      let end = prefix.frame_end(&view, at)?;
+      let frame = view.split_at(end).0.split_at(at + prefix.width()).1;
+      at = end;
+      Some(frame)
+    })
+  }
+}
@@ -0,0 +1,112 @@
+use core::hash::{Hash, Hasher};
+
+use crate::{Buffer, View};
+
+/// The 64 bits FNV-1a hasher: stable across platforms and releases, and independent of
+/// how the input is split across `write` calls.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+  #[inline]
+  fn default() -> Self {
+    Self(0xcbf2_9ce4_8422_2325)
+  }
+}
+
+impl Hasher for Fnv1a {
+  #[inline]
+  fn finish(&self) -> u64 {
+    self.0
+  }
+
+  #[inline]
+  fn write(&mut self, bytes: &[u8]) {
+    self.0 = bytes.iter().fold(self.0, |hash, &b| {
+      (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    });
+  }
+}
+
+/// Hashes the unread bytes in logical order, so buffers holding the same data hash
+/// identically regardless of their cursors.
+impl<B> Hash for Buffer<B>
+where
+  B: AsRef<[u8]>,
+{
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    state.write_usize(self.len);
+    for b in self.iter::<u8>() {
+      state.write_u8(b);
+    }
+  }
+}
+
+/// Compares the unread bytes in logical order, see [`Buffer::content_eq`].
+impl<B, B2> PartialEq<Buffer<B2>> for Buffer<B>
+where
+  B: AsRef<[u8]>,
+  B2: AsRef<[u8]>,
+{
+  #[inline]
+  fn eq(&self, other: &Buffer<B2>) -> bool {
+    self.content_eq(other)
+  }
+}
+
+impl<B> Eq for Buffer<B> where B: AsRef<[u8]> {}
+
+impl<B> Buffer<B> {
+  /// Returns `true` if both buffers hold the same unread data, in the same logical order,
+  /// regardless of their capacity, cursors, or where the data wraps around.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut a = Buffer::new([0u32; 3]);
+  /// a.write(&[1, 2, 3, 4]);
+  ///
+  /// let mut b = Buffer::new(vec![0u32; 8]);
+  /// b.write(&[2, 3, 4]);
+  ///
+  /// assert!(a.content_eq(&b));
+  /// ```
+  pub fn content_eq<T, B2>(&self, other: &Buffer<B2>) -> bool
+  where
+    B: AsRef<[T]>,
+    B2: AsRef<[T]>,
+    T: PartialEq,
+  {
+    let (a, b) = (self.as_slices(), other.as_slices());
+    View::new(a.0, a.1) == View::new(b.0, b.1)
+  }
+
+  /// Returns the 64 bits FNV-1a hash of the unread bytes, in logical order.
+  ///
+  /// Unlike [`Hash`], the result is stable across platforms and releases, and does not
+  /// depend on where the data wraps around.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut a = Buffer::new([0u8; 4]);
+  /// a.write(b"ab");
+  /// a.write(b"cdef");
+  ///
+  /// let mut b = Buffer::new([0u8; 8]);
+  /// b.write(b"cdef");
+  ///
+  /// assert_ne!(a.as_slices(), b.as_slices());
+  /// assert_eq!(a.content_hash(), b.content_hash());
+  /// ```
+  pub fn content_hash(&self) -> u64
+  where
+    B: AsRef<[u8]>,
+  {
+    let (front, back) = self.as_slices();
+    let mut hasher = Fnv1a::default();
+    hasher.write(front);
+    hasher.write(back);
+    hasher.finish()
+  }
+}
@@ -0,0 +1,160 @@
+use core::hash::Hasher;
+use std::{
+  fs::{File, OpenOptions},
+  io::{self, Read},
+  path::Path,
+};
+
+use crate::hash::Fnv1a;
+
+/// The size of a header slot.
+pub(crate) const SLOT: usize = 64;
+
+/// The size of the on-disk header, made of two slots written alternately, so a torn
+/// header write always leaves the previous state recoverable.
+pub(crate) const LEN: usize = 2 * SLOT;
+
+const MAGIC: [u8; 4] = *b"CBUF";
+const VERSION: u32 = 1;
+/// The size of the encoded fields covered by the checksum.
+const FIELDS: usize = 48;
+
+/// The state of a persistent ring, as stored on disk.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Header {
+  /// Incremented on every update, the slot with the highest sequence wins on recovery.
+  pub(crate) seq: u64,
+  pub(crate) capacity: usize,
+  pub(crate) cursor: usize,
+  pub(crate) written: usize,
+  pub(crate) len: usize,
+}
+
+impl Header {
+  /// Returns the header describing `buf`.
+  #[cfg(feature = "mmap")]
+  #[inline]
+  pub(crate) fn of<B: AsRef<[u8]>>(buf: &crate::Buffer<B>, seq: u64) -> Self {
+    Self {
+      seq,
+      capacity: buf.size(),
+      cursor: buf.write_cursor,
+      written: buf.written,
+      len: buf.len,
+    }
+  }
+
+  /// Returns the offset of the slot this header is stored in.
+  #[inline]
+  pub(crate) const fn slot(&self) -> usize {
+    (self.seq % 2) as usize * SLOT
+  }
+
+  pub(crate) fn encode(&self) -> [u8; SLOT] {
+    let mut out = [0; SLOT];
+    out[..4].copy_from_slice(&MAGIC);
+    out[4..8].copy_from_slice(&VERSION.to_le_bytes());
+    out[8..16].copy_from_slice(&self.seq.to_le_bytes());
+    out[16..24].copy_from_slice(&(self.capacity as u64).to_le_bytes());
+    out[24..32].copy_from_slice(&(self.cursor as u64).to_le_bytes());
+    out[32..40].copy_from_slice(&(self.written as u64).to_le_bytes());
+    out[40..48].copy_from_slice(&(self.len as u64).to_le_bytes());
+    let checksum = checksum(&out[..FIELDS]);
+    out[FIELDS..FIELDS + 8].copy_from_slice(&checksum.to_le_bytes());
+    out
+  }
+
+  /// Decodes a slot, returns `None` if it is empty, torn or corrupted.
+  fn decode(slot: &[u8]) -> Option<Self> {
+    let u64_at = |at: usize| u64::from_le_bytes(slot[at..at + 8].try_into().unwrap());
+    if slot[..4] != MAGIC
+      || slot[4..8] != VERSION.to_le_bytes()
+      || u64_at(FIELDS) != checksum(&slot[..FIELDS])
+    {
+      return None;
+    }
+
+    let header = Self {
+      seq: u64_at(8),
+      capacity: usize::try_from(u64_at(16)).ok()?,
+      cursor: usize::try_from(u64_at(24)).ok()?,
+      written: usize::try_from(u64_at(32)).ok()?,
+      len: usize::try_from(u64_at(40)).ok()?,
+    };
+    let valid =
+      header.cursor < header.capacity.max(1) && header.len <= header.capacity.min(header.written);
+    valid.then_some(header)
+  }
+
+  /// Recovers the most recent valid header from the [`LEN`] bytes of header.
+  fn recover(header: &[u8]) -> Option<Self> {
+    let a = Self::decode(&header[..SLOT]);
+    let b = Self::decode(&header[SLOT..LEN]);
+    match (a, b) {
+      (Some(a), Some(b)) => Some(if a.seq > b.seq { a } else { b }),
+      (a, b) => a.or(b),
+    }
+  }
+
+  /// Rebuilds a buffer described by this header on top of `data`.
+  #[cfg(feature = "mmap")]
+  pub(crate) fn restore<B>(&self, data: B) -> crate::Buffer<B> {
+    crate::Buffer {
+      data,
+      write_cursor: self.cursor,
+      written: self.written,
+      len: self.len,
+      // Bytes beyond the unread ones may have been torn by an interrupted write
+      retained: self.len,
+      stats: Default::default(),
+    }
+  }
+}
+
+/// FNV-1a, enough to detect torn or garbage slots.
+fn checksum(bytes: &[u8]) -> u64 {
+  let mut hasher = Fnv1a::default();
+  hasher.write(bytes);
+  hasher.finish()
+}
+
+/// Opens the file of a persistent ring holding `capacity` bytes, creating it if
+/// it does not exist, returns the file and whether it is fresh.
+///
+/// A file whose header was never written, e.g. after a crash right after sizing it,
+/// is fresh as well.
+pub(crate) fn open(path: &Path, capacity: usize) -> io::Result<(File, bool)> {
+  let file = OpenOptions::new()
+    .read(true)
+    .write(true)
+    .create(true)
+    .truncate(false)
+    .open(path)?;
+
+  let size = (LEN + capacity) as u64;
+  match file.metadata()?.len() {
+    0 => {
+      file.set_len(size)?;
+      Ok((file, true))
+    }
+    len if len == size => {
+      let mut header = [0; LEN];
+      (&file).read_exact(&mut header)?;
+      let fresh = header.iter().all(|&b| b == 0);
+      Ok((file, fresh))
+    }
+    _ => Err(invalid_data("file size does not match the capacity")),
+  }
+}
+
+/// Recovers the header of an existing ring holding `capacity` bytes.
+pub(crate) fn recover(header: &[u8], capacity: usize) -> io::Result<Header> {
+  Header::recover(header)
+    .filter(|header| header.capacity == capacity)
+    .ok_or_else(|| invalid_data("corrupted or incompatible header"))
+}
+
+#[inline]
+fn invalid_data(msg: &'static str) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, msg)
+}
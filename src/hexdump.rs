@@ -0,0 +1,50 @@
+use core::fmt;
+
+use crate::View;
+
+/// The number of bytes per row.
+const ROW: usize = 16;
+
+/// Formats bytes as offset, hex and ASCII columns, returned by [`Buffer::hexdump`](crate::Buffer::hexdump).
+pub(crate) struct Hexdump<'a>(pub(crate) View<'a, u8>);
+
+impl fmt::Display for Hexdump<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut bytes = self.0.iter().copied();
+    let mut offset = 0;
+    loop {
+      let mut row = [0u8; ROW];
+      let n = row
+        .iter_mut()
+        .zip(&mut bytes)
+        .map(|(dst, b)| *dst = b)
+        .count();
+      if n == 0 {
+        return Ok(());
+      }
+
+      write!(f, "{offset:08x} ")?;
+      for (i, b) in row.iter().enumerate() {
+        if i == ROW / 2 {
+          f.write_str(" ")?;
+        }
+        match i < n {
+          true => write!(f, " {b:02x}")?,
+          false => f.write_str("   ")?,
+        }
+      }
+
+      f.write_str("  |")?;
+      for &b in &row[..n] {
+        let c = if b.is_ascii_graphic() || b == b' ' {
+          b as char
+        } else {
+          '.'
+        };
+        write!(f, "{c}")?;
+      }
+      writeln!(f, "|")?;
+      offset += n;
+    }
+  }
+}
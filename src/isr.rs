@@ -0,0 +1,94 @@
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+
+use crate::Buffer;
+
+/// A circular buffer guarded by a [critical section](critical_section),
+/// shareable between interrupt handlers and the main loop on bare-metal targets.
+///
+/// All constructors are `const`, so it can be placed in a `static`.
+///
+/// ```rust
+/// use circularbuf::IsrBuffer;
+///
+/// static UART_RX: IsrBuffer<[u8; 64]> = IsrBuffer::new([0; 64]);
+///
+/// // In the interrupt handler
+/// UART_RX.write(b"AT+OK\r\n");
+///
+/// // In the main loop
+/// let mut line = [0u8; 64];
+/// let n = UART_RX.read(&mut line);
+/// assert_eq!(&line[..n], b"AT+OK\r\n");
+/// ```
+#[derive(Debug)]
+pub struct IsrBuffer<B> {
+  inner: Mutex<RefCell<Buffer<B>>>,
+}
+
+impl<B> IsrBuffer<B> {
+  /// Creates a new interrupt-safe buffer with the given data.
+  #[inline]
+  pub const fn new(data: B) -> Self {
+    Self::from_buffer(Buffer::new(data))
+  }
+
+  /// Creates a new interrupt-safe buffer from a [`Buffer`].
+  #[inline]
+  pub const fn from_buffer(buf: Buffer<B>) -> Self {
+    Self {
+      inner: Mutex::new(RefCell::new(buf)),
+    }
+  }
+
+  /// Runs `f` with exclusive access to the buffer, inside a critical section.
+  #[inline]
+  pub fn with<R>(&self, f: impl FnOnce(&mut Buffer<B>) -> R) -> R {
+    critical_section::with(|cs| f(&mut self.inner.borrow_ref_mut(cs)))
+  }
+
+  /// Writes up to len(buf) elements to the internal ring,
+  /// overriding older data if necessary.
+  #[inline]
+  pub fn write<T>(&self, buf: &[T]) -> usize
+  where
+    B: AsMut<[T]>,
+    T: Copy,
+  {
+    self.with(|b| b.write(buf))
+  }
+
+  /// Reads up to `dst.len()` of the oldest unread elements into `dst` and consumes them,
+  /// returns number of elements readed.
+  #[inline]
+  pub fn read<T>(&self, dst: &mut [T]) -> usize
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    self.with(|b| b.read(dst))
+  }
+
+  /// Copies the most recent `n` elements into `dst`, returns number of elements readed.
+  #[inline]
+  pub fn tail<T>(&self, n: usize, dst: &mut [T]) -> usize
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    self.with(|b| b.tail(n, dst))
+  }
+
+  /// Returns how many elements are pending.
+  #[inline]
+  pub fn unread(&self) -> usize {
+    self.with(|b| b.unread())
+  }
+
+  /// Consumes the wrapper and returns the inner [`Buffer`].
+  #[inline]
+  pub fn into_inner(self) -> Buffer<B> {
+    self.inner.into_inner().into_inner()
+  }
+}
@@ -3,7 +3,28 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![cfg_attr(docsrs, allow(unused_attributes))]
 #![deny(missing_docs)]
-#![forbid(unsafe_code)]
+#![cfg_attr(
+  not(any(
+    feature = "bytes",
+    feature = "mmap",
+    feature = "rkyv",
+    feature = "shmem",
+    feature = "uninit",
+    feature = "unsafe-perf"
+  )),
+  forbid(unsafe_code)
+)]
+#![cfg_attr(
+  any(
+    feature = "bytes",
+    feature = "mmap",
+    feature = "rkyv",
+    feature = "shmem",
+    feature = "uninit",
+    feature = "unsafe-perf"
+  ),
+  deny(unsafe_code)
+)]
 
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
 extern crate alloc as std;
@@ -11,262 +32,2739 @@ extern crate alloc as std;
 #[cfg(feature = "std")]
 extern crate std;
 
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use audit::AuditBuffer;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use blocking::BlockingBuffer;
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+pub use checkpoint::Checkpoint;
+#[cfg(feature = "deflate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "deflate")))]
+pub use compressed::CompressedBuffer;
+#[cfg(feature = "hex")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hex")))]
+pub use error::InvalidHex;
+pub use error::{Full, Gap, Lagged, ReadError, TooLarge};
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use file::FileBuffer;
+pub use frame::LengthPrefix;
+#[cfg(feature = "critical-section")]
+#[cfg_attr(docsrs, doc(cfg(feature = "critical-section")))]
+pub use isr::IsrBuffer;
+pub use line::LineBuffer;
+#[cfg(feature = "log")]
+#[cfg_attr(docsrs, doc(cfg(feature = "log")))]
+pub use logger::RingLogger;
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub use metered::MeteredBuffer;
+#[cfg(feature = "mmap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+pub use mmap::{MmapBuffer, MmapRegion};
+#[cfg(all(any(feature = "std", feature = "alloc"), target_has_atomic = "ptr"))]
+#[cfg_attr(
+  docsrs,
+  doc(cfg(all(any(feature = "std", feature = "alloc"), target_has_atomic = "ptr")))
+)]
+pub use mpsc::MpscBuffer;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use pool::{BufferPool, PooledBuffer};
+pub use reader::Reader;
+pub use record::RecordBuffer;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub use serialize::DeserializeStorage;
+#[cfg(feature = "shmem")]
+#[cfg_attr(docsrs, doc(cfg(feature = "shmem")))]
+pub use shmem::{ShmemBuffer, ShmemReader};
+pub use slot::SlotBuffer;
+#[cfg(all(any(feature = "std", feature = "alloc"), target_has_atomic = "ptr"))]
+#[cfg_attr(
+  docsrs,
+  doc(cfg(all(any(feature = "std", feature = "alloc"), target_has_atomic = "ptr")))
+)]
+pub use snapshot::FrozenBuffer;
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+pub use snapshot::Snapshot;
+#[cfg(all(any(feature = "std", feature = "alloc"), target_has_atomic = "ptr"))]
+#[cfg_attr(
+  docsrs,
+  doc(cfg(all(any(feature = "std", feature = "alloc"), target_has_atomic = "ptr")))
+)]
+pub use spsc::{Consumer, Observer, Producer};
+#[cfg(all(feature = "futures-core", feature = "bytes"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "future", feature = "bytes"))))]
+pub use sync::Follow;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use sync::{Subscriber, SyncBuffer};
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use tee::TeeWriter;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use timed::StdClock;
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+pub use timed::{Clock, TimedBuffer};
+#[cfg(feature = "uninit")]
+#[cfg_attr(docsrs, doc(cfg(feature = "uninit")))]
+pub use uninit::{UninitBuffer, UninitStorage};
+pub use view::{View, ViewReader};
+pub use watermark::{Crossing, WatermarkBuffer};
+
+#[cfg(feature = "std")]
+mod audit;
+#[cfg(feature = "std")]
+mod blocking;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod checkpoint;
+#[cfg(feature = "deflate")]
+mod compressed;
+mod display;
+mod error;
+#[cfg(feature = "std")]
+mod file;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod flight_recorder;
+mod frame;
+mod hash;
+#[cfg(feature = "std")]
+mod header;
+mod hexdump;
+#[cfg(feature = "critical-section")]
+mod isr;
+mod line;
+#[cfg(feature = "log")]
+mod logger;
+#[cfg(feature = "metrics")]
+mod metered;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(all(any(feature = "std", feature = "alloc"), target_has_atomic = "ptr"))]
+mod mpsc;
+#[cfg(feature = "std")]
+mod pool;
+mod reader;
+mod record;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(all(any(feature = "std", feature = "alloc"), target_has_atomic = "ptr"))]
+mod shared;
+#[cfg(feature = "shmem")]
+mod shmem;
+mod slot;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod snapshot;
+#[cfg(all(any(feature = "std", feature = "alloc"), target_has_atomic = "ptr"))]
+mod spsc;
+#[cfg(feature = "proptest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "proptest")))]
+pub mod strategies;
+#[cfg(feature = "std")]
+mod sync;
+#[cfg(feature = "std")]
+mod tee;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod timed;
+#[cfg(feature = "uninit")]
+mod uninit;
+mod view;
+mod watermark;
+
 /// A circular buffer. It is a fixed size,
 /// and new writes overwrite older data, such that for a buffer
 /// of size N, for any amount of writes, only the last N bytes
 /// are retained.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+///
+/// The element type is not limited to `u8`, any `T: Copy` works as long as
+/// the storage implements `AsRef<[T]>`/`AsMut<[T]>`. The I/O trait implementations
+/// are only available for byte buffers.
+///
+/// With the `serde` feature enabled, byte buffers (de)serialize their full state,
+/// including the cursors, so a restored buffer has identical read semantics.
+/// Human-readable formats such as JSON get the unread bytes as a base64 string instead
+/// of the raw storage, along with explicit `capacity` and `written` fields.
+/// The `rkyv` feature does the same for rkyv archives, whose unread bytes can be accessed
+/// without deserializing, see [`ArchivedBuffer::as_slices`].
+///
+/// When the capacity is a power of two, the cursor arithmetic uses a bitmask instead
+/// of a division, see [`with_capacity_pow2`](Buffer::with_capacity_pow2).
+///
+/// Byte buffers compare and hash by their unread bytes in logical order, regardless of
+/// their storage type or where the data wraps around.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(
+  feature = "rkyv",
+  derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+  rkyv(bytecheck(verify))
+)]
 pub struct Buffer<B> {
   data: B,
   write_cursor: usize,
   written: usize,
+  len: usize,
+  /// The number of elements ending at the cursor which hold stream history, read or not.
+  /// It is at least `len`, and at most the size and `written`.
+  retained: usize,
+  stats: Counters,
+}
+
+/// A circular buffer whose storage is an inline `[u8; N]` array.
+///
+/// All constructors are `const`, so it can be used in `static`s and `const`s.
+///
+/// ```rust
+/// use circularbuf::InlineBuffer;
+///
+/// static LOG: InlineBuffer<512> = InlineBuffer::new_zeroed();
+///
+/// assert_eq!(LOG.size(), 512);
+/// ```
+pub type InlineBuffer<const N: usize> = Buffer<[u8; N]>;
+
+impl<const N: usize> Buffer<[u8; N]> {
+  /// Creates a new buffer backed by a zeroed `[u8; N]` array.
+  #[inline]
+  pub const fn new_zeroed() -> Self {
+    Self::new([0; N])
+  }
+}
+
+/// Creates an empty buffer backed by an array of default elements, for any `N`.
+///
+/// ```rust
+/// use circularbuf::Buffer;
+///
+/// #[derive(Default)]
+/// struct Connection {
+///   id: u64,
+///   tail: Buffer<[u8; 256]>,
+/// }
+///
+/// let conn = Connection::default();
+/// assert_eq!(conn.tail.size(), 256);
+/// assert!(conn.tail.is_empty());
+/// ```
+impl<T, const N: usize> Default for Buffer<[T; N]>
+where
+  T: Copy + Default,
+{
+  #[inline]
+  fn default() -> Self {
+    Self::new([T::default(); N])
+  }
+}
+
+/// The counters a [`Buffer`] maintains on every write, from which [`Stats`] are built.
+///
+/// The number of elements written is not among them, it is [`Buffer::written`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+  feature = "rkyv",
+  derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub(crate) struct Counters {
+  writes: usize,
+  overwritten: usize,
+  wraps: usize,
+  largest_write: usize,
+}
+
+impl Counters {
+  pub(crate) const fn new() -> Self {
+    Self {
+      writes: 0,
+      overwritten: 0,
+      wraps: 0,
+      largest_write: 0,
+    }
+  }
+}
+
+/// Counters describing the writes into a [`Buffer`], returned by [`Buffer::stats`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+  feature = "rkyv",
+  derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+pub struct Stats {
+  writes: usize,
+  written: usize,
+  overwritten: usize,
+  wraps: usize,
+  largest_write: usize,
+}
+
+impl Stats {
+  /// Returns the number of writes.
+  #[inline]
+  pub const fn writes(&self) -> usize {
+    self.writes
+  }
+
+  /// Returns the total number of elements written, like [`Buffer::written`].
+  #[inline]
+  pub const fn written(&self) -> usize {
+    self.written
+  }
+
+  /// Returns the number of unread elements which were overwritten before they could be
+  /// read, like [`WriteReport::overwritten`].
+  ///
+  /// The leading part of a write larger than the buffer is not counted, it can be
+  /// derived from the writes and the buffer size.
+  #[inline]
+  pub const fn overwritten(&self) -> usize {
+    self.overwritten
+  }
+
+  /// Returns the number of times the write cursor wrapped around the end of the storage.
+  ///
+  /// A write longer than the storage wraps once per capacity it spans, as if all of its
+  /// elements were stored, so a buffer only ever written to has wrapped
+  /// `written / capacity` times.
+  #[inline]
+  pub const fn wraps(&self) -> usize {
+    self.wraps
+  }
+
+  /// Returns the length of the largest single write.
+  #[inline]
+  pub const fn largest_write(&self) -> usize {
+    self.largest_write
+  }
+}
+
+/// The outcome of a [`Buffer::write_with_report`] call.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct WriteReport {
+  written: usize,
+  overwritten: usize,
+}
+
+impl WriteReport {
+  /// Returns the number of elements written, i.e. the length of the input.
+  #[inline]
+  pub const fn written(&self) -> usize {
+    self.written
+  }
+
+  /// Returns the number of previously retained elements that were overwritten.
+  #[inline]
+  pub const fn overwritten(&self) -> usize {
+    self.overwritten
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+impl Buffer<std::boxed::Box<[u8]>> {
+  /// Creates a new heap allocated buffer with a zeroed storage of `capacity` bytes.
+  #[inline]
+  pub fn with_capacity(capacity: usize) -> Self {
+    Self::new(std::vec![0; capacity].into_boxed_slice())
+  }
+
+  /// Creates a new heap allocated buffer with a zeroed storage of at least `min_capacity`
+  /// bytes, rounded up to the next power of two.
+  ///
+  /// With a power of two capacity, the cursor arithmetic uses a bitmask instead of
+  /// a division.
+  ///
+  /// ## Panics
+  ///
+  /// Panics if the rounded capacity overflows `usize`.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let buf = Buffer::with_capacity_pow2(1000);
+  /// assert_eq!(buf.size(), 1024);
+  /// ```
+  #[inline]
+  pub fn with_capacity_pow2(min_capacity: usize) -> Self {
+    let capacity = min_capacity
+      .checked_next_power_of_two()
+      .expect("capacity overflow");
+    Self::with_capacity(capacity)
+  }
+}
+
+#[cfg(feature = "allocator-api2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "allocator-api2")))]
+impl<A> Buffer<allocator_api2::boxed::Box<[u8], A>>
+where
+  A: allocator_api2::alloc::Allocator,
+{
+  /// Creates a new buffer with a zeroed storage of `capacity` bytes, allocated by `alloc`,
+  /// so the ring can live in an arena, or in huge pages for a large flight recorder.
+  ///
+  /// The allocator API is provided by the `allocator-api2` crate, which re-exports the
+  /// nightly `allocator_api` when its `nightly` feature is enabled.
+  ///
+  /// ```rust
+  /// use allocator_api2::alloc::Global;
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::with_capacity_in(4, Global);
+  /// buf.write(b"hello");
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b"ello");
+  /// ```
+  pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+    let mut data = allocator_api2::vec::Vec::with_capacity_in(capacity, alloc);
+    data.resize(capacity, 0);
+    Self::new(data.into_boxed_slice())
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<T: Copy + Default> Buffer<std::boxed::Box<[T]>> {
+  /// Grows or shrinks the storage to `new_capacity` elements, keeping the most recent
+  /// elements that fit. Those not read yet stay unread, the others stay reachable by
+  /// [`read_since`](Buffer::read_since).
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::with_capacity(4);
+  /// buf.write(b"hello");
+  ///
+  /// buf.resize(8);
+  /// buf.write(b"!!!");
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b"ello!!!");
+  ///
+  /// buf.resize(2);
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b"!!");
+  /// ```
+  #[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+  pub fn resize(&mut self, new_capacity: usize) {
+    self.data = self.resized(new_capacity).into_boxed_slice();
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<T: Copy + Default> Buffer<std::vec::Vec<T>> {
+  /// Grows or shrinks the storage to `new_capacity` elements, keeping the most recent
+  /// elements that fit. Those not read yet stay unread, the others stay reachable by
+  /// [`read_since`](Buffer::read_since).
+  #[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+  pub fn resize(&mut self, new_capacity: usize) {
+    self.data = self.resized(new_capacity);
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+impl<T> Buffer<std::boxed::Box<[T]>> {
+  /// Creates a full buffer whose capacity is the length of the deque, and whose unread
+  /// elements are the elements of the deque, in the same order.
+  ///
+  /// This is also available as a `From` conversion, which needs a type annotation
+  /// as any type converts into a `Buffer`.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  /// use std::collections::VecDeque;
+  ///
+  /// let mut deque = VecDeque::from(b"world".to_vec());
+  /// deque.push_front(b' ');
+  ///
+  /// let buf = Buffer::from_vecdeque(deque.clone());
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b" world");
+  /// assert_eq!(buf.into_vecdeque(), deque);
+  /// ```
+  pub fn from_vecdeque(deque: std::collections::VecDeque<T>) -> Self {
+    Self::new_full(std::vec::Vec::from(deque).into_boxed_slice())
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+impl<T> From<std::collections::VecDeque<T>> for Buffer<std::boxed::Box<[T]>> {
+  #[inline]
+  fn from(deque: std::collections::VecDeque<T>) -> Self {
+    Self::from_vecdeque(deque)
+  }
+}
+
+impl<B> From<B> for Buffer<B> {
+  fn from(data: B) -> Self {
+    Self {
+      data,
+      write_cursor: 0,
+      written: 0,
+      len: 0,
+      retained: 0,
+      stats: Counters::new(),
+    }
+  }
+}
+
+/// Indexes the unread bytes by logical position, relative to the oldest unread byte.
+///
+/// Use [`Buffer::range`] to address a range, which may wrap around.
+impl<B> core::ops::Index<usize> for Buffer<B>
+where
+  B: AsRef<[u8]>,
+{
+  type Output = u8;
+
+  #[inline]
+  fn index(&self, i: usize) -> &u8 {
+    match self.get_ref(i) {
+      Some(b) => b,
+      None => panic!(
+        "index {i} out of bounds for a buffer holding {} bytes",
+        self.len
+      ),
+    }
+  }
+}
+
+/// Writes the bytes of the iterator in order, like [`write`](Buffer::write).
+///
+/// The bytes are stored straight into the ring, and the cursor is updated once at the end.
+///
+/// ```rust
+/// use circularbuf::Buffer;
+///
+/// let mut buf = Buffer::new([0u8; 4]);
+/// buf.extend(b"hello".iter().map(u8::to_ascii_uppercase));
+/// assert_eq!(buf.read_to_bytes().as_ref(), b"ELLO");
+/// ```
+impl<B> Extend<u8> for Buffer<B>
+where
+  B: AsMut<[u8]>,
+{
+  fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+    if self.data.as_mut().is_empty() {
+      let n = iter.into_iter().count();
+      self.record_write(n, 0);
+      self.written += n;
+      return;
+    }
+
+    let data = self.data.as_mut();
+    let size = data.len();
+
+    let mut pos = self.write_cursor;
+    let mut n = 0;
+    for item in iter {
+      data[pos] = item;
+      pos = if pos + 1 == size { 0 } else { pos + 1 };
+      n += 1;
+    }
+    self.advance_write(n);
+  }
+}
+
+impl<'a, B> Extend<&'a u8> for Buffer<B>
+where
+  B: AsMut<[u8]>,
+{
+  #[inline]
+  fn extend<I: IntoIterator<Item = &'a u8>>(&mut self, iter: I) {
+    self.extend(iter.into_iter().copied());
+  }
 }
 
-impl<B> From<B> for Buffer<B> {
-  fn from(data: B) -> Self {
-    Self {
-      data,
-      write_cursor: 0,
-      written: 0,
+impl<B> Buffer<B> {
+  /// Creates a new buffer with the given data.
+  #[inline]
+  pub const fn new(data: B) -> Self {
+    Self {
+      data,
+      write_cursor: 0,
+      written: 0,
+      len: 0,
+      retained: 0,
+      stats: Counters::new(),
+    }
+  }
+
+  /// Creates a new buffer treating the whole storage as already written, in order,
+  /// so all of it is unread and the next write overwrites its start.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new_full(*b"hello");
+  /// assert_eq!(buf.written(), 5);
+  ///
+  /// buf.write(b"!");
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b"ello!");
+  /// ```
+  #[inline]
+  pub fn new_full<T>(data: B) -> Self
+  where
+    B: AsRef<[T]>,
+  {
+    let len = data.as_ref().len();
+    Self::from_filled(data, len)
+  }
+
+  /// Creates a new buffer treating the first `filled` elements of the storage as already
+  /// written, in order, so the next write appends right after them.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `filled` is larger than the size of the storage.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::from_filled(*b"hi\0\0\0", 2);
+  /// buf.write(b" you");
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b"i you");
+  /// ```
+  pub fn from_filled<T>(data: B, filled: usize) -> Self
+  where
+    B: AsRef<[T]>,
+  {
+    let size = data.as_ref().len();
+    assert!(
+      filled <= size,
+      "filled length {filled} exceeds the buffer size {size}"
+    );
+    Self {
+      data,
+      write_cursor: if filled == size { 0 } else { filled },
+      written: filled,
+      len: filled,
+      retained: filled,
+      stats: Counters::new(),
+    }
+  }
+
+  /// Writes up to len(buf) bytes to the internal ring,
+  /// overriding older data if necessary.
+  ///
+  /// With the `unsafe-perf` feature enabled, the copies into the storage are not
+  /// bounds checked.
+  pub fn write<T>(&mut self, buf: &[T]) -> usize
+  where
+    B: AsMut<[T]>,
+    T: Copy,
+  {
+    let n = buf.len();
+    let size = self.data.as_mut().len();
+    self.record_write(n, size);
+    self.store(buf)
+  }
+
+  /// Copies `buf` in place at the cursor, without accounting for it in the statistics.
+  fn store<T>(&mut self, buf: &[T]) -> usize
+  where
+    B: AsMut<[T]>,
+    T: Copy,
+  {
+    let n = buf.len();
+    // `AsMut` is not trusted to return the same storage on every call, so the size
+    // bounding the copies below must come from this very slice
+    let data = self.data.as_mut();
+    let size = data.len();
+    self.written += n;
+    self.len = (self.len + n).min(size);
+    self.retained = (self.retained + n).min(size);
+    if size == 0 {
+      return n;
+    }
+
+    // Only the last `size` elements end up retained
+    let buf = &buf[n.saturating_sub(size)..];
+    let cursor = self.write_cursor;
+
+    // Copy in place, in at most two chunks: up to the end of the storage, then from its start
+    assert!(cursor < size, "write cursor out of bounds");
+    let remain = size - cursor;
+    if buf.len() <= remain {
+      copy_at(data, cursor, buf);
+    } else {
+      let (first, second) = buf.split_at(remain);
+      copy_at(data, cursor, first);
+      copy_at(data, 0, second);
+    }
+
+    // Update location of the cursor
+    self.write_cursor = wrap(cursor + buf.len(), size);
+    n
+  }
+
+  /// Writes all of `bufs` in order, as if they were concatenated, returns the total
+  /// number of elements written.
+  ///
+  /// The retained window is computed across all the slices first, so elements which
+  /// would be immediately overwritten by a later slice are never copied.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 6]);
+  /// assert_eq!(buf.write_vectored(&[b"hello", b" ", b"world"]), 11);
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b" world");
+  /// ```
+  #[inline]
+  pub fn write_vectored<T>(&mut self, bufs: &[&[T]]) -> usize
+  where
+    B: AsMut<[T]>,
+    T: Copy,
+  {
+    self.write_slices(bufs.iter().copied())
+  }
+
+  fn write_slices<'a, T>(&mut self, bufs: impl Iterator<Item = &'a [T]> + Clone) -> usize
+  where
+    B: AsMut<[T]>,
+    T: Copy + 'a,
+  {
+    let total = bufs.clone().map(<[T]>::len).sum::<usize>();
+    let size = self.data.as_mut().len();
+    self.record_write(total, size);
+
+    // Only the last `size` elements end up retained, skip the rest.
+    let mut skip = total.saturating_sub(size);
+    self.written += skip;
+    for buf in bufs {
+      let n = skip.min(buf.len());
+      skip -= n;
+      if n < buf.len() {
+        self.store(&buf[n..]);
+      }
+    }
+    total
+  }
+
+  /// Writes up to len(buf) bytes to the internal ring like [`write`](Buffer::write),
+  /// and reports how many previously retained elements were overwritten.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 4]);
+  /// buf.write(b"abc");
+  ///
+  /// let report = buf.write_with_report(b"de");
+  /// assert_eq!(report.written(), 2);
+  /// assert_eq!(report.overwritten(), 1);
+  /// ```
+  pub fn write_with_report<T>(&mut self, buf: &[T]) -> WriteReport
+  where
+    B: AsMut<[T]>,
+    T: Copy,
+  {
+    let size = self.data.as_mut().len();
+    let overwritten = self.overwritten_by(buf.len(), size);
+    WriteReport {
+      written: self.write(buf),
+      overwritten,
+    }
+  }
+
+  /// Writes as much of `buf` as fits in the free space, never overwriting unread data.
+  ///
+  /// Returns the number of elements written if all of `buf` fit, or an error reporting
+  /// how many leading elements were written and how many were rejected.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 8]);
+  /// assert_eq!(buf.try_write(b"hello"), Ok(5));
+  ///
+  /// let err = buf.try_write(b" world").unwrap_err();
+  /// assert_eq!((err.written(), err.rejected()), (3, 3));
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b"hello wo");
+  /// ```
+  pub fn try_write<T>(&mut self, buf: &[T]) -> Result<usize, Full>
+  where
+    B: AsMut<[T]>,
+    T: Copy,
+  {
+    let free = self.data.as_mut().len() - self.len;
+    if buf.len() <= free {
+      return Ok(self.write(buf));
+    }
+
+    self.write(&buf[..free]);
+    Err(Full::new(free, buf.len() - free))
+  }
+
+  /// Writes up to len(buf) bytes to the internal ring like [`write`](Buffer::write),
+  /// but invokes `on_evict` with the retained data that is about to be overwritten first.
+  ///
+  /// The evicted data is reported in order, from the oldest to the newest, possibly over
+  /// several calls. If `buf` is larger than the buffer, the leading part of `buf` which
+  /// would be overwritten immediately is reported as well.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 4]);
+  /// let mut spilled = Vec::new();
+  ///
+  /// buf.write_with_evict(b"abc", |evicted| spilled.extend_from_slice(evicted));
+  /// buf.write_with_evict(b"def", |evicted| spilled.extend_from_slice(evicted));
+  /// assert_eq!(spilled, b"ab");
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b"cdef");
+  /// ```
+  pub fn write_with_evict<T, F>(&mut self, buf: &[T], mut on_evict: F) -> usize
+  where
+    B: AsRef<[T]> + AsMut<[T]>,
+    T: Copy,
+    F: FnMut(&[T]),
+  {
+    let size = self.size();
+    let evicted = self.overwritten_by(buf.len(), size);
+    let (front, back) = self.head(evicted);
+    for segment in [front, back, &buf[..buf.len().saturating_sub(size)]] {
+      if !segment.is_empty() {
+        on_evict(segment);
+      }
+    }
+
+    self.write(buf)
+  }
+
+  /// Returns how many bytes can be read from the buffer.
+  ///
+  /// This is useful when you want to read from the buffer.
+  /// Same as [`len`](Buffer::len).
+  #[inline]
+  pub const fn read_hint(&self) -> usize {
+    self.len
+  }
+
+  /// Drops up to `n` of the oldest unread bytes without copying them, returns the number
+  /// of bytes dropped.
+  ///
+  /// The rest of the unread bytes is untouched. This frees space for
+  /// [`try_write`](Buffer::try_write) once a consumer has persisted a prefix
+  /// it read in place, e.g. with [`as_slices`](Buffer::as_slices).
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 8]);
+  /// buf.write(b"hello world");
+  /// assert!(buf.try_write(b"!").is_err());
+  ///
+  /// assert_eq!(buf.discard_oldest(3), 3);
+  /// assert_eq!(buf.try_write(b"!"), Ok(1));
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b"world!");
+  /// ```
+  #[inline]
+  pub const fn discard_oldest(&mut self, n: usize) -> usize {
+    let n = if n < self.len { n } else { self.len };
+    self.len -= n;
+    n
+  }
+
+  /// Returns the oldest unread elements which are contiguous in the storage, so a
+  /// consumer can process them in place, then consume them with
+  /// [`release`](Buffer::release).
+  ///
+  /// If the unread elements wrap around the end of the storage, only the part up to the
+  /// end is returned, the rest is granted once it has been released.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 8]);
+  /// buf.write(b"hello");
+  /// buf.write(b" world");
+  ///
+  /// // The unread bytes wrap around after "lo wo"
+  /// assert_eq!(buf.read_grant(), b"lo wo");
+  /// buf.release(5);
+  /// assert_eq!(buf.read_grant(), b"rld");
+  /// buf.release(1);
+  /// assert_eq!(buf.read_grant(), b"ld");
+  /// ```
+  #[inline]
+  pub fn read_grant<T>(&self) -> &[T]
+  where
+    B: AsRef<[T]>,
+  {
+    self.as_slices().0
+  }
+
+  /// Consumes the `n` oldest unread elements, after they were processed in place through
+  /// [`read_grant`](Buffer::read_grant).
+  ///
+  /// # Panics
+  ///
+  /// Panics if `n` is larger than the number of unread elements.
+  #[inline]
+  pub const fn release(&mut self, n: usize) {
+    assert!(n <= self.len, "release exceeds the unread elements");
+    self.len -= n;
+  }
+
+  /// Returns the number of unread bytes, which the next [`read`](Buffer::read)
+  /// calls can consume.
+  ///
+  /// Bytes already consumed do not count, even while they are still stored and reachable
+  /// through [`read_since`](Buffer::read_since).
+  #[doc(alias = "unread")]
+  #[doc(alias = "read_hint")]
+  #[inline]
+  pub const fn len(&self) -> usize {
+    self.len
+  }
+
+  /// Returns `true` if the buffer holds no unread bytes.
+  #[inline]
+  pub const fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Returns how many bytes are pending, i.e. the number of bytes
+  /// the next [`read`](Buffer::read) calls can consume.
+  /// Same as [`len`](Buffer::len).
+  #[inline]
+  pub const fn unread(&self) -> usize {
+    self.len
+  }
+
+  /// Reads up to `dst.len()` of the oldest unread bytes into `dst` and consumes them,
+  /// returns number of bytes readed.
+  ///
+  /// Subsequent reads only return data written after the bytes consumed here.
+  pub fn read<T>(&mut self, dst: &mut [T]) -> usize
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    let (front, back) = self.as_slices();
+    let n = copy_segments(dst, front, back);
+    self.len -= n;
+    n
+  }
+
+  /// Returns the element at the logical position `i`, relative to the oldest unread
+  /// element, or `None` if `i` is out of bounds.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 4]);
+  /// buf.write(b"abcdef");
+  ///
+  /// assert_eq!(buf.get(0), Some(b'c'));
+  /// assert_eq!(buf[3], b'f');
+  /// assert_eq!(buf.get(4), None::<u8>);
+  /// assert_eq!(buf.range(1..3), b"de");
+  /// ```
+  #[inline]
+  pub fn get<T>(&self, i: usize) -> Option<T>
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    self.get_ref(i).copied()
+  }
+
+  /// Returns a [`View`] over the logical `range` of the unread data, relative to the
+  /// oldest unread element, the counterpart of slicing for a circular buffer.
+  ///
+  /// ## Panics
+  ///
+  /// Panics if the range is out of bounds.
+  pub fn range<T>(&self, range: impl core::ops::RangeBounds<usize>) -> View<'_, T>
+  where
+    B: AsRef<[T]>,
+  {
+    use core::ops::Bound;
+
+    let start = match range.start_bound() {
+      Bound::Included(&start) => start,
+      Bound::Excluded(&start) => start + 1,
+      Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+      Bound::Included(&end) => end + 1,
+      Bound::Excluded(&end) => end,
+      Bound::Unbounded => self.len,
+    };
+    assert!(
+      start <= end && end <= self.len,
+      "range {start}..{end} out of bounds for a buffer holding {} elements",
+      self.len
+    );
+
+    let (front, back) = self.as_slices();
+    View::new(front, back).split_at(end).0.split_at(start).1
+  }
+
+  /// Divides the unread data into two [`View`]s at the logical position `mid`, relative
+  /// to the oldest unread element, without copying.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 8]);
+  /// buf.write(b"old");
+  /// buf.write(b"HDRbody!");
+  ///
+  /// let (header, payload) = buf.split_at(3);
+  /// assert_eq!(header, b"HDR");
+  /// assert_eq!(payload, b"body!");
+  /// ```
+  ///
+  /// ## Panics
+  ///
+  /// Panics if `mid` is greater than the number of unread elements.
+  pub fn split_at<T>(&self, mid: usize) -> (View<'_, T>, View<'_, T>)
+  where
+    B: AsRef<[T]>,
+  {
+    assert!(
+      mid <= self.len,
+      "mid {mid} out of bounds for a buffer holding {} elements",
+      self.len
+    );
+    let (front, back) = self.as_slices();
+    View::new(front, back).split_at(mid)
+  }
+
+  fn get_ref<T>(&self, i: usize) -> Option<&T>
+  where
+    B: AsRef<[T]>,
+  {
+    let (front, back) = self.as_slices();
+    View::new(front, back).get(i)
+  }
+
+  /// Reads up to `dst.len()` bytes starting at `offset` into `dst`, returns number of bytes readed.
+  ///
+  /// The `offset` is logical, relative to the oldest unread byte, and the wrap point
+  /// is handled internally. Nothing is consumed.
+  pub fn read_at<T>(&self, offset: usize, dst: &mut [T]) -> usize
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    let (front, back) = self.slices_from(offset);
+    copy_segments(dst, front, back)
+  }
+
+  /// Overwrites the unread elements starting at `offset` with `buf` in place,
+  /// returns number of elements written.
+  ///
+  /// The `offset` is logical, relative to the oldest unread element, and the wrap point
+  /// is handled internally. Only unread elements are patched: `buf` is truncated at the
+  /// newest one, and nothing is appended. The statistics are left untouched.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 8]);
+  /// buf.write(b"ab");
+  ///
+  /// // Reserve a length field, write the body, then fill the length in
+  /// let offset = buf.unread();
+  /// buf.write(&[0]);
+  /// buf.write(b"body");
+  /// buf.write_at(offset, &[4]);
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b"ab\x04body");
+  /// ```
+  pub fn write_at<T>(&mut self, offset: usize, buf: &[T]) -> usize
+  where
+    B: AsMut<[T]>,
+    T: Copy,
+  {
+    let n = buf.len().min(self.len.saturating_sub(offset));
+    if n == 0 {
+      return 0;
+    }
+
+    let data = self.data.as_mut();
+    let size = data.len();
+    let start = wrap(self.write_cursor + size - self.len + offset, size);
+    let (first, second) = buf[..n].split_at(n.min(size - start));
+    data[start..start + first.len()].copy_from_slice(first);
+    data[..second.len()].copy_from_slice(second);
+    n
+  }
+
+  /// Reads up to `dst.len()` elements starting at the absolute stream offset `offset`
+  /// (see [`written`](Buffer::written)) into `dst`, returns number of elements readed.
+  ///
+  /// This lets an external consumer resume from where it left off, regardless of what
+  /// was consumed in the meantime. Nothing is consumed. Returns a [`Gap`] error if some
+  /// of the data written since `offset` was overwritten. An offset past the end of the
+  /// stream, e.g. after a [`reset`](Buffer::reset), is treated as the start of the stream.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 8]);
+  /// buf.write(b"hello");
+  ///
+  /// let mut dst = [0u8; 8];
+  /// assert_eq!(buf.read_since(2, &mut dst), Ok(3));
+  /// assert_eq!(&dst[..3], b"llo");
+  ///
+  /// buf.write(b" world");
+  /// let gap = buf.read_since(2, &mut dst).unwrap_err();
+  /// assert_eq!((gap.missed(), gap.oldest()), (1, 3));
+  /// assert_eq!(buf.read_since(gap.oldest(), &mut dst), Ok(8));
+  /// assert_eq!(&dst, b"lo world");
+  /// ```
+  pub fn read_since<T>(&self, offset: usize, dst: &mut [T]) -> Result<usize, Gap>
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    let (missed, front, back) = self.history(offset);
+    if missed != 0 {
+      return Err(Gap::new(missed, self.written - front.len() - back.len()));
+    }
+    Ok(copy_segments(dst, front, back))
+  }
+
+  /// Copies the most recent `n` bytes into `dst`, returns number of bytes readed.
+  ///
+  /// Fewer bytes are copied if less than `n` bytes are retained or `dst` is shorter than `n`,
+  /// in which case the newest bytes that fit are copied.
+  pub fn tail<T>(&self, n: usize, dst: &mut [T]) -> usize
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    let n = n.min(self.len).min(dst.len());
+    self.read_at(self.len - n, &mut dst[..n])
+  }
+
+  /// Returns the most recent `n` bytes as a vector.
+  #[cfg(any(feature = "std", feature = "alloc"))]
+  #[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+  pub fn tail_vec<T>(&self, n: usize) -> std::vec::Vec<T>
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    let (front, back) = self.slices_from(self.len - n.min(self.len));
+    let mut out = std::vec::Vec::with_capacity(front.len() + back.len());
+    out.extend_from_slice(front);
+    out.extend_from_slice(back);
+    out
+  }
+
+  /// Reads the whole buffer into the `dst`, returns number of bytes readed.
+  ///
+  /// To avoid panics, you should check the [`read_hint`](Buffer::read_hint) method
+  /// to see how many bytes can be read.
+  ///
+  /// ## Panics
+  ///
+  /// Panics if the data contained in the buffer is larger than the given `dst`.
+  ///
+  /// With the `unsafe-perf` feature enabled, `dst` is checked once and the copies
+  /// are not bounds checked.
+  pub fn read_into<T>(&self, dst: &mut [T]) -> usize
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    let (front, back) = self.as_slices();
+    let n = front.len() + back.len();
+    let dst = &mut dst[..n];
+    copy_at(dst, 0, front);
+    copy_at(dst, front.len(), back);
+    n
+  }
+
+  /// Reads the whole buffer into the `dst`, returns number of bytes readed.
+  ///
+  /// Unlike [`read_into`](Buffer::read_into), this method never panics, and returns
+  /// an error without copying anything if `dst` is too small.
+  pub fn try_read_into<T>(&self, dst: &mut [T]) -> Result<usize, ReadError>
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    if dst.len() < self.len {
+      return Err(ReadError::new(self.len, dst.len()));
+    }
+
+    Ok(self.read_into(dst))
+  }
+
+  /// Copies up to `N` of the oldest retained bytes into a stack array, returns the array
+  /// and the number of bytes copied into it, without any allocation.
+  ///
+  /// The rest of the array is zeroed. Nothing is consumed.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 8]);
+  /// buf.write(b"hello");
+  ///
+  /// let (bytes, n) = buf.read_to_array::<16>();
+  /// assert_eq!(&bytes[..n], b"hello");
+  /// ```
+  pub fn read_to_array<const N: usize>(&self) -> ([u8; N], usize)
+  where
+    B: AsRef<[u8]>,
+  {
+    let mut out = [0; N];
+    let n = self.read_at(0, &mut out);
+    (out, n)
+  }
+
+  /// Writes the unread bytes into `w`, from the oldest to the newest, returns the number
+  /// of bytes written.
+  ///
+  /// Both segments are written with [`write_all`](std::io::Write::write_all), so no
+  /// intermediate copy is needed to dump the buffer into a file or a socket.
+  /// Nothing is consumed.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 6]);
+  /// buf.write(b"hello world");
+  ///
+  /// let mut out = Vec::new();
+  /// assert_eq!(buf.copy_to_writer(&mut out).unwrap(), 6);
+  /// assert_eq!(out, b" world");
+  /// ```
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+  pub fn copy_to_writer<W>(&self, w: &mut W) -> std::io::Result<usize>
+  where
+    W: std::io::Write + ?Sized,
+    B: AsRef<[u8]>,
+  {
+    let (front, back) = self.as_slices();
+    w.write_all(front)?;
+    w.write_all(back)?;
+    Ok(front.len() + back.len())
+  }
+
+  /// Returns the storage the next `n` elements would be written to, so a producer can
+  /// fill it in place, then make it readable with [`commit`](Buffer::commit).
+  ///
+  /// The region starts at the cursor and is split in two if it wraps around the end of
+  /// the storage. `n` is capped to the size of the buffer. Beyond the
+  /// `size - len` free elements, the region holds the oldest unread elements: writing
+  /// into it overwrites them right away, while they still count as unread until the
+  /// commit drops them. A grant of at most `size - len` elements never touches unread data.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 8]);
+  /// buf.write(b"hello");
+  ///
+  /// let (first, second) = buf.write_grant(6);
+  /// assert_eq!((first.len(), second.as_deref().map(<[u8]>::len)), (3, Some(3)));
+  /// first.copy_from_slice(b" wo");
+  /// second.unwrap().copy_from_slice(b"rld");
+  ///
+  /// buf.commit(6);
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b"lo world");
+  /// ```
+  pub fn write_grant<T>(&mut self, n: usize) -> (&mut [T], Option<&mut [T]>)
+  where
+    B: AsMut<[T]>,
+  {
+    let cursor = self.write_cursor;
+    let data = self.data.as_mut();
+    let n = n.min(data.len());
+    let (head, tail) = data.split_at_mut(cursor);
+    if n <= tail.len() {
+      (&mut tail[..n], None)
+    } else {
+      let wrapped = n - tail.len();
+      (tail, Some(&mut head[..wrapped]))
+    }
+  }
+
+  /// Makes the `n` elements following the cursor readable, after they were written in
+  /// place through [`write_grant`](Buffer::write_grant), overwriting the oldest data
+  /// if necessary.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `n` is larger than the size of the buffer.
+  pub fn commit<T>(&mut self, n: usize)
+  where
+    B: AsMut<[T]>,
+  {
+    let size = self.data.as_mut().len();
+    assert!(
+      n <= size,
+      "commit of {n} elements exceeds the buffer size {size}"
+    );
+    self.advance_write(n);
+  }
+
+  /// Fills the ring with bytes pulled from `r`, returns the number of bytes read.
+  ///
+  /// The bytes are read directly into the storage, in up to two segments: from the
+  /// cursor to the end of the storage, then from the start of the storage to the cursor
+  /// if the first segment was filled. Like [`write`](Buffer::write), the oldest data is
+  /// overwritten. Reads interrupted by [`ErrorKind::Interrupted`](std::io::ErrorKind::Interrupted)
+  /// are retried.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 6]);
+  /// let mut reader = &b"hello world"[..];
+  ///
+  /// assert_eq!(buf.read_from(&mut reader).unwrap(), 6);
+  /// assert_eq!(buf.read_from(&mut reader).unwrap(), 5);
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b" world");
+  /// ```
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+  pub fn read_from<R>(&mut self, r: &mut R) -> std::io::Result<usize>
+  where
+    R: std::io::Read + ?Sized,
+    B: AsMut<[u8]>,
+  {
+    fn read_segment<R: std::io::Read + ?Sized>(
+      r: &mut R,
+      dst: &mut [u8],
+    ) -> std::io::Result<usize> {
+      loop {
+        match r.read(dst) {
+          Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+          res => return res,
+        }
+      }
+    }
+
+    let start = self.write_cursor;
+    let first = read_segment(r, &mut self.data.as_mut()[start..])?;
+    self.advance_write(first);
+    if start == 0 || self.write_cursor != 0 {
+      return Ok(first);
+    }
+
+    match read_segment(r, &mut self.data.as_mut()[..start]) {
+      Ok(second) => {
+        self.advance_write(second);
+        Ok(first + second)
+      }
+      Err(_) if first != 0 => Ok(first),
+      Err(e) => Err(e),
+    }
+  }
+
+  /// Continuously copies the bytes of the tokio [`AsyncRead`](tokio::io::AsyncRead) `r`
+  /// into the ring until it reaches EOF, returns the total number of bytes read.
+  ///
+  /// Like [`read_from`](Buffer::read_from), the bytes are read directly into the storage,
+  /// so only the last bytes of the stream are retained.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+  /// let mut buf = Buffer::new([0u8; 6]);
+  /// assert_eq!(buf.fill_from_tokio(&b"hello world"[..]).await.unwrap(), 11);
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b" world");
+  /// # });
+  /// ```
+  #[cfg(feature = "tokio")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+  pub async fn fill_from_tokio<R>(&mut self, mut r: R) -> std::io::Result<usize>
+  where
+    R: tokio::io::AsyncRead + Unpin,
+    B: AsMut<[u8]>,
+  {
+    let mut total = 0;
+    loop {
+      let start = self.write_cursor;
+      let n = core::future::poll_fn(|cx| {
+        let mut dst = tokio::io::ReadBuf::new(&mut self.data.as_mut()[start..]);
+        core::pin::Pin::new(&mut r)
+          .poll_read(cx, &mut dst)
+          .map_ok(|()| dst.filled().len())
+      })
+      .await;
+
+      match n {
+        Ok(0) => return Ok(total),
+        Ok(n) => {
+          self.advance_write(n);
+          total += n;
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+        Err(e) => return Err(e),
+      }
+    }
+  }
+
+  /// Continuously copies the bytes of the futures-io [`AsyncRead`](futures_io::AsyncRead) `r`
+  /// into the ring until it reaches EOF, returns the total number of bytes read.
+  ///
+  /// Like [`read_from`](Buffer::read_from), the bytes are read directly into the storage,
+  /// so only the last bytes of the stream are retained.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// # futures_util::FutureExt::now_or_never(async {
+  /// let mut buf = Buffer::new([0u8; 6]);
+  /// assert_eq!(buf.fill_from_futures(&b"hello world"[..]).await.unwrap(), 11);
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b" world");
+  /// # }).unwrap();
+  /// ```
+  #[cfg(all(feature = "std", feature = "futures-io"))]
+  #[cfg_attr(docsrs, doc(cfg(feature = "future")))]
+  pub async fn fill_from_futures<R>(&mut self, mut r: R) -> std::io::Result<usize>
+  where
+    R: futures_io::AsyncRead + Unpin,
+    B: AsMut<[u8]>,
+  {
+    let mut total = 0;
+    loop {
+      let start = self.write_cursor;
+      let n = core::future::poll_fn(|cx| {
+        core::pin::Pin::new(&mut r).poll_read(cx, &mut self.data.as_mut()[start..])
+      })
+      .await;
+
+      match n {
+        Ok(0) => return Ok(total),
+        Ok(n) => {
+          self.advance_write(n);
+          total += n;
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+        Err(e) => return Err(e),
+      }
+    }
+  }
+
+  /// Provides a slice of the bytes written. This
+  /// slice should not be written to.
+  #[cfg(any(feature = "std", feature = "alloc"))]
+  #[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+  #[inline]
+  pub fn read_to_bytes<T>(&self) -> std::borrow::Cow<'_, [T]>
+  where
+    B: AsRef<[T]>,
+    T: Clone,
+  {
+    match self.as_slices() {
+      (front, []) => std::borrow::Cow::Borrowed(front),
+      (front, back) => {
+        let mut out = std::vec::Vec::with_capacity(self.len);
+        out.extend_from_slice(front);
+        out.extend_from_slice(back);
+        std::borrow::Cow::Owned(out)
+      }
+    }
+  }
+
+  /// Returns the retained bytes as a string, replacing invalid UTF-8 with
+  /// [`U+FFFD REPLACEMENT CHARACTER`](char::REPLACEMENT_CHARACTER).
+  ///
+  /// After a wrap, the oldest retained byte frequently lands in the middle of a
+  /// code point: the continuation bytes of such a torn sequence at the start are
+  /// replaced by a single replacement character.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 6]);
+  /// buf.write("señal".as_bytes());
+  /// buf.write(b"!!!");
+  ///
+  /// // Only the last byte of "ñ" is retained
+  /// assert_eq!(buf.read_to_string_lossy(), "\u{FFFD}al!!!");
+  /// ```
+  #[cfg(any(feature = "std", feature = "alloc"))]
+  #[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+  pub fn read_to_string_lossy(&self) -> std::borrow::Cow<'_, str>
+  where
+    B: AsRef<[u8]>,
+  {
+    use std::{borrow::Cow, string::String};
+
+    let bytes = self.read_to_bytes();
+    let torn = bytes
+      .iter()
+      .take(3)
+      .take_while(|&&b| b & 0b1100_0000 == 0b1000_0000)
+      .count();
+    if torn != 0 {
+      let mut out = String::from(char::REPLACEMENT_CHARACTER);
+      out.push_str(&String::from_utf8_lossy(&bytes[torn..]));
+      return Cow::Owned(out);
+    }
+
+    match bytes {
+      Cow::Borrowed(bytes) => String::from_utf8_lossy(bytes),
+      Cow::Owned(bytes) => Cow::Owned(match String::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+      }),
+    }
+  }
+
+  /// Returns the retained bytes as a [`Bytes`](bytes::Bytes), which can be handed
+  /// to network code directly.
+  #[cfg(feature = "bytes")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+  pub fn read_to_bytes_shared(&self) -> bytes::Bytes
+  where
+    B: AsRef<[u8]>,
+  {
+    let (front, back) = self.as_slices();
+    let mut out = bytes::BytesMut::with_capacity(self.len);
+    out.extend_from_slice(front);
+    out.extend_from_slice(back);
+    out.freeze()
+  }
+
+  /// Returns an iterator over the retained data, from the oldest to the newest,
+  /// across the wrap point.
+  #[inline]
+  pub fn iter<'a, T>(&'a self) -> impl DoubleEndedIterator<Item = T>
+  where
+    B: AsRef<[T]>,
+    T: Copy + 'a,
+  {
+    let (front, back) = self.as_slices();
+    front.iter().chain(back).copied()
+  }
+
+  /// Returns the size of the buffer
+  ///
+  /// Same as [`capacity`](Buffer::capacity).
+  #[inline]
+  pub fn size<T>(&self) -> usize
+  where
+    B: AsRef<[T]>,
+  {
+    self.data.as_ref().len()
+  }
+
+  /// Returns the number of bytes the buffer can retain, beyond which the oldest
+  /// bytes are overwritten.
+  #[inline]
+  pub fn capacity<T>(&self) -> usize
+  where
+    B: AsRef<[T]>,
+  {
+    self.size()
+  }
+
+  /// Returns `true` if the buffer holds as many unread bytes as its capacity,
+  /// so the next write overwrites the oldest unread ones.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 4]);
+  /// buf.write(b"abc");
+  /// assert_eq!((buf.len(), buf.capacity()), (3, 4));
+  /// assert!(!buf.is_full());
+  ///
+  /// buf.write(b"def");
+  /// assert!(buf.is_full());
+  /// ```
+  #[inline]
+  pub fn is_full<T>(&self) -> bool
+  where
+    B: AsRef<[T]>,
+  {
+    self.len == self.size()
+  }
+
+  /// Returns how many bytes can still be written before the cursor wraps around the end
+  /// of the storage, or before unread bytes start being overwritten, whichever comes first.
+  ///
+  /// This is also the length of the free space contiguous to the cursor, so callers
+  /// implementing their own flush policy can drain proactively before data is lost.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 8]);
+  /// buf.write(b"hello");
+  /// assert_eq!(buf.remaining_until_wrap(), 3);
+  ///
+  /// buf.read(&mut [0u8; 5]);
+  /// buf.write(b"!!");
+  /// // The cursor wraps after one more byte, although 6 bytes are free
+  /// assert_eq!(buf.remaining_until_wrap(), 1);
+  /// ```
+  #[inline]
+  pub fn remaining_until_wrap<T>(&self) -> usize
+  where
+    B: AsRef<[T]>,
+  {
+    let size = self.size();
+    (size - self.write_cursor).min(size - self.len)
+  }
+
+  /// Returns the total number of bytes written to the buffer
+  ///
+  /// This is also the stream offset of the next byte to be written: it increases
+  /// monotonically until the buffer is [`reset`](Buffer::reset).
+  #[inline]
+  pub const fn written(&self) -> usize {
+    self.written
+  }
+
+  /// Returns the number of times the write cursor wrapped around the end of the storage,
+  /// see [`Stats::wraps`].
+  #[inline]
+  pub const fn wraps(&self) -> usize {
+    self.stats.wraps
+  }
+
+  /// Returns the number of unread bytes which were overwritten before they could be read,
+  /// see [`Stats::overwritten`].
+  ///
+  /// Comparing it between two inspections tells whether, and how much, data was lost
+  /// in between, which [`written`](Buffer::written) alone cannot answer.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 4]);
+  /// buf.write(b"abc");
+  /// let before = buf.overwritten();
+  ///
+  /// buf.write(b"de");
+  /// assert_eq!(buf.overwritten() - before, 1);
+  /// assert_eq!(buf.wraps(), 1);
+  /// ```
+  #[inline]
+  pub const fn overwritten(&self) -> usize {
+    self.stats.overwritten
+  }
+
+  /// Resets the buffer so it has no content, and clears its [`stats`](Buffer::stats).
+  #[inline]
+  pub const fn reset(&mut self) {
+    self.write_cursor = 0;
+    self.written = 0;
+    self.len = 0;
+    self.retained = 0;
+    self.stats = Counters::new();
+  }
+
+  /// Returns the statistics of the writes since the buffer was created or reset.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 4]);
+  /// buf.write(b"abc");
+  /// buf.write(b"defgh");
+  ///
+  /// let stats = buf.stats();
+  /// assert_eq!(stats.writes(), 2);
+  /// assert_eq!(stats.written(), 8);
+  /// assert_eq!(stats.overwritten(), 3);
+  /// assert_eq!(stats.wraps(), 2);
+  /// assert_eq!(stats.largest_write(), 5);
+  /// ```
+  #[inline]
+  pub const fn stats(&self) -> Stats {
+    let Counters {
+      writes,
+      overwritten,
+      wraps,
+      largest_write,
+    } = self.stats;
+    Stats {
+      writes,
+      written: self.written,
+      overwritten,
+      wraps,
+      largest_write,
+    }
+  }
+
+  /// Takes the unread data, from the oldest to the newest, and [`reset`](Buffer::reset)s
+  /// the buffer.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 6]);
+  /// buf.write(b"hello world");
+  ///
+  /// assert_eq!(buf.drain(), b" world");
+  /// assert_eq!(buf.unread(), 0);
+  /// assert_eq!(buf.written(), 0);
+  /// ```
+  #[cfg(any(feature = "std", feature = "alloc"))]
+  #[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+  pub fn drain<T>(&mut self) -> std::vec::Vec<T>
+  where
+    B: AsRef<[T]>,
+    T: Clone,
+  {
+    let out = self.read_to_bytes().into_owned();
+    self.reset();
+    out
+  }
+
+  /// Consumes the buffer and returns its unread elements as a deque,
+  /// from the oldest at the front to the newest at the back.
+  #[cfg(any(feature = "std", feature = "alloc"))]
+  #[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+  pub fn into_vecdeque<T>(self) -> std::collections::VecDeque<T>
+  where
+    B: AsRef<[T]>,
+    T: Clone,
+  {
+    let (front, back) = self.as_slices();
+    let mut out = std::collections::VecDeque::with_capacity(self.len);
+    out.extend(front.iter().cloned());
+    out.extend(back.iter().cloned());
+    out
+  }
+
+  /// Consumes the buffer and returns the underlying data.
+  #[inline]
+  pub fn into_inner(self) -> B {
+    self.data
+  }
+
+  /// Returns the retained data as (older segment, newer segment) without any allocation.
+  ///
+  /// The second slice is empty unless the retained data wraps around
+  /// the end of the storage, like `VecDeque::as_slices`.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 6]);
+  /// buf.write(b"hello");
+  /// buf.write(b" world");
+  ///
+  /// assert_eq!(buf.as_slices(), (&b" "[..], &b"world"[..]));
+  /// ```
+  pub fn as_slices<T>(&self) -> (&[T], &[T])
+  where
+    B: AsRef<[T]>,
+  {
+    self.window(self.len)
+  }
+  /// Rotates the storage in place so the unread data is a single contiguous slice,
+  /// from the oldest to the newest, and returns it, like `VecDeque::make_contiguous`.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 6]);
+  /// buf.write(b"hello");
+  /// buf.write(b" world");
+  ///
+  /// assert_eq!(buf.make_contiguous(), b" world");
+  /// assert_eq!(buf.as_slices(), (&b" world"[..], &b""[..]));
+  /// ```
+  pub fn make_contiguous<T>(&mut self) -> &mut [T]
+  where
+    B: AsMut<[T]>,
+  {
+    let data = self.data.as_mut();
+    let size = data.len();
+    if size == 0 {
+      return data;
+    }
+
+    let start = wrap(self.write_cursor + size - self.len, size);
+    data.rotate_left(start);
+    self.write_cursor = wrap(self.len, size);
+    &mut data[..self.len]
+  }
+
+  /// Returns the oldest `n` retained elements as (older segment, newer segment),
+  /// i.e. the data that is going to be overwritten next.
+  ///
+  /// Fewer elements are returned if less than `n` elements are retained.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 6]);
+  /// buf.write(b"hello");
+  /// buf.write(b" world");
+  ///
+  /// assert_eq!(buf.head(3), (&b" "[..], &b"wo"[..]));
+  /// ```
+  pub fn head<T>(&self, n: usize) -> (&[T], &[T])
+  where
+    B: AsRef<[T]>,
+  {
+    let (front, back) = self.as_slices();
+    match n {
+      n if n <= front.len() => (&front[..n], &[]),
+      n => (front, &back[..(n - front.len()).min(back.len())]),
+    }
+  }
+
+  /// Returns the offset, relative to the oldest unread element, of the first occurrence
+  /// of `needle` in the unread data, including occurrences spanning the wrap point.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 8]);
+  /// buf.write(b"....");
+  /// buf.write(b"MARKER!!");
+  ///
+  /// assert_eq!(buf.find(b"KER"), Some(3));
+  /// assert!(buf.contains(b"MARKER"));
+  /// assert!(!buf.contains(b"...."));
+  /// ```
+  #[inline]
+  pub fn find<T>(&self, needle: &[T]) -> Option<usize>
+  where
+    B: AsRef<[T]>,
+    T: PartialEq,
+  {
+    let (front, back) = self.as_slices();
+    View::new(front, back).find(needle)
+  }
+
+  /// Returns `true` if `needle` occurs in the unread data, including across the wrap point.
+  #[inline]
+  pub fn contains<T>(&self, needle: &[T]) -> bool
+  where
+    B: AsRef<[T]>,
+    T: PartialEq,
+  {
+    self.find(needle).is_some()
+  }
+
+  /// Returns an iterator over the newline-delimited lines of the unread bytes, from
+  /// the oldest to the newest, without allocating.
+  ///
+  /// Lines spanning the wrap point are yielded as a single [`View`]. The trailing `\n`
+  /// or `\r\n` is stripped, and the last line is yielded even if it is not terminated.
+  /// Nothing is consumed.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 20]);
+  /// buf.write(b"dropped\n");
+  /// buf.write(b"first\r\nsecond\nthi");
+  ///
+  /// let lines = buf.lines().collect::<Vec<_>>();
+  /// assert_eq!(lines, [&b"ed"[..], b"first", b"second", b"thi"]);
+  /// ```
+  #[inline]
+  pub fn lines(&self) -> impl Iterator<Item = View<'_, u8>> + '_
+  where
+    B: AsRef<[u8]>,
+  {
+    let (front, back) = self.as_slices();
+    View::new(front, back).lines()
+  }
+
+  /// Returns a [`Display`](core::fmt::Display) rendering of the unread bytes in the
+  /// classic `hexdump -C` layout: an offset, 16 bytes in hex, and their ASCII form per row.
+  ///
+  /// Offsets are relative to the oldest unread byte, across the wrap point.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 20]);
+  /// buf.write(b"....");
+  /// buf.write(b"The quick brown fox\n");
+  ///
+  /// assert_eq!(
+  ///   buf.hexdump().to_string(),
+  ///   "00000000  54 68 65 20 71 75 69 63  6b 20 62 72 6f 77 6e 20  |The quick brown |\n\
+  ///    00000010  66 6f 78 0a                                       |fox.|\n",
+  /// );
+  /// ```
+  #[inline]
+  pub fn hexdump(&self) -> impl core::fmt::Display + '_
+  where
+    B: AsRef<[u8]>,
+  {
+    let (front, back) = self.as_slices();
+    hexdump::Hexdump(View::new(front, back))
+  }
+
+  /// Returns a new storage of `capacity` elements holding the most recent unread elements
+  /// that fit, and moves the cursor accordingly.
+  #[cfg(any(feature = "std", feature = "alloc"))]
+  fn resized<T>(&mut self, capacity: usize) -> std::vec::Vec<T>
+  where
+    B: AsRef<[T]>,
+    T: Copy + Default,
+  {
+    let mut data = std::vec![T::default(); capacity];
+    // The newest history that fits is kept, read or not, so `read_since` still reaches it
+    let kept = self.retained.min(capacity);
+    let (front, back) = self.window(kept);
+    data[..front.len()].copy_from_slice(front);
+    data[front.len()..kept].copy_from_slice(back);
+    self.len = self.len.min(kept);
+    self.retained = kept;
+    self.write_cursor = match capacity {
+      0 => 0,
+      capacity => wrap(kept, capacity),
+    };
+    data
+  }
+
+  /// Accounts for `n` elements written in place at the cursor.
+  fn advance_write<T>(&mut self, n: usize)
+  where
+    B: AsMut<[T]>,
+  {
+    let size = self.data.as_mut().len();
+    if n == 0 {
+      return;
+    }
+
+    self.record_write(n, size);
+    self.written += n;
+    self.len = (self.len + n).min(size);
+    self.retained = (self.retained + n).min(size);
+    self.write_cursor = wrap(self.write_cursor + n, size);
+  }
+
+  /// Returns how many unread elements a write of `n` elements into a storage of `size`
+  /// elements overwrites.
+  #[inline]
+  pub(crate) fn overwritten_by(&self, n: usize, size: usize) -> usize {
+    (self.len + n).saturating_sub(size).min(self.len)
+  }
+
+  /// Updates the statistics for a write of `n` elements into a storage of `size` elements,
+  /// before the cursors are moved.
+  #[inline]
+  fn record_write(&mut self, n: usize, size: usize) {
+    self.stats.writes += 1;
+    self.stats.overwritten += self.overwritten_by(n, size);
+    self.stats.wraps += (self.write_cursor + n).checked_div(size).unwrap_or(0);
+    self.stats.largest_write = self.stats.largest_write.max(n);
+  }
+
+  /// Returns the newest `len` elements stored in the ring as (older segment, newer segment),
+  /// regardless of whether they were consumed. `len` must not exceed the size.
+  fn window<T>(&self, len: usize) -> (&[T], &[T])
+  where
+    B: AsRef<[T]>,
+  {
+    segments(self.data.as_ref(), self.write_cursor, len)
+  }
+
+  /// Returns the data written since the absolute stream offset `from`
+  /// (see [`written`](Buffer::written)), regardless of whether it was consumed.
+  ///
+  /// The first element of the tuple is the number of elements written since `from`
+  /// which have already been overwritten. An offset past the end of the stream,
+  /// e.g. after a [`reset`](Buffer::reset), is treated as the start of the stream.
+  pub(crate) fn history<T>(&self, from: usize) -> (usize, &[T], &[T])
+  where
+    B: AsRef<[T]>,
+  {
+    let from = if from > self.written { 0 } else { from };
+    let oldest = self.written - self.retained;
+    let missed = oldest.saturating_sub(from);
+    let (front, back) = self.window(self.written - from.max(oldest));
+    (missed, front, back)
+  }
+
+  /// Returns the retained data starting at the logical `offset` as (older segment, newer segment).
+  fn slices_from<T>(&self, offset: usize) -> (&[T], &[T])
+  where
+    B: AsRef<[T]>,
+  {
+    let (front, back) = self.as_slices();
+    match offset {
+      offset if offset <= front.len() => (&front[offset..], back),
+      offset => (&[], &back[(offset - front.len()).min(back.len())..]),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+  use std::io::{BufRead, Read, Write};
+
+  impl<B> Read for Buffer<B>
+  where
+    B: AsRef<[u8]>,
+  {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+      Ok(self.read(buf))
+    }
+  }
+
+  impl<B> BufRead for Buffer<B>
+  where
+    B: AsRef<[u8]>,
+  {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+      Ok(self.as_slices().0)
+    }
+
+    fn consume(&mut self, amt: usize) {
+      self.len -= amt.min(self.len);
+    }
+  }
+
+  impl<B> Write for Buffer<B>
+  where
+    B: AsMut<[u8]>,
+  {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+      Ok(self.write(buf))
+    }
+
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+      Ok(self.write_slices(bufs.iter().map(|buf| &**buf)))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+      Ok(())
+    }
+  }
+};
+
+#[cfg(all(feature = "tokio", feature = "std"))]
+const _: () = {
+  use core::{
+    pin::Pin,
+    task::{Context, Poll},
+  };
+  use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+  impl<B> AsyncRead for Buffer<B>
+  where
+    B: AsRef<[u8]> + Unpin,
+  {
+    fn poll_read(
+      self: Pin<&mut Self>,
+      _: &mut Context<'_>,
+      buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+      let n = self.get_mut().read(buf.initialize_unfilled());
+      buf.advance(n);
+      Poll::Ready(Ok(()))
+    }
+  }
+
+  impl<B> AsyncWrite for Buffer<B>
+  where
+    B: AsMut<[u8]> + Unpin,
+  {
+    fn poll_write(
+      self: Pin<&mut Self>,
+      _: &mut Context<'_>,
+      buf: &[u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+      Poll::Ready(Ok(self.get_mut().write(buf)))
+    }
+
+    fn poll_write_vectored(
+      self: Pin<&mut Self>,
+      _: &mut Context<'_>,
+      bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<Result<usize, std::io::Error>> {
+      Poll::Ready(Ok(
+        self.get_mut().write_slices(bufs.iter().map(|buf| &**buf)),
+      ))
+    }
+
+    fn is_write_vectored(&self) -> bool {
+      true
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), std::io::Error>> {
+      Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(
+      self: Pin<&mut Self>,
+      _: &mut Context<'_>,
+    ) -> Poll<Result<(), std::io::Error>> {
+      Poll::Ready(Ok(()))
+    }
+  }
+
+  impl<B> Buffer<B> {
+    /// Writes the unread bytes into the tokio [`AsyncWrite`] `w`, from the oldest to the
+    /// newest, returns the number of bytes written.
+    ///
+    /// Both segments are written directly, without the allocation of
+    /// [`read_to_bytes`](Buffer::read_to_bytes). Nothing is consumed.
+    ///
+    /// ```rust
+    /// use circularbuf::Buffer;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let mut buf = Buffer::new([0u8; 6]);
+    /// buf.write(b"hello world");
+    ///
+    /// let mut out = Vec::new();
+    /// assert_eq!(buf.copy_to(&mut out).await.unwrap(), 6);
+    /// assert_eq!(out, b" world");
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    pub async fn copy_to<W>(&self, mut w: W) -> std::io::Result<usize>
+    where
+      W: AsyncWrite + Unpin,
+      B: AsRef<[u8]>,
+    {
+      let (front, back) = self.as_slices();
+      write_all_async(|cx, buf| Pin::new(&mut w).poll_write(cx, buf), front, back).await
     }
   }
-}
+};
 
-impl<B> Buffer<B> {
-  /// Creates a new buffer with the given data.
-  #[inline]
-  pub const fn new(data: B) -> Self {
-    Self {
-      data,
-      write_cursor: 0,
-      written: 0,
+#[cfg(all(feature = "std", feature = "futures-io"))]
+const _: () = {
+  use core::{
+    pin::Pin,
+    task::{Context, Poll},
+  };
+  use futures_io::{AsyncRead, AsyncWrite};
+
+  impl<B> AsyncRead for Buffer<B>
+  where
+    B: AsRef<[u8]> + Unpin,
+  {
+    fn poll_read(
+      self: Pin<&mut Self>,
+      _: &mut Context<'_>,
+      buf: &mut [u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+      Poll::Ready(Ok(self.get_mut().read(buf)))
     }
   }
 
-  /// Writes up to len(buf) bytes to the internal ring,
-  /// overriding older data if necessary.
-  pub fn write(&mut self, mut buf: &[u8]) -> usize
+  impl<B> AsyncWrite for Buffer<B>
   where
-    B: AsMut<[u8]>,
+    B: AsMut<[u8]> + Unpin,
   {
-    // Account for total bytes written
-    let n = buf.len();
-    let data = self.data.as_mut();
-    let size = data.len();
-    self.written += n;
+    fn poll_write(
+      self: Pin<&mut Self>,
+      _: &mut Context<'_>,
+      buf: &[u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+      Poll::Ready(Ok(self.get_mut().write(buf)))
+    }
 
-    // If the buffer is larger than ours, then we only care
-    // about the last size bytes anyways
-    if n > size {
-      buf = &buf[n - size..];
+    fn poll_write_vectored(
+      self: Pin<&mut Self>,
+      _: &mut Context<'_>,
+      bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<Result<usize, std::io::Error>> {
+      Poll::Ready(Ok(
+        self.get_mut().write_slices(bufs.iter().map(|buf| &**buf)),
+      ))
     }
 
-    // Copy in place
-    let remain = size - self.write_cursor;
-    let data = self.data.as_mut();
-    copy(&mut data[self.write_cursor..], buf);
-    if n > remain {
-      copy(data, &buf[remain..]);
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), std::io::Error>> {
+      Poll::Ready(Ok(()))
     }
 
-    // Update location of the cursor
-    self.write_cursor = (self.write_cursor + buf.len()) % size;
-    n
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), std::io::Error>> {
+      Poll::Ready(Ok(()))
+    }
   }
 
-  /// Returns how many bytes can be read from the buffer.
-  ///
-  /// This is useful when you want to read from the buffer.
-  #[inline]
-  pub fn read_hint(&self) -> usize
-  where
-    B: AsRef<[u8]>,
-  {
-    let data = self.data.as_ref();
-    let size = data.len();
-    match () {
-      () if self.written >= size && self.write_cursor == 0 => self.data.as_ref().len(),
-      () if self.written > size => size,
-      _ => self.data.as_ref()[..self.write_cursor].len(),
+  impl<B> Buffer<B> {
+    /// Writes the unread bytes into the futures [`AsyncWrite`] `w`, from the oldest to the
+    /// newest, returns the number of bytes written.
+    ///
+    /// The counterpart of `copy_to` for the `futures-io` traits. Both segments are written
+    /// directly, without the allocation of [`read_to_bytes`](Buffer::read_to_bytes).
+    /// Nothing is consumed.
+    ///
+    /// ```rust
+    /// use circularbuf::Buffer;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let mut buf = Buffer::new([0u8; 6]);
+    /// buf.write(b"hello world");
+    ///
+    /// let mut out = futures_util::io::Cursor::new(Vec::new());
+    /// assert_eq!(buf.copy_to_futures(&mut out).await.unwrap(), 6);
+    /// assert_eq!(out.into_inner(), b" world");
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "future")))]
+    pub async fn copy_to_futures<W>(&self, mut w: W) -> std::io::Result<usize>
+    where
+      W: AsyncWrite + Unpin,
+      B: AsRef<[u8]>,
+    {
+      let (front, back) = self.as_slices();
+      write_all_async(|cx, buf| Pin::new(&mut w).poll_write(cx, buf), front, back).await
     }
   }
+};
 
-  /// Reads the whole buffer into the `dst`, returns number of bytes readed.
-  ///
-  /// To avoid panics, you should check the [`read_hint`](Buffer::read_hint) method
-  /// to see how many bytes can be read.
-  ///
-  /// ## Panics
+#[cfg(feature = "bytes")]
+const _: () = {
+  use bytes::{BufMut, buf::UninitSlice};
+
+  /// Writes past the capacity overwrite the oldest data, like [`Buffer::write`], so the
+  /// buffer never runs out of space, unless its size is zero.
   ///
-  /// Panics if the data contained in the buffer is larger than the given `dst`.
+  /// ```rust
+  /// use bytes::BufMut;
+  /// use circularbuf::Buffer;
   ///
-  pub fn read_into(&self, dst: &mut [u8]) -> usize
+  /// let mut buf = Buffer::new([0u8; 8]);
+  /// buf.put_u16(0x0102);
+  /// buf.put_slice(b"hello world");
+  /// assert_eq!(buf.read_to_bytes().as_ref(), b"lo world");
+  /// ```
+  // SAFETY: the storage is always initialized, and `chunk_mut` only ever returns
+  // a part of it.
+  #[allow(unsafe_code)]
+  unsafe impl<B> BufMut for Buffer<B>
   where
-    B: AsRef<[u8]>,
+    B: AsRef<[u8]> + AsMut<[u8]>,
   {
-    let data = self.data.as_ref();
-    let size = data.len();
-
-    match () {
-      () if self.written >= size && self.write_cursor == 0 => {
-        dst[..size].copy_from_slice(data);
-        size
+    #[inline]
+    fn remaining_mut(&self) -> usize {
+      match self.size() {
+        0 => 0,
+        _ => usize::MAX,
       }
-      () if self.written > size => {
-        copy(dst, &data[self.write_cursor..]);
-        copy(
-          &mut dst[size - self.write_cursor..],
-          &data[..self.write_cursor],
-        );
-        size
-      }
-      _ => {
-        dst[..self.write_cursor].copy_from_slice(&data[..self.write_cursor]);
-        self.write_cursor
+    }
+
+    #[inline]
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+      self.advance_write(cnt);
+    }
+
+    #[inline]
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+      let cursor = self.write_cursor;
+      UninitSlice::new(&mut self.data.as_mut()[cursor..])
+    }
+
+    #[inline]
+    fn put_slice(&mut self, src: &[u8]) {
+      self.write(src);
+    }
+  }
+};
+
+#[cfg(feature = "rkyv")]
+const _: () = {
+  use rkyv::{
+    Archive,
+    bytecheck::Verify,
+    rancor::{Fallible, Source, fail},
+  };
+
+  /// The error of an archived buffer whose cursors are inconsistent with its storage.
+  #[derive(Debug)]
+  struct InvalidState;
+
+  impl core::fmt::Display for InvalidState {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+      f.write_str("the cursors of the archived buffer are inconsistent with its storage")
+    }
+  }
+
+  impl core::error::Error for InvalidState {}
+
+  // SAFETY: `valid_state` holds for the checked cursors, which is all a deserialized
+  // buffer relies on, and `as_slices` checks it again for unchecked archives.
+  #[allow(unsafe_code)]
+  unsafe impl<B, C> Verify<C> for ArchivedBuffer<B>
+  where
+    B: Archive,
+    B::Archived: AsRef<[u8]>,
+    C: Fallible + ?Sized,
+    C::Error: Source,
+  {
+    fn verify(&self, _: &mut C) -> Result<(), C::Error> {
+      if self.state().is_none() {
+        fail!(InvalidState);
       }
+      Ok(())
     }
   }
 
-  /// Provides a slice of the bytes written. This
-  /// slice should not be written to.
-  #[cfg(any(feature = "std", feature = "alloc"))]
-  #[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
-  #[inline]
-  pub fn read_to_bytes(&self) -> std::borrow::Cow<'_, [u8]>
+  impl<B> ArchivedBuffer<B>
   where
-    B: AsRef<[u8]>,
+    B: Archive,
+    B::Archived: AsRef<[u8]>,
   {
-    let data = self.data.as_ref();
-    let size = data.len();
-    std::borrow::Cow::Borrowed(match () {
-      () if self.written >= size && self.write_cursor == 0 => data,
-      () if self.written > size => {
-        let mut out = std::vec![0; size];
-        copy(&mut out, &data[self.write_cursor..]);
-        copy(
-          &mut out[size - self.write_cursor..],
-          &data[..self.write_cursor],
-        );
-        return out.into();
+    /// Returns the unread bytes of the archived buffer as (older segment, newer segment),
+    /// without deserializing it, e.g. straight from a memory-mapped archive.
+    ///
+    /// Returns `None` if the archived cursors are inconsistent with the archived storage.
+    ///
+    /// ```rust
+    /// use circularbuf::{ArchivedBuffer, Buffer};
+    ///
+    /// let mut buf = Buffer::new(vec![0u8; 6]);
+    /// buf.write(b"hello");
+    /// buf.write(b" world");
+    ///
+    /// let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&buf).unwrap();
+    /// let archived = rkyv::access::<ArchivedBuffer<Vec<u8>>, rkyv::rancor::Error>(&bytes).unwrap();
+    /// assert_eq!(archived.as_slices(), Some((&b" "[..], &b"world"[..])));
+    /// assert_eq!(archived.written(), 11);
+    /// ```
+    pub fn as_slices(&self) -> Option<(&[u8], &[u8])> {
+      let (cursor, len) = self.state()?;
+      Some(segments(self.data.as_ref(), cursor, len))
+    }
+
+    /// Returns the cursor and the unread length, if the archived cursors are consistent
+    /// with the archived storage.
+    fn state(&self) -> Option<(usize, usize)> {
+      let field = |n: &rkyv::primitive::ArchivedUsize| usize::try_from(n.to_native()).ok();
+      let (cursor, written) = (field(&self.write_cursor)?, field(&self.written)?);
+      let (len, retained) = (field(&self.len)?, field(&self.retained)?);
+      let size = self.data.as_ref().len();
+      valid_state(size, cursor, written, len, retained).then_some((cursor, len))
+    }
+
+    /// Returns how many bytes were unread when the buffer was archived.
+    #[inline]
+    pub fn unread(&self) -> usize {
+      self.len.to_native() as usize
+    }
+
+    /// Returns the total number of bytes written to the buffer before it was archived.
+    #[inline]
+    pub fn written(&self) -> usize {
+      self.written.to_native() as usize
+    }
+  }
+};
+
+#[cfg(feature = "postcard")]
+const _: () = {
+  impl<B> Buffer<B> {
+    /// Serializes the full state of the buffer with [`postcard`] into `out`,
+    /// returns the used part of `out`.
+    ///
+    /// This works without allocation, so a `no_std` device can ship its captured data
+    /// over a serial link, and the other end restore it with
+    /// [`from_postcard`](Buffer::from_postcard).
+    ///
+    /// ```rust
+    /// use circularbuf::Buffer;
+    ///
+    /// let mut buf = Buffer::new([0u8; 16]);
+    /// buf.write(b"temperature=21");
+    ///
+    /// let mut out = [0u8; 64];
+    /// let bytes = buf.to_postcard(&mut out).unwrap();
+    ///
+    /// let restored = Buffer::<[u8; 16]>::from_postcard(bytes).unwrap();
+    /// assert_eq!(restored, buf);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "postcard")))]
+    #[inline]
+    pub fn to_postcard<'a>(&self, out: &'a mut [u8]) -> Result<&'a mut [u8], postcard::Error>
+    where
+      B: AsRef<[u8]>,
+    {
+      postcard::to_slice(self, out)
+    }
+
+    /// Deserializes a buffer serialized by [`to_postcard`](Buffer::to_postcard).
+    #[cfg_attr(docsrs, doc(cfg(feature = "postcard")))]
+    #[inline]
+    pub fn from_postcard<'de>(bytes: &'de [u8]) -> Result<Self, postcard::Error>
+    where
+      B: DeserializeStorage<'de>,
+    {
+      postcard::from_bytes(bytes)
+    }
+  }
+};
+
+#[cfg(feature = "hex")]
+const _: () = {
+  use std::{boxed::Box, string::String, vec::Vec};
+
+  const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+  impl<B> Buffer<B> {
+    /// Returns the unread bytes in logical order as a lowercase hex string,
+    /// two digits per byte.
+    ///
+    /// ```rust
+    /// use circularbuf::Buffer;
+    ///
+    /// let mut buf = Buffer::new([0u8; 4]);
+    /// buf.write(&[0x00, 0x01, 0xfe, 0xff, 0x10]);
+    /// assert_eq!(buf.to_hex(), "01feff10");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "hex")))]
+    pub fn to_hex(&self) -> String
+    where
+      B: AsRef<[u8]>,
+    {
+      let (front, back) = self.as_slices();
+      let mut out = String::with_capacity(self.len * 2);
+      for &b in front.iter().chain(back) {
+        out.push(DIGITS[usize::from(b >> 4)] as char);
+        out.push(DIGITS[usize::from(b & 0xf)] as char);
       }
-      _ => &data[..self.write_cursor],
-    })
+      out
+    }
   }
 
-  /// Returns the size of the buffer
-  #[inline]
-  pub fn size(&self) -> usize
+  impl Buffer<Box<[u8]>> {
+    /// Creates a full buffer holding the bytes of a hex string, as produced by
+    /// [`to_hex`](Buffer::to_hex). Both lowercase and uppercase digits are accepted.
+    ///
+    /// ```rust
+    /// use circularbuf::Buffer;
+    ///
+    /// let buf = Buffer::from_hex("01FEff10").unwrap();
+    /// assert_eq!(buf.read_to_bytes().as_ref(), [0x01, 0xfe, 0xff, 0x10]);
+    ///
+    /// assert_eq!(Buffer::from_hex("0g").unwrap_err().position(), 1);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "hex")))]
+    pub fn from_hex(s: &str) -> Result<Self, InvalidHex> {
+      let digit = |i: usize| match s.as_bytes().get(i) {
+        Some(&c) => (c as char)
+          .to_digit(16)
+          .map(|d| d as u8)
+          .ok_or(InvalidHex::new(i)),
+        None => Err(InvalidHex::new(i)),
+      };
+
+      let data = (0..s.len())
+        .step_by(2)
+        .map(|i| Ok(digit(i)? << 4 | digit(i + 1)?))
+        .collect::<Result<Vec<u8>, _>>()?;
+      Ok(Self::new_full(data.into_boxed_slice()))
+    }
+  }
+};
+
+#[cfg(feature = "zeroize")]
+const _: () = {
+  use zeroize::{DefaultIsZeroes, Zeroize};
+
+  /// Wipes the storage and [`reset`](Buffer::reset)s the buffer, like
+  /// [`reset_secure`](Buffer::reset_secure). The size of the storage is unchanged.
+  ///
+  /// `Buffer` is `Copy`, so it cannot wipe itself on drop: wrap it in
+  /// [`Zeroizing`](zeroize::Zeroizing) for that.
+  impl<B> Zeroize for Buffer<B>
   where
-    B: AsRef<[u8]>,
+    B: AsMut<[u8]>,
   {
-    self.data.as_ref().len()
+    #[inline]
+    fn zeroize(&mut self) {
+      self.reset_secure::<u8>();
+    }
   }
 
-  /// Returns the total number of bytes written to the buffer
-  #[inline]
-  pub const fn written(&self) -> usize {
-    self.written
+  impl<B> Buffer<B> {
+    /// Resets the buffer like [`reset`](Buffer::reset), and wipes the whole storage,
+    /// for buffers which may have captured credentials or key material.
+    ///
+    /// The wipe is not optimized away by the compiler.
+    ///
+    /// ```rust
+    /// use circularbuf::Buffer;
+    ///
+    /// let mut buf = Buffer::new([0u8; 8]);
+    /// buf.write(b"password");
+    /// buf.reset_secure();
+    ///
+    /// assert_eq!(buf.unread(), 0);
+    /// assert_eq!(buf.into_inner(), [0; 8]);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+    pub fn reset_secure<T>(&mut self)
+    where
+      B: AsMut<[T]>,
+      T: DefaultIsZeroes,
+    {
+      self.data.as_mut().zeroize();
+      self.reset();
+    }
   }
+};
 
-  /// Resets the buffer so it has no content.
-  #[inline]
-  pub const fn reset(&mut self) {
-    self.write_cursor = 0;
-    self.written = 0;
+#[cfg(feature = "arbitrary")]
+const _: () = {
+  use arbitrary::{Arbitrary, Result, Unstructured};
+
+  /// Generates buffers in arbitrary, but internally consistent, states: the cursor is
+  /// within the storage, and the unread length fits both the storage and the total
+  /// number of elements written.
+  impl<'a, T> Arbitrary<'a> for Buffer<std::vec::Vec<T>>
+  where
+    T: Arbitrary<'a>,
+  {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+      let data: std::vec::Vec<T> = u.arbitrary()?;
+      let size = data.len();
+      let write_cursor = match size {
+        0 => 0,
+        size => u.choose_index(size)?,
+      };
+      let len = u.int_in_range(0..=size)?;
+      let written = len + usize::from(u.arbitrary::<u16>()?);
+      Ok(Self {
+        data,
+        write_cursor,
+        written,
+        len,
+        retained: len,
+        stats: Counters::default(),
+      })
+    }
   }
+};
 
-  /// Consumes the buffer and returns the underlying data.
-  #[inline]
-  pub fn into_inner(self) -> B {
-    self.data
+#[cfg(feature = "defmt")]
+const _: () = {
+  use defmt::{Format, Formatter};
+
+  /// The maximum number of the newest bytes shown by the [`Format`] implementation.
+  const PREVIEW: usize = 16;
+
+  impl<B> Format for Buffer<B>
+  where
+    B: AsRef<[u8]>,
+  {
+    fn format(&self, f: Formatter<'_>) {
+      let mut preview = [0u8; PREVIEW];
+      let n = self.tail(PREVIEW, &mut preview);
+      defmt::write!(
+        f,
+        "Buffer {{ size: {=usize}, written: {=usize}, unread: {=usize}, tail: {=[u8]} }}",
+        self.size(),
+        self.written,
+        self.len,
+        preview[..n]
+      );
+    }
   }
-}
+};
 
-#[cfg(feature = "std")]
+#[cfg(feature = "embedded-io")]
 const _: () = {
-  use std::io::Write;
+  use core::convert::Infallible;
+  use embedded_io::{ErrorType, Read, Write};
+
+  impl<B> ErrorType for Buffer<B> {
+    type Error = Infallible;
+  }
+
+  impl<B> Read for Buffer<B>
+  where
+    B: AsRef<[u8]>,
+  {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+      Ok(self.read(buf))
+    }
+  }
 
   impl<B> Write for Buffer<B>
   where
     B: AsMut<[u8]>,
   {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
       Ok(self.write(buf))
     }
 
-    fn flush(&mut self) -> std::io::Result<()> {
+    fn flush(&mut self) -> Result<(), Self::Error> {
       Ok(())
     }
   }
 };
 
-#[cfg(all(feature = "tokio", feature = "std"))]
+#[cfg(feature = "embedded-io-async")]
 const _: () = {
-  use core::{
-    pin::Pin,
-    task::{Context, Poll},
-  };
-  use tokio::io::AsyncWrite;
+  use embedded_io_async::{Read, Write};
 
-  impl<B> AsyncWrite for Buffer<B>
+  impl<B> Read for Buffer<B>
   where
-    B: AsMut<[u8]> + Unpin,
+    B: AsRef<[u8]>,
   {
-    fn poll_write(
-      self: Pin<&mut Self>,
-      _: &mut Context<'_>,
-      buf: &[u8],
-    ) -> Poll<Result<usize, std::io::Error>> {
-      Poll::Ready(Ok(self.get_mut().write(buf)))
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+      Ok(self.read(buf))
     }
+  }
 
-    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), std::io::Error>> {
-      Poll::Ready(Ok(()))
+  impl<B> Write for Buffer<B>
+  where
+    B: AsMut<[u8]>,
+  {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+      Ok(self.write(buf))
     }
 
-    fn poll_shutdown(
-      self: Pin<&mut Self>,
-      _: &mut Context<'_>,
-    ) -> Poll<Result<(), std::io::Error>> {
-      Poll::Ready(Ok(()))
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+      Ok(())
     }
   }
 };
 
-#[cfg(all(feature = "std", feature = "futures-io"))]
+#[cfg(feature = "futures-sink")]
 const _: () = {
   use core::{
+    convert::Infallible,
     pin::Pin,
     task::{Context, Poll},
   };
-  use futures_io::AsyncWrite;
+  use futures_sink::Sink;
 
-  impl<B> AsyncWrite for Buffer<B>
+  /// Every item is written to the buffer, e.g. a stream pipeline can be terminated
+  /// by the buffer with `stream.forward(buffer)`, capturing whatever it produced last.
+  impl<B, I> Sink<I> for Buffer<B>
   where
     B: AsMut<[u8]> + Unpin,
+    I: AsRef<[u8]>,
   {
-    fn poll_write(
-      self: Pin<&mut Self>,
-      _: &mut Context<'_>,
-      buf: &[u8],
-    ) -> Poll<Result<usize, std::io::Error>> {
-      Poll::Ready(Ok(self.get_mut().write(buf)))
+    type Error = Infallible;
+
+    fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+      Poll::Ready(Ok(()))
     }
 
-    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), std::io::Error>> {
+    fn start_send(self: Pin<&mut Self>, item: I) -> Result<(), Self::Error> {
+      self.get_mut().write(item.as_ref());
+      Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
       Poll::Ready(Ok(()))
     }
 
-    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), std::io::Error>> {
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
       Poll::Ready(Ok(()))
     }
   }
 };
 
+/// Writes all of the two segments in order with `poll_write`, like `write_all`,
+/// returns the number of bytes written.
+#[cfg(all(feature = "std", any(feature = "tokio", feature = "futures-io")))]
+async fn write_all_async<F>(mut poll_write: F, front: &[u8], back: &[u8]) -> std::io::Result<usize>
+where
+  F: FnMut(&mut core::task::Context<'_>, &[u8]) -> core::task::Poll<std::io::Result<usize>>,
+{
+  use std::io::{Error, ErrorKind};
+
+  for mut segment in [front, back] {
+    while !segment.is_empty() {
+      match core::future::poll_fn(|cx| poll_write(cx, segment)).await {
+        Ok(0) => return Err(Error::from(ErrorKind::WriteZero)),
+        Ok(n) => segment = &segment[n..],
+        Err(e) if e.kind() == ErrorKind::Interrupted => {}
+        Err(e) => return Err(e),
+      }
+    }
+  }
+  Ok(front.len() + back.len())
+}
+
+/// Copies the two segments in order into `dst`, returns the number of elements copied.
+#[inline]
+pub(crate) fn copy_segments<T: Copy>(dst: &mut [T], front: &[T], back: &[T]) -> usize {
+  let n = copy(dst, front);
+  n + copy(&mut dst[n..], back)
+}
+
+/// Returns whether the cursors of a buffer are consistent with a storage of `size`
+/// elements, i.e. whether they could have been left by writes and reads.
+#[cfg(any(feature = "rkyv", feature = "serde"))]
+pub(crate) const fn valid_state(
+  size: usize,
+  write_cursor: usize,
+  written: usize,
+  len: usize,
+  retained: usize,
+) -> bool {
+  (write_cursor < size || write_cursor == 0 && size == 0)
+    && len <= retained
+    && retained <= size
+    && retained <= written
+}
+
+/// Returns the `len` elements of `data` ending right before `cursor`,
+/// as (older segment, newer segment).
+pub(crate) fn segments<T>(data: &[T], cursor: usize, len: usize) -> (&[T], &[T]) {
+  if len == 0 {
+    return (&[], &[]);
+  }
+
+  let size = data.len();
+  let start = wrap(cursor + size - len, size);
+  if start + len <= size {
+    (&data[start..start + len], &[])
+  } else {
+    (&data[start..], &data[..cursor])
+  }
+}
+
+/// Wraps the position `pos` into a storage of `size` elements, with a bitmask instead of
+/// a division when `size` is a power of two. `size` must not be zero.
+#[inline(always)]
+pub(crate) const fn wrap(pos: usize, size: usize) -> usize {
+  if size.is_power_of_two() {
+    pos & (size - 1)
+  } else {
+    pos % size
+  }
+}
+
+/// Copies all of `src` into `dst`, starting at `at`. The caller must ensure
+/// `at + src.len() <= dst.len()`, which is only checked in debug builds
+/// when the `unsafe-perf` feature is enabled.
+#[inline(always)]
+fn copy_at<T: Copy>(dst: &mut [T], at: usize, src: &[T]) {
+  #[cfg(not(feature = "unsafe-perf"))]
+  dst[at..at + src.len()].copy_from_slice(src);
+
+  #[cfg(feature = "unsafe-perf")]
+  {
+    debug_assert!(at + src.len() <= dst.len());
+    // SAFETY: the range `at..at + src.len()` is in bounds of `dst` as required above,
+    // and `src` cannot overlap `dst` as `dst` is borrowed mutably.
+    #[allow(unsafe_code)]
+    unsafe {
+      core::ptr::copy_nonoverlapping(src.as_ptr(), dst.as_mut_ptr().add(at), src.len());
+    }
+  }
+}
+
 /// Copies elements from a source slice into a destination slice. (As a special case, it also will copy bytes from a string to a slice of bytes.) The source and destination may overlap.
 /// Copy returns the number of elements copied, which will be the minimum of `src.len()` and `dst.len()`.
 #[inline]
-fn copy(dst: &mut [u8], src: &[u8]) -> usize {
+fn copy<T: Copy>(dst: &mut [T], src: &[T]) -> usize {
   let min_len = core::cmp::min(src.len(), dst.len());
   dst[..min_len].copy_from_slice(&src[..min_len]);
   min_len
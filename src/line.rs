@@ -0,0 +1,171 @@
+use crate::{Buffer, View};
+
+/// A circular buffer retaining the most recent complete lines, rather than the
+/// most recent bytes.
+///
+/// At most `max_lines` complete lines are kept, and on overflow (either of the line
+/// count or of the byte storage) the oldest lines are discarded as a whole, so the
+/// first line is never torn. The trailing line which is not terminated by `\n` yet
+/// is kept, but not yielded by [`lines`](LineBuffer::lines).
+///
+/// ```rust
+/// use circularbuf::LineBuffer;
+///
+/// let mut buf = LineBuffer::new([0u8; 64], 2);
+/// buf.write(b"first\nsecond\nthird\nfour");
+///
+/// let lines = buf.lines().map(|l| l.to_vec()).collect::<Vec<_>>();
+/// assert_eq!(lines, [b"second".to_vec(), b"third".to_vec()]);
+///
+/// buf.write(b"th\n");
+/// assert_eq!(buf.lines().last().unwrap(), b"fourth");
+/// ```
+#[derive(Debug, Clone)]
+pub struct LineBuffer<B> {
+  buf: Buffer<B>,
+  max_lines: usize,
+  lines: usize,
+  /// Whether the beginning of the line being written was lost, in which case
+  /// input is skipped up to the next line break.
+  torn: bool,
+}
+
+impl<B> LineBuffer<B> {
+  /// Creates a new line buffer with the given storage, retaining at most `max_lines` lines.
+  #[inline]
+  pub const fn new(data: B, max_lines: usize) -> Self {
+    Self {
+      buf: Buffer::new(data),
+      max_lines,
+      lines: 0,
+      torn: false,
+    }
+  }
+
+  /// Writes `data` to the buffer, discarding the oldest lines if necessary,
+  /// returns the length of `data`.
+  pub fn write(&mut self, mut data: &[u8]) -> usize
+  where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+  {
+    let n = data.len();
+    if self.torn {
+      match data.iter().position(|b| *b == b'\n') {
+        Some(i) => {
+          data = &data[i + 1..];
+          self.torn = false;
+        }
+        None => return n,
+      }
+    }
+
+    let mut evicted_lines = 0;
+    let mut last_evicted = None;
+    self.buf.write_with_evict(data, |evicted| {
+      evicted_lines += count_lines(evicted);
+      last_evicted = evicted.last().copied();
+    });
+    self.lines = self.lines + count_lines(data) - evicted_lines;
+
+    // The oldest line lost its beginning, drop the rest of it.
+    if last_evicted.is_some_and(|b| b != b'\n') {
+      match self.first_line_len() {
+        Some(len) => {
+          self.buf.discard_oldest(len + 1);
+          self.lines -= 1;
+        }
+        None => {
+          self.buf.discard_oldest(self.buf.unread());
+          self.torn = true;
+        }
+      }
+    }
+
+    while self.lines > self.max_lines {
+      if let Some(len) = self.first_line_len() {
+        self.buf.discard_oldest(len + 1);
+      }
+      self.lines -= 1;
+    }
+    n
+  }
+
+  /// Returns an iterator over the retained complete lines, from the oldest to the newest,
+  /// without the trailing `\n`.
+  pub fn lines(&self) -> impl Iterator<Item = View<'_, u8>>
+  where
+    B: AsRef<[u8]>,
+  {
+    let (front, back) = self.buf.as_slices();
+    let mut rest = View::new(front, back);
+    (0..self.lines).map(move |_| {
+      let len = rest.position_of(b'\n').unwrap_or(rest.len());
+      let (line, tail) = rest.split_at(len);
+      rest = tail.split_at(tail.len().min(1)).1;
+      line
+    })
+  }
+
+  /// Returns the number of retained complete lines.
+  #[inline]
+  pub const fn len(&self) -> usize {
+    self.lines
+  }
+
+  /// Returns `true` if no complete line is retained.
+  #[inline]
+  pub const fn is_empty(&self) -> bool {
+    self.lines == 0
+  }
+
+  /// Returns the maximum number of retained lines.
+  #[inline]
+  pub const fn max_lines(&self) -> usize {
+    self.max_lines
+  }
+
+  /// Resets the buffer so it has no content.
+  #[inline]
+  pub const fn reset(&mut self) {
+    self.buf.reset();
+    self.lines = 0;
+    self.torn = false;
+  }
+
+  /// Returns the underlying byte [`Buffer`].
+  #[inline]
+  pub const fn as_buffer(&self) -> &Buffer<B> {
+    &self.buf
+  }
+
+  /// Returns the length of the oldest retained line, without the trailing `\n`.
+  fn first_line_len(&self) -> Option<usize>
+  where
+    B: AsRef<[u8]>,
+  {
+    self.buf.iter().position(|b: u8| b == b'\n')
+  }
+}
+
+#[inline]
+fn count_lines(data: &[u8]) -> usize {
+  data.iter().filter(|b| **b == b'\n').count()
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+  use std::io;
+
+  impl<B> io::Write for LineBuffer<B>
+  where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+  {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+      Ok(self.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+};
@@ -0,0 +1,116 @@
+use std::{
+  string::String,
+  sync::{Mutex, MutexGuard, PoisonError},
+  vec::Vec,
+};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::RecordBuffer;
+
+/// A [`Log`] implementation keeping an in-memory tail of the most recent log records.
+///
+/// Each record is formatted as `LEVEL target: message` and stored as a whole in a
+/// [`RecordBuffer`], so the oldest records are evicted entirely and the tail never
+/// starts in the middle of a line. Records larger than the buffer are truncated.
+///
+/// ```rust
+/// use circularbuf::RingLogger;
+/// use log::LevelFilter;
+///
+/// static LOGGER: RingLogger<[u8; 4096]> = RingLogger::new([0; 4096], LevelFilter::Info);
+///
+/// log::set_logger(&LOGGER).unwrap();
+/// log::set_max_level(LevelFilter::Info);
+///
+/// log::info!(target: "app", "started");
+/// log::debug!("filtered out");
+///
+/// assert_eq!(LOGGER.lines(), ["INFO app: started"]);
+/// ```
+#[derive(Debug)]
+pub struct RingLogger<B> {
+  records: Mutex<RecordBuffer<B>>,
+  level: LevelFilter,
+}
+
+impl<B> RingLogger<B> {
+  /// Creates a new logger storing the records at or above `level` in `data`.
+  #[inline]
+  pub const fn new(data: B, level: LevelFilter) -> Self {
+    Self {
+      records: Mutex::new(RecordBuffer::new(data)),
+      level,
+    }
+  }
+
+  /// Returns the retained log lines, from the oldest to the newest.
+  pub fn lines(&self) -> Vec<String>
+  where
+    B: AsRef<[u8]>,
+  {
+    self
+      .lock()
+      .records()
+      .map(|record| String::from_utf8_lossy(&record.to_vec()).into_owned())
+      .collect()
+  }
+
+  /// Returns the retained log lines, from the oldest to the newest, each terminated
+  /// by a newline.
+  pub fn dump(&self) -> String
+  where
+    B: AsRef<[u8]>,
+  {
+    self
+      .lines()
+      .into_iter()
+      .fold(String::new(), |mut out, line| {
+        out.push_str(&line);
+        out.push('\n');
+        out
+      })
+  }
+
+  /// Removes all the retained log lines.
+  #[inline]
+  pub fn clear(&self) {
+    self.lock().reset();
+  }
+
+  #[inline]
+  fn lock(&self) -> MutexGuard<'_, RecordBuffer<B>> {
+    self.records.lock().unwrap_or_else(PoisonError::into_inner)
+  }
+}
+
+impl<B> Log for RingLogger<B>
+where
+  B: AsRef<[u8]> + AsMut<[u8]> + Send,
+{
+  #[inline]
+  fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+    metadata.level() <= self.level
+  }
+
+  fn log(&self, record: &Record<'_>) {
+    if !self.enabled(record.metadata()) {
+      return;
+    }
+
+    let mut line = std::format!("{} {}: {}", record.level(), record.target(), record.args());
+    let mut records = self.lock();
+    let max = records.max_record_len();
+    if line.len() > max {
+      let mut end = max;
+      while !line.is_char_boundary(end) {
+        end -= 1;
+      }
+      line.truncate(end);
+    }
+    let _ = records.write(line.as_bytes());
+  }
+
+  #[inline]
+  fn flush(&self) {}
+}
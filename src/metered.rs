@@ -0,0 +1,118 @@
+use metrics::{Counter, Gauge, SharedString};
+
+use crate::Buffer;
+
+/// The name of the counter of elements written.
+const BYTES_WRITTEN: &str = "circularbuf_bytes_written";
+/// The name of the counter of unread elements overwritten.
+const BYTES_OVERWRITTEN: &str = "circularbuf_bytes_overwritten";
+/// The name of the gauge of the fill ratio, from `0.0` to `1.0`.
+const FILL_RATIO: &str = "circularbuf_fill_ratio";
+
+/// A circular buffer which reports its activity through the [`metrics`] facade.
+///
+/// Created by [`Buffer::with_metrics`]. Every metric carries a `buffer` label set to
+/// the configured name:
+///
+/// - `circularbuf_bytes_written`: a counter of the elements written,
+/// - `circularbuf_bytes_overwritten`: a counter of the unread elements which were
+///   overwritten before they could be read,
+/// - `circularbuf_fill_ratio`: a gauge of the unread elements over the buffer size.
+///
+/// The metric handles are registered once, when the buffer is created, so the recorder
+/// must be installed first.
+///
+/// ```rust
+/// use circularbuf::Buffer;
+///
+/// let mut buf = Buffer::new([0u8; 64]).with_metrics("requests");
+/// buf.write(b"GET /index.html");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MeteredBuffer<B> {
+  buf: Buffer<B>,
+  written: Counter,
+  overwritten: Counter,
+  fill_ratio: Gauge,
+}
+
+impl<B> Buffer<B> {
+  /// Turns the buffer into a [`MeteredBuffer`] whose metrics are labeled with `name`.
+  pub fn with_metrics(self, name: impl Into<SharedString>) -> MeteredBuffer<B> {
+    let name = name.into();
+    MeteredBuffer {
+      buf: self,
+      written: metrics::counter!(BYTES_WRITTEN, "buffer" => name.clone()),
+      overwritten: metrics::counter!(BYTES_OVERWRITTEN, "buffer" => name.clone()),
+      fill_ratio: metrics::gauge!(FILL_RATIO, "buffer" => name),
+    }
+  }
+}
+
+impl<B> MeteredBuffer<B> {
+  /// Writes up to len(buf) elements to the internal ring,
+  /// overriding older data if necessary.
+  pub fn write<T>(&mut self, buf: &[T]) -> usize
+  where
+    B: AsRef<[T]> + AsMut<[T]>,
+    T: Copy,
+  {
+    let report = self.buf.write_with_report(buf);
+    self.written.increment(report.written() as u64);
+    if report.overwritten() != 0 {
+      self.overwritten.increment(report.overwritten() as u64);
+    }
+    self.update_fill_ratio();
+    report.written()
+  }
+
+  /// Reads up to `dst.len()` of the oldest unread elements into `dst` and consumes them,
+  /// returns number of elements readed.
+  pub fn read<T>(&mut self, dst: &mut [T]) -> usize
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    let n = self.buf.read(dst);
+    self.update_fill_ratio();
+    n
+  }
+
+  fn update_fill_ratio<T>(&self)
+  where
+    B: AsRef<[T]>,
+  {
+    let ratio = match self.buf.size() {
+      0 => 0.0,
+      size => self.buf.len as f64 / size as f64,
+    };
+    self.fill_ratio.set(ratio);
+  }
+
+  /// Returns the underlying buffer.
+  #[inline]
+  pub const fn as_buffer(&self) -> &Buffer<B> {
+    &self.buf
+  }
+
+  /// Consumes the metered buffer and returns the underlying buffer.
+  #[inline]
+  pub fn into_buffer(self) -> Buffer<B> {
+    self.buf
+  }
+}
+
+impl<B> std::io::Write for MeteredBuffer<B>
+where
+  B: AsRef<[u8]> + AsMut<[u8]>,
+{
+  #[inline]
+  fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    Ok(MeteredBuffer::write(self, buf))
+  }
+
+  #[inline]
+  fn flush(&mut self) -> std::io::Result<()> {
+    Ok(())
+  }
+}
@@ -0,0 +1,152 @@
+use std::{io, path::Path};
+
+use memmap2::MmapMut;
+
+use crate::{
+  Buffer,
+  header::{self, Header},
+};
+
+/// The storage of a [`MmapBuffer`]: the data region of a memory-mapped file.
+#[derive(Debug)]
+pub struct MmapRegion(MmapMut);
+
+impl AsRef<[u8]> for MmapRegion {
+  #[inline]
+  fn as_ref(&self) -> &[u8] {
+    &self.0[header::LEN..]
+  }
+}
+
+impl AsMut<[u8]> for MmapRegion {
+  #[inline]
+  fn as_mut(&mut self) -> &mut [u8] {
+    &mut self.0[header::LEN..]
+  }
+}
+
+/// A circular buffer backed by a memory-mapped file.
+///
+/// The retained bytes live in the page cache, so they survive a crash of the process,
+/// and the cursor is kept in a small header in front of the data, so reopening
+/// the file recovers the buffer. This makes it usable as a black-box recorder.
+///
+/// Before any retained byte is overwritten, the header is updated to no longer include
+/// it, so the recovered data is never a mix of old and new bytes.
+/// Use [`flush`](MmapBuffer::flush) to also survive an operating system crash.
+///
+/// ```rust,no_run
+/// use circularbuf::MmapBuffer;
+///
+/// // SAFETY: no other process touches the file
+/// let mut buf = unsafe { MmapBuffer::open("recorder.bin", 4096)? };
+/// buf.write(b"hello world");
+/// drop(buf);
+///
+/// let buf = unsafe { MmapBuffer::open("recorder.bin", 4096)? };
+/// assert_eq!(buf.as_buffer().read_to_bytes().as_ref(), b"hello world");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct MmapBuffer {
+  buf: Buffer<MmapRegion>,
+  seq: u64,
+}
+
+impl MmapBuffer {
+  /// Opens the buffer stored in the file at `path`, or creates it with room for
+  /// `capacity` bytes if the file does not exist or is empty.
+  ///
+  /// Fails with [`io::ErrorKind::InvalidData`] if the file is not a valid buffer,
+  /// or was created with another capacity.
+  ///
+  /// # Safety
+  ///
+  /// The file is mapped in memory, so it must not be truncated, resized or modified by
+  /// anything else, another `MmapBuffer` or another process included, while the returned
+  /// buffer is alive. Otherwise the buffer may read bytes changing under it, or fault.
+  #[allow(unsafe_code)]
+  pub unsafe fn open(path: impl AsRef<Path>, capacity: usize) -> io::Result<Self> {
+    let (file, fresh) = header::open(path.as_ref(), capacity)?;
+
+    // SAFETY: the caller guarantees the file is exclusively owned by this buffer.
+    let map = unsafe { MmapMut::map_mut(&file)? };
+
+    if fresh {
+      let mut this = Self {
+        buf: Buffer::new(MmapRegion(map)),
+        seq: 0,
+      };
+      this.store(this.buf.len);
+      return Ok(this);
+    }
+
+    let header = header::recover(&map[..header::LEN], capacity)?;
+    Ok(Self {
+      buf: header.restore(MmapRegion(map)),
+      seq: header.seq + 1,
+    })
+  }
+
+  /// Writes `buf` into the ring, overwriting the oldest data if necessary,
+  /// returns the number of bytes written.
+  pub fn write(&mut self, buf: &[u8]) -> usize {
+    let keep = self.buf.size() - buf.len().min(self.buf.size());
+    if self.buf.len > keep {
+      self.store(keep);
+    }
+
+    let n = self.buf.write(buf);
+    self.store(self.buf.len);
+    n
+  }
+
+  /// Reads up to `dst.len()` of the oldest unread bytes into `dst` and consumes them,
+  /// returns the number of bytes read.
+  pub fn read(&mut self, dst: &mut [u8]) -> usize {
+    let n = self.buf.read(dst);
+    self.store(self.buf.len);
+    n
+  }
+
+  /// Resets the buffer so it has no content.
+  pub fn reset(&mut self) {
+    self.buf.reset();
+    self.store(0);
+  }
+
+  /// Flushes the data and the header to the file.
+  #[inline]
+  pub fn flush(&self) -> io::Result<()> {
+    self.buf.data.0.flush()
+  }
+
+  /// Returns a reference to the underlying buffer.
+  #[inline]
+  pub const fn as_buffer(&self) -> &Buffer<MmapRegion> {
+    &self.buf
+  }
+
+  /// Stores a header describing the buffer with `len` retained bytes.
+  fn store(&mut self, len: usize) {
+    let header = Header {
+      len,
+      ..Header::of(&self.buf, self.seq)
+    };
+    let slot = header.slot();
+    self.buf.data.0[slot..slot + header::SLOT].copy_from_slice(&header.encode());
+    self.seq += 1;
+  }
+}
+
+impl io::Write for MmapBuffer {
+  #[inline]
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    Ok(self.write(buf))
+  }
+
+  #[inline]
+  fn flush(&mut self) -> io::Result<()> {
+    MmapBuffer::flush(self)
+  }
+}
@@ -0,0 +1,142 @@
+use core::sync::atomic::{Ordering, fence};
+use std::{sync::Arc, vec::Vec};
+
+use crate::{Buffer, shared::Shared};
+
+/// A circular buffer many threads can append to concurrently without locks,
+/// keeping the overwrite-oldest semantics of [`Buffer`].
+///
+/// Each write reserves its range of the stream with an atomic cursor, so writers
+/// copy their data in parallel. Cloning a `MpscBuffer` is cheap and yields
+/// a handle to the same ring. Snapshots of the tail never contain torn writes.
+///
+/// ```rust
+/// use circularbuf::MpscBuffer;
+///
+/// let buf = MpscBuffer::with_capacity(64);
+///
+/// std::thread::scope(|s| {
+///   for _ in 0..4 {
+///     let buf = buf.clone();
+///     s.spawn(move || buf.write(b"trace"));
+///   }
+/// });
+///
+/// assert_eq!(buf.written(), 20);
+/// assert_eq!(buf.to_vec(), b"tracetracetracetrace");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MpscBuffer {
+  shared: Arc<Shared>,
+}
+
+impl MpscBuffer {
+  /// Creates a new concurrent buffer of `capacity` bytes.
+  #[inline]
+  pub fn with_capacity(capacity: usize) -> Self {
+    Self {
+      shared: Arc::new(Shared::new(capacity, 0, true)),
+    }
+  }
+
+  /// Writes up to len(buf) bytes to the internal ring,
+  /// overriding older data if necessary.
+  pub fn write(&self, buf: &[u8]) -> usize {
+    let shared = &*self.shared;
+    let cap = shared.capacity();
+    let n = buf.len();
+    if cap == 0 || n == 0 {
+      return n;
+    }
+
+    let start = shared.reserved.fetch_add(n, Ordering::Relaxed);
+    let end = start.wrapping_add(n);
+    // Orders the reservation before the stores below, so snapshots can detect torn copies.
+    fence(Ordering::Release);
+
+    // When the reservations in flight exceed the capacity, earlier writers may still be
+    // copying into the slots of this write, which waits for them so neither tears the other.
+    let overlap = if n < cap {
+      end.wrapping_sub(cap)
+    } else {
+      start
+    };
+    wait(|| shared.head.load(Ordering::Acquire).wrapping_sub(overlap) as isize >= 0);
+    let src = &buf[n.saturating_sub(cap)..];
+    shared.store(end.wrapping_sub(src.len()), src);
+
+    // Publish in reservation order, once all the earlier writers are done.
+    wait(|| shared.head.load(Ordering::Acquire) == start);
+    shared.head.store(end, Ordering::Release);
+    n
+  }
+
+  /// Copies the newest retained bytes that fit into `dst`, returns the number of bytes copied.
+  #[inline]
+  pub fn snapshot(&self, dst: &mut [u8]) -> usize {
+    self.shared.snapshot(dst)
+  }
+
+  /// Returns a copy of all the retained bytes, from the oldest to the newest.
+  pub fn to_vec(&self) -> Vec<u8> {
+    let mut out = std::vec![0; self.shared.capacity()];
+    let n = self.snapshot(&mut out);
+    out.truncate(n);
+    out
+  }
+
+  /// Returns the total number of bytes published to the buffer.
+  #[inline]
+  pub fn written(&self) -> usize {
+    self.shared.head.load(Ordering::Acquire)
+  }
+
+  /// Returns the capacity of the ring.
+  #[inline]
+  pub fn capacity(&self) -> usize {
+    self.shared.capacity()
+  }
+}
+
+/// Spins until `done` returns `true`.
+#[inline]
+fn wait(done: impl Fn() -> bool) {
+  while !done() {
+    #[cfg(feature = "std")]
+    std::thread::yield_now();
+    #[cfg(not(feature = "std"))]
+    core::hint::spin_loop();
+  }
+}
+
+impl<B> From<Buffer<B>> for MpscBuffer
+where
+  B: AsRef<[u8]>,
+{
+  fn from(buf: Buffer<B>) -> Self {
+    let shared = Shared::new(buf.size(), buf.unread(), true);
+    let (front, back) = buf.as_slices();
+    shared.store(0, front);
+    shared.store(front.len(), back);
+    Self {
+      shared: Arc::new(shared),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+  use std::io;
+
+  impl io::Write for &MpscBuffer {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+      Ok(MpscBuffer::write(self, buf))
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+      Ok(())
+    }
+  }
+};
@@ -0,0 +1,148 @@
+use core::ops::{Deref, DerefMut};
+use std::{
+  boxed::Box,
+  sync::{Arc, Mutex, PoisonError},
+  vec::Vec,
+};
+
+use crate::Buffer;
+
+/// A pool of heap allocated buffers of the same capacity, which reuses their storage.
+///
+/// [`get`](BufferPool::get) hands out an empty buffer, whose storage goes back to the pool
+/// when the [`PooledBuffer`] is dropped, so short-lived captures, e.g. one per connection,
+/// do not allocate once the pool is warm. At most `max_idle` storages are kept, the
+/// others are freed, as well as the storages whose capacity changed, e.g. by a resize.
+///
+/// Cloning a `BufferPool` is cheap and yields a handle to the same pool.
+///
+/// ```rust
+/// use circularbuf::BufferPool;
+///
+/// let pool = BufferPool::new(4096, 64);
+///
+/// let mut tail = pool.get();
+/// tail.write(b"GET / HTTP/1.1\r\n");
+/// drop(tail);
+///
+/// // The storage is reused, and the buffer starts empty
+/// assert_eq!(pool.idle(), 1);
+/// assert!(pool.get().is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufferPool {
+  inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+  capacity: usize,
+  max_idle: usize,
+  free: Mutex<Vec<Box<[u8]>>>,
+}
+
+impl BufferPool {
+  /// Creates a new pool of buffers of `capacity` bytes, keeping at most `max_idle`
+  /// storages for reuse.
+  pub fn new(capacity: usize, max_idle: usize) -> Self {
+    Self {
+      inner: Arc::new(Inner {
+        capacity,
+        max_idle,
+        free: Mutex::new(Vec::new()),
+      }),
+    }
+  }
+
+  /// Returns an empty buffer, reusing an idle storage if any.
+  ///
+  /// Storages are zeroed when they go back to the pool, so nothing written by a previous
+  /// user can be read back, even through [`into_inner`](PooledBuffer::into_inner) or a
+  /// [`write_grant`](Buffer::write_grant).
+  pub fn get(&self) -> PooledBuffer {
+    let data = self
+      .inner
+      .free
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .pop();
+    let buf = match data {
+      Some(data) => Buffer::new(data),
+      None => Buffer::with_capacity(self.inner.capacity),
+    };
+    PooledBuffer {
+      buf,
+      pool: self.inner.clone(),
+    }
+  }
+
+  /// Returns the capacity of the buffers.
+  #[inline]
+  pub fn capacity(&self) -> usize {
+    self.inner.capacity
+  }
+
+  /// Returns the number of idle storages waiting for reuse.
+  pub fn idle(&self) -> usize {
+    self
+      .inner
+      .free
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .len()
+  }
+}
+
+/// A buffer handed out by a [`BufferPool`], which returns its storage to the pool
+/// when dropped.
+///
+/// It dereferences to the [`Buffer`].
+#[derive(Debug)]
+pub struct PooledBuffer {
+  buf: Buffer<Box<[u8]>>,
+  pool: Arc<Inner>,
+}
+
+impl PooledBuffer {
+  /// Detaches the buffer from the pool, so its storage is never reused.
+  pub fn into_inner(mut self) -> Buffer<Box<[u8]>> {
+    core::mem::replace(&mut self.buf, Buffer::new(Box::default()))
+  }
+}
+
+impl Deref for PooledBuffer {
+  type Target = Buffer<Box<[u8]>>;
+
+  #[inline]
+  fn deref(&self) -> &Self::Target {
+    &self.buf
+  }
+}
+
+impl DerefMut for PooledBuffer {
+  #[inline]
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.buf
+  }
+}
+
+impl Drop for PooledBuffer {
+  fn drop(&mut self) {
+    let mut data = core::mem::take(&mut self.buf.data);
+    // Detached buffers leave an empty storage behind
+    if data.len() != self.pool.capacity {
+      return;
+    }
+
+    // The next user must not see anything written by this one
+    data.fill(0);
+    let mut free = self
+      .pool
+      .free
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner);
+    if free.len() < self.pool.max_idle {
+      free.push(data);
+    }
+  }
+}
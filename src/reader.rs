@@ -0,0 +1,102 @@
+use crate::{Buffer, Lagged, copy_segments};
+
+/// An independent read cursor over a [`Buffer`], created by [`Buffer::reader`].
+///
+/// Every reader tracks its own logical position in the stream, so several consumers can
+/// read the same data without interfering with each other, nor with [`Buffer::read`].
+/// A reader does not borrow the buffer, which is passed to every read instead, so the
+/// buffer can keep being written in between.
+///
+/// If the writer laps a reader, overwriting data it has not read yet, the next read
+/// returns a [`Lagged`] error reporting the amount of lost data, and the reader resumes
+/// from the oldest retained element.
+///
+/// ```rust
+/// use circularbuf::Buffer;
+///
+/// let mut buf = Buffer::new([0u8; 8]);
+/// let mut ui = buf.reader();
+/// let mut sink = buf.reader();
+///
+/// buf.write(b"hello");
+/// let mut dst = [0u8; 8];
+/// assert_eq!(ui.read(&buf, &mut dst), Ok(5));
+/// assert_eq!(&dst[..5], b"hello");
+///
+/// buf.write(b" world");
+/// assert_eq!(ui.read(&buf, &mut dst), Ok(6));
+/// assert_eq!(&dst[..6], b" world");
+///
+/// // The sink was lapped, "hel" is lost
+/// assert_eq!(sink.read(&buf, &mut dst).unwrap_err().missed(), 3);
+/// assert_eq!(sink.read(&buf, &mut dst), Ok(8));
+/// assert_eq!(&dst, b"lo world");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Reader {
+  pos: usize,
+}
+
+impl<B> Buffer<B> {
+  /// Returns a new [`Reader`], positioned at the oldest retained element.
+  pub fn reader<T>(&self) -> Reader
+  where
+    B: AsRef<[T]>,
+  {
+    let (_, front, back) = self.history(0);
+    Reader {
+      pos: self.written - front.len() - back.len(),
+    }
+  }
+
+  /// Returns a new [`Reader`] which only sees the data written from now on.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 8]);
+  /// buf.write(b"before");
+  ///
+  /// let mut sub = buf.subscribe();
+  /// buf.write(b"after");
+  ///
+  /// let mut dst = [0u8; 8];
+  /// assert_eq!(sub.read(&buf, &mut dst), Ok(5));
+  /// assert_eq!(&dst[..5], b"after");
+  /// ```
+  #[inline]
+  pub const fn subscribe(&self) -> Reader {
+    Reader { pos: self.written }
+  }
+}
+
+impl Reader {
+  /// Reads up to `dst.len()` of the elements following the reader's position into `dst`,
+  /// returns the number of elements read.
+  ///
+  /// Returns a [`Lagged`] error if some of those elements were overwritten, in which case
+  /// the reader is moved to the oldest retained element.
+  pub fn read<B, T>(&mut self, buf: &Buffer<B>, dst: &mut [T]) -> Result<usize, Lagged>
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    let (missed, front, back) = buf.history(self.pos);
+    let start = buf.written - front.len() - back.len();
+    if missed != 0 {
+      self.pos = start;
+      return Err(Lagged(missed));
+    }
+
+    let n = copy_segments(dst, front, back);
+    self.pos = start + n;
+    Ok(n)
+  }
+
+  /// Returns the absolute stream offset of the next element to read,
+  /// see [`Buffer::written`].
+  #[inline]
+  pub const fn position(&self) -> usize {
+    self.pos
+  }
+}
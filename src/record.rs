@@ -0,0 +1,159 @@
+use crate::{Buffer, ReadError, TooLarge, View};
+
+/// The size of the length header stored in front of every record.
+const HEADER: usize = core::mem::size_of::<u32>();
+
+/// A circular buffer preserving write boundaries.
+///
+/// Every [`write`](RecordBuffer::write) is a record. On overflow, the oldest records are
+/// discarded as a whole, and reads always yield complete records, in order.
+/// Each record occupies its length plus a 4 bytes header in the storage.
+///
+/// ```rust
+/// use circularbuf::RecordBuffer;
+///
+/// let mut buf = RecordBuffer::new([0u8; 24]);
+/// buf.write(b"first").unwrap();
+/// buf.write(b"second").unwrap();
+/// buf.write(b"third").unwrap();
+///
+/// // "first" was evicted as a whole to make room for "third"
+/// let records = buf.records().map(|r| r.to_vec()).collect::<Vec<_>>();
+/// assert_eq!(records, [b"second".to_vec(), b"third".to_vec()]);
+///
+/// let mut dst = [0u8; 16];
+/// assert_eq!(buf.read(&mut dst), Ok(Some(6)));
+/// assert_eq!(&dst[..6], b"second");
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct RecordBuffer<B> {
+  buf: Buffer<B>,
+  records: usize,
+}
+
+impl<B> RecordBuffer<B> {
+  /// Creates a new record buffer with the given storage.
+  #[inline]
+  pub const fn new(data: B) -> Self {
+    Self {
+      buf: Buffer::new(data),
+      records: 0,
+    }
+  }
+
+  /// Writes `record` as a single record, discarding the oldest records if necessary.
+  ///
+  /// Returns an error, leaving the buffer untouched, if the record cannot fit even in
+  /// an empty buffer.
+  pub fn write(&mut self, record: &[u8]) -> Result<usize, TooLarge>
+  where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+  {
+    let capacity = self.buf.size();
+    let required = record.len() + HEADER;
+    if required > capacity || u32::try_from(record.len()).is_err() {
+      return Err(TooLarge::new(required, capacity));
+    }
+
+    while capacity - self.buf.unread() < required {
+      self.discard_oldest();
+    }
+
+    self.buf.write(&(record.len() as u32).to_le_bytes());
+    self.buf.write(record);
+    self.records += 1;
+    Ok(record.len())
+  }
+
+  /// Reads the oldest record into `dst` and consumes it.
+  ///
+  /// Returns `Ok(None)` if there is no record, or an error, leaving the record in place,
+  /// if `dst` is too small to hold it.
+  pub fn read(&mut self, dst: &mut [u8]) -> Result<Option<usize>, ReadError>
+  where
+    B: AsRef<[u8]>,
+  {
+    let Some(record) = self.oldest() else {
+      return Ok(None);
+    };
+
+    let len = record.len();
+    if dst.len() < len {
+      return Err(ReadError::new(len, dst.len()));
+    }
+
+    record.copy_to_slice(dst);
+    self.discard_oldest();
+    Ok(Some(len))
+  }
+
+  /// Returns the oldest record.
+  #[inline]
+  pub fn oldest(&self) -> Option<View<'_, u8>>
+  where
+    B: AsRef<[u8]>,
+  {
+    self.records().next()
+  }
+
+  /// Discards the oldest record, returns `false` if there is no record.
+  pub fn discard_oldest(&mut self) -> bool
+  where
+    B: AsRef<[u8]>,
+  {
+    match self.oldest() {
+      Some(record) => {
+        let len = record.len();
+        self.buf.discard_oldest(HEADER + len);
+        self.records -= 1;
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Returns an iterator over the retained records, from the oldest to the newest.
+  pub fn records(&self) -> impl Iterator<Item = View<'_, u8>>
+  where
+    B: AsRef<[u8]>,
+  {
+    let (front, back) = self.buf.as_slices();
+    let mut rest = View::new(front, back);
+    (0..self.records).map(move |_| {
+      let mut header = [0; HEADER];
+      rest.copy_to_slice(&mut header);
+      let (_, tail) = rest.split_at(HEADER);
+      let (record, tail) = tail.split_at(u32::from_le_bytes(header) as usize);
+      rest = tail;
+      record
+    })
+  }
+
+  /// Returns the length of the largest record the buffer can hold.
+  #[inline]
+  pub fn max_record_len(&self) -> usize
+  where
+    B: AsRef<[u8]>,
+  {
+    self.buf.size().saturating_sub(HEADER)
+  }
+
+  /// Returns the number of retained records.
+  #[inline]
+  pub const fn len(&self) -> usize {
+    self.records
+  }
+
+  /// Returns `true` if no record is retained.
+  #[inline]
+  pub const fn is_empty(&self) -> bool {
+    self.records == 0
+  }
+
+  /// Resets the buffer so it has no content.
+  #[inline]
+  pub const fn reset(&mut self) {
+    self.buf.reset();
+    self.records = 0;
+  }
+}
@@ -0,0 +1,440 @@
+#[cfg(any(feature = "std", feature = "alloc"))]
+use core::fmt;
+
+use serde::{
+  Deserialize, Deserializer, Serialize, Serializer,
+  de::{Error, SeqAccess, Visitor},
+};
+
+use crate::{Buffer, Counters, valid_state};
+
+/// The standard base64 alphabet.
+#[cfg(any(feature = "std", feature = "alloc"))]
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The storage of a byte [`Buffer`] which can be deserialized.
+///
+/// The storage is (de)serialized as bytes, rather than through its own serde
+/// implementation, so inline arrays of any size work without allocating.
+///
+/// This trait is sealed. It is implemented for `[u8; N]`, for `&[u8]` borrowed from
+/// the input of compact formats, and for `Vec<u8>` and `Box<[u8]>` when the `std` or
+/// `alloc` feature is enabled.
+pub trait DeserializeStorage<'de>: sealed::Sealed<'de> + AsRef<[u8]> {}
+
+mod sealed {
+  use serde::Deserializer;
+
+  pub trait Sealed<'de>: Sized {
+    /// Deserializes the storage from the bytes written by compact formats.
+    fn deserialize_bytes<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+
+    /// Builds a storage of `capacity` bytes starting with `contents`.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn with_contents<E: serde::de::Error>(
+      contents: std::vec::Vec<u8>,
+      capacity: usize,
+    ) -> Result<Self, E>;
+  }
+}
+
+/// Deserializes `[u8; N]` from bytes, or from a sequence for formats without bytes.
+struct ArrayVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for ArrayVisitor<N> {
+  type Value = [u8; N];
+
+  fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    write!(f, "{N} bytes")
+  }
+
+  fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<[u8; N], E> {
+    v.try_into().map_err(|_| E::invalid_length(v.len(), &self))
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<[u8; N], A::Error> {
+    let mut data = [0u8; N];
+    for (i, b) in data.iter_mut().enumerate() {
+      *b = seq
+        .next_element()?
+        .ok_or_else(|| A::Error::invalid_length(i, &self))?;
+    }
+    match seq.next_element::<u8>()? {
+      Some(_) => Err(A::Error::invalid_length(N + 1, &self)),
+      None => Ok(data),
+    }
+  }
+}
+
+impl<'de, const N: usize> sealed::Sealed<'de> for [u8; N] {
+  #[inline]
+  fn deserialize_bytes<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    deserializer.deserialize_bytes(ArrayVisitor)
+  }
+
+  #[cfg(any(feature = "std", feature = "alloc"))]
+  fn with_contents<E: Error>(contents: std::vec::Vec<u8>, capacity: usize) -> Result<Self, E> {
+    // Also guarantees that the contents fit
+    if capacity != N {
+      return Err(E::invalid_value(
+        serde::de::Unexpected::Unsigned(capacity as u64),
+        &ArrayVisitor::<N>,
+      ));
+    }
+
+    let mut data = [0u8; N];
+    let n = contents.len().min(N);
+    data[..n].copy_from_slice(&contents[..n]);
+    Ok(data)
+  }
+}
+
+impl<const N: usize> DeserializeStorage<'_> for [u8; N] {}
+
+/// Deserializes `&[u8]` borrowed from the input.
+struct BorrowedVisitor;
+
+impl<'de> Visitor<'de> for BorrowedVisitor {
+  type Value = &'de [u8];
+
+  fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.write_str("borrowed bytes")
+  }
+
+  #[inline]
+  fn visit_borrowed_bytes<E: Error>(self, v: &'de [u8]) -> Result<&'de [u8], E> {
+    Ok(v)
+  }
+}
+
+impl<'de> sealed::Sealed<'de> for &'de [u8] {
+  #[inline]
+  fn deserialize_bytes<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    deserializer.deserialize_bytes(BorrowedVisitor)
+  }
+
+  #[cfg(any(feature = "std", feature = "alloc"))]
+  fn with_contents<E: Error>(_: std::vec::Vec<u8>, _: usize) -> Result<Self, E> {
+    Err(E::custom(
+      "decoded contents cannot be borrowed from the input",
+    ))
+  }
+}
+
+impl<'de> DeserializeStorage<'de> for &'de [u8] {}
+
+/// Deserializes `Vec<u8>` from bytes, or from a sequence for formats without bytes.
+#[cfg(any(feature = "std", feature = "alloc"))]
+struct VecVisitor;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'de> Visitor<'de> for VecVisitor {
+  type Value = std::vec::Vec<u8>;
+
+  fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.write_str("bytes")
+  }
+
+  #[inline]
+  fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+    Ok(v.to_vec())
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+    // The length hint comes from the input, it is not trusted for preallocation
+    let mut data = std::vec::Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+    while let Some(b) = seq.next_element()? {
+      data.push(b);
+    }
+    Ok(data)
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'de> sealed::Sealed<'de> for std::vec::Vec<u8> {
+  #[inline]
+  fn deserialize_bytes<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    deserializer.deserialize_bytes(VecVisitor)
+  }
+
+  fn with_contents<E: Error>(mut contents: std::vec::Vec<u8>, capacity: usize) -> Result<Self, E> {
+    // The capacity comes from the input, failing to allocate it must not abort
+    let extra = capacity.saturating_sub(contents.len());
+    if contents.try_reserve_exact(extra).is_err() {
+      return Err(E::invalid_value(
+        serde::de::Unexpected::Unsigned(capacity as u64),
+        &"a capacity which can be allocated",
+      ));
+    }
+    contents.resize(capacity, 0);
+    Ok(contents)
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl DeserializeStorage<'_> for std::vec::Vec<u8> {}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'de> sealed::Sealed<'de> for std::boxed::Box<[u8]> {
+  #[inline]
+  fn deserialize_bytes<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    std::vec::Vec::deserialize_bytes(deserializer).map(std::vec::Vec::into_boxed_slice)
+  }
+
+  #[inline]
+  fn with_contents<E: Error>(contents: std::vec::Vec<u8>, capacity: usize) -> Result<Self, E> {
+    std::vec::Vec::with_contents(contents, capacity).map(std::vec::Vec::into_boxed_slice)
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl DeserializeStorage<'_> for std::boxed::Box<[u8]> {}
+
+/// Serializes the storage as bytes.
+#[inline]
+fn serialize_bytes<S: Serializer>(data: &&[u8], serializer: S) -> Result<S::Ok, S::Error> {
+  serializer.serialize_bytes(data)
+}
+
+/// The full state of a buffer, as serialized by compact formats.
+#[derive(Serialize)]
+#[serde(rename = "Buffer")]
+struct CompactRef<'a> {
+  #[serde(serialize_with = "serialize_bytes")]
+  data: &'a [u8],
+  write_cursor: usize,
+  written: usize,
+  len: usize,
+  retained: usize,
+  stats: &'a Counters,
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Buffer", bound = "B: sealed::Sealed<'de>")]
+struct Compact<B> {
+  #[serde(deserialize_with = "sealed::Sealed::deserialize_bytes")]
+  data: B,
+  write_cursor: usize,
+  written: usize,
+  len: usize,
+  retained: usize,
+  #[serde(default)]
+  stats: Counters,
+}
+
+/// The state of a buffer, as serialized by human-readable formats.
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Serialize)]
+#[serde(rename = "Buffer")]
+struct ReadableRef<'a> {
+  capacity: usize,
+  written: usize,
+  contents: Base64<'a>,
+  stats: &'a Counters,
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[derive(Deserialize)]
+#[serde(rename = "Buffer")]
+struct Readable {
+  capacity: usize,
+  written: usize,
+  contents: Decoded,
+  #[serde(default)]
+  stats: Counters,
+}
+
+/// Serializes the unread bytes as a base64 string, without allocating.
+#[cfg(any(feature = "std", feature = "alloc"))]
+struct Base64<'a>(&'a [u8], &'a [u8]);
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl fmt::Display for Base64<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut bytes = self.0.iter().chain(self.1).copied();
+    loop {
+      let mut chunk = [0u8; 3];
+      let n = chunk
+        .iter_mut()
+        .zip(&mut bytes)
+        .map(|(dst, b)| *dst = b)
+        .count();
+      if n == 0 {
+        return Ok(());
+      }
+
+      let bits = u32::from_be_bytes([0, chunk[0], chunk[1], chunk[2]]);
+      let mut out = [b'='; 4];
+      for (i, c) in out[..n + 1].iter_mut().enumerate() {
+        *c = ALPHABET[(bits >> (18 - 6 * i)) as usize & 0x3f];
+      }
+      // The alphabet and the padding are ASCII
+      f.write_str(core::str::from_utf8(&out).map_err(|_| fmt::Error)?)?;
+    }
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl Serialize for Base64<'_> {
+  #[inline]
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+
+/// The bytes decoded from a base64 string.
+#[cfg(any(feature = "std", feature = "alloc"))]
+struct Decoded(std::vec::Vec<u8>);
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'de> Deserialize<'de> for Decoded {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    struct Visitor;
+
+    impl serde::de::Visitor<'_> for Visitor {
+      type Value = Decoded;
+
+      fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a base64 string")
+      }
+
+      fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Decoded, E> {
+        decode(v)
+          .map(Decoded)
+          .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+      }
+    }
+
+    deserializer.deserialize_str(Visitor)
+  }
+}
+
+/// Decodes a standard base64 string, with or without padding.
+#[cfg(any(feature = "std", feature = "alloc"))]
+fn decode(s: &str) -> Option<std::vec::Vec<u8>> {
+  let s = s.as_bytes();
+  let s = s
+    .strip_suffix(b"==")
+    .or_else(|| s.strip_suffix(b"="))
+    .unwrap_or(s);
+  if s.len() % 4 == 1 {
+    return None;
+  }
+
+  let mut out = std::vec::Vec::with_capacity(s.len() / 4 * 3 + 2);
+  let mut bits = 0u32;
+  let mut count = 0;
+  for &c in s {
+    let value = ALPHABET.iter().position(|&a| a == c)?;
+    bits = bits << 6 | value as u32;
+    count += 6;
+    if count >= 8 {
+      count -= 8;
+      out.push((bits >> count) as u8);
+    }
+  }
+  Some(out)
+}
+
+/// Serializes the full state of the buffer.
+///
+/// Human-readable formats get the capacity, the stream offset and the unread bytes in
+/// logical order as a base64 string, when the `std` or `alloc` feature is enabled.
+/// Other formats get the raw storage and the cursors.
+impl<B> Serialize for Buffer<B>
+where
+  B: AsRef<[u8]>,
+{
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    if serializer.is_human_readable() {
+      let (front, back) = self.as_slices();
+      return ReadableRef {
+        capacity: self.data.as_ref().len(),
+        written: self.written,
+        contents: Base64(front, back),
+        stats: &self.stats,
+      }
+      .serialize(serializer);
+    }
+
+    CompactRef {
+      data: self.data.as_ref(),
+      write_cursor: self.write_cursor,
+      written: self.written,
+      len: self.len,
+      retained: self.retained,
+      stats: &self.stats,
+    }
+    .serialize(serializer)
+  }
+}
+
+impl<B: AsRef<[u8]>> Buffer<B> {
+  /// Builds a deserialized buffer, rejecting cursors inconsistent with the storage,
+  /// which would make the next read or write panic.
+  fn from_parts<E: Error>(
+    data: B,
+    write_cursor: usize,
+    written: usize,
+    len: usize,
+    retained: usize,
+    stats: Counters,
+  ) -> Result<Self, E> {
+    let size = data.as_ref().len();
+    if !valid_state(size, write_cursor, written, len, retained) {
+      return Err(E::custom(format_args!(
+        "cursor {write_cursor} with {len} unread and {retained} retained bytes is not a valid state for a buffer of capacity {size} after {written} bytes written"
+      )));
+    }
+
+    Ok(Self {
+      data,
+      write_cursor,
+      written,
+      len,
+      retained,
+      stats,
+    })
+  }
+}
+
+impl<'de, B> Deserialize<'de> for Buffer<B>
+where
+  B: DeserializeStorage<'de>,
+{
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    if deserializer.is_human_readable() {
+      let Readable {
+        capacity,
+        written,
+        contents: Decoded(data),
+        stats,
+      } = Readable::deserialize(deserializer)?;
+      let len = data.len();
+      if len > capacity {
+        return Err(D::Error::invalid_value(
+          serde::de::Unexpected::Unsigned(capacity as u64),
+          &"a capacity holding the contents",
+        ));
+      }
+
+      // The unread bytes are restored at the start of the storage, and are the only
+      // history. The capacity is checked against fixed storages before allocating.
+      let data = B::with_contents(data, capacity)?;
+      let write_cursor = if len >= capacity { 0 } else { len };
+      return Self::from_parts(data, write_cursor, written, len, len, stats);
+    }
+
+    let Compact {
+      data,
+      write_cursor,
+      written,
+      len,
+      retained,
+      stats,
+    } = Compact::deserialize(deserializer)?;
+    Self::from_parts(data, write_cursor, written, len, retained, stats)
+  }
+}
@@ -0,0 +1,93 @@
+//! The lock-free ring shared by the concurrent buffer flavors.
+//!
+//! The storage is made of atomics so it can be shared without `unsafe`,
+//! positions are absolute (wrapping) stream offsets.
+
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering, fence};
+use std::boxed::Box;
+
+#[derive(Debug)]
+pub(crate) struct Shared {
+  pub(crate) data: Box<[AtomicU8]>,
+  /// Total number of bytes published by the producers.
+  pub(crate) head: AtomicUsize,
+  /// Total number of bytes the producers have started to write, always `>= head`.
+  pub(crate) reserved: AtomicUsize,
+  /// Total number of bytes consumed by the consumer.
+  pub(crate) tail: AtomicUsize,
+  pub(crate) overwrite: bool,
+}
+
+impl Shared {
+  /// Creates a zeroed ring of `capacity` bytes, whose first `len` bytes are published.
+  pub(crate) fn new(capacity: usize, len: usize, overwrite: bool) -> Self {
+    Self {
+      data: (0..capacity).map(|_| AtomicU8::new(0)).collect(),
+      head: AtomicUsize::new(len),
+      reserved: AtomicUsize::new(len),
+      tail: AtomicUsize::new(0),
+      overwrite,
+    }
+  }
+
+  #[inline]
+  pub(crate) fn capacity(&self) -> usize {
+    self.data.len()
+  }
+
+  /// Stores `src` starting at the absolute position `pos`.
+  pub(crate) fn store(&self, pos: usize, src: &[u8]) {
+    if src.is_empty() {
+      return;
+    }
+
+    let cap = self.capacity();
+    let start = crate::wrap(pos, cap);
+    let (first, second) = src.split_at(src.len().min(cap - start));
+    for (slot, b) in self.data[start..].iter().zip(first) {
+      slot.store(*b, Ordering::Relaxed);
+    }
+    for (slot, b) in self.data.iter().zip(second) {
+      slot.store(*b, Ordering::Relaxed);
+    }
+  }
+
+  /// Copies the newest retained bytes that fit into `dst`, returns the number of bytes copied.
+  ///
+  /// `reserved` acts as the sequence counter of a seqlock: if a producer reserved
+  /// any position overlapping the copied range while copying, the copy is retried.
+  pub(crate) fn snapshot(&self, dst: &mut [u8]) -> usize {
+    let cap = self.capacity();
+    loop {
+      let head = self.head.load(Ordering::Acquire);
+      let tail = self.tail.load(Ordering::Acquire);
+      let n = head.wrapping_sub(tail).min(cap).min(dst.len());
+      let start = head.wrapping_sub(n);
+      self.load(start, &mut dst[..n]);
+
+      fence(Ordering::Acquire);
+      let reserved = self.reserved.load(Ordering::Relaxed);
+      if reserved.wrapping_sub(start) <= cap {
+        return n;
+      }
+      core::hint::spin_loop();
+    }
+  }
+
+  /// Loads `dst.len()` bytes starting at the absolute position `pos`.
+  pub(crate) fn load(&self, pos: usize, dst: &mut [u8]) {
+    if dst.is_empty() {
+      return;
+    }
+
+    let cap = self.capacity();
+    let start = crate::wrap(pos, cap);
+    let (first, second) = dst.split_at_mut(dst.len().min(cap - start));
+    for (b, slot) in first.iter_mut().zip(&self.data[start..]) {
+      *b = slot.load(Ordering::Relaxed);
+    }
+    for (b, slot) in second.iter_mut().zip(self.data.iter()) {
+      *b = slot.load(Ordering::Relaxed);
+    }
+  }
+}
@@ -0,0 +1,337 @@
+use core::{
+  ptr::NonNull,
+  sync::atomic::{AtomicU64, Ordering, fence},
+};
+use std::{boxed::Box, fs::OpenOptions, io, path::Path, vec};
+
+use memmap2::MmapMut;
+
+use crate::{Buffer, Counters};
+
+/// Marks an initialized segment, stored last on creation.
+const MAGIC: u64 = u64::from_le_bytes(*b"CBUFSHM1");
+
+/// The size of the header in front of the data, a cache line.
+const HEADER: usize = 64;
+
+/// The header of a shared memory segment.
+///
+/// `reserved` is advanced before the data is overwritten, and `written` once the write
+/// is complete, so readers can tell which bytes a concurrent or interrupted write may
+/// have torn. `start` is the stream offset the current writer started at, the bytes
+/// before it belong to a previous writer of the segment. All of them only grow.
+#[repr(C)]
+struct Header {
+  magic: AtomicU64,
+  capacity: AtomicU64,
+  reserved: AtomicU64,
+  written: AtomicU64,
+  start: AtomicU64,
+}
+
+/// A memory-mapped segment: the header followed by the data.
+struct Segment {
+  _map: MmapMut,
+  ptr: NonNull<u8>,
+  capacity: usize,
+}
+
+// SAFETY: the segment owns its mapping, the pointer is only derived from it.
+#[allow(unsafe_code)]
+unsafe impl Send for Segment {}
+
+impl Segment {
+  fn new(mut map: MmapMut) -> Self {
+    let capacity = map.len() - HEADER;
+    // Both the header and the data are accessed through this pointer only
+    let ptr = NonNull::new(map.as_mut_ptr()).expect("mappings are never null");
+    Self {
+      _map: map,
+      ptr,
+      capacity,
+    }
+  }
+
+  #[inline]
+  fn header(&self) -> &Header {
+    // SAFETY: the mapping is page aligned and larger than the header, which is only
+    // made of atomics, valid for any bit pattern.
+    #[allow(unsafe_code)]
+    unsafe {
+      self.ptr.cast::<Header>().as_ref()
+    }
+  }
+
+  #[inline]
+  fn data(&self) -> *mut u8 {
+    // SAFETY: the mapping is `HEADER + capacity` bytes long.
+    #[allow(unsafe_code)]
+    unsafe {
+      self.ptr.as_ptr().add(HEADER)
+    }
+  }
+}
+
+// There is deliberately no `AsRef` implementation: readers must never hold a reference
+// to bytes the writer may modify concurrently.
+impl AsMut<[u8]> for Segment {
+  #[inline]
+  fn as_mut(&mut self) -> &mut [u8] {
+    // SAFETY: the data is only written by the single writer of the segment, through this
+    // slice. Readers copy it out with volatile reads, without creating references.
+    #[allow(unsafe_code)]
+    unsafe {
+      core::slice::from_raw_parts_mut(self.data(), self.capacity)
+    }
+  }
+}
+
+impl core::fmt::Debug for Segment {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("Segment")
+      .field("capacity", &self.capacity)
+      .finish_non_exhaustive()
+  }
+}
+
+impl Header {
+  /// Returns whether the segment was initialized by a writer with room for `capacity` bytes.
+  #[inline]
+  fn is_valid(&self, capacity: usize) -> bool {
+    self.magic.load(Ordering::Acquire) == MAGIC
+      && self.capacity.load(Ordering::Relaxed) == capacity as u64
+  }
+}
+
+/// Maps the segment in the file at `path`, checking it was initialized by a writer.
+///
+/// # Safety
+///
+/// The file must not be resized or truncated while mapped.
+#[allow(unsafe_code)]
+unsafe fn map(path: &Path) -> io::Result<Segment> {
+  let file = OpenOptions::new().read(true).write(true).open(path)?;
+  let len = usize::try_from(file.metadata()?.len()).map_err(|_| invalid_data())?;
+  if len < HEADER {
+    return Err(invalid_data());
+  }
+
+  // SAFETY: guaranteed by the caller.
+  let map = unsafe { MmapMut::map_mut(&file)? };
+  let segment = Segment::new(map);
+  if !segment.header().is_valid(segment.capacity) {
+    return Err(invalid_data());
+  }
+  Ok(segment)
+}
+
+#[inline]
+fn invalid_data() -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, "not a shared memory buffer")
+}
+
+/// A circular buffer living in a shared memory segment, so another process can read
+/// the most recent bytes at any time, even after the writing process died.
+///
+/// The segment is a file mapped in memory, which should be on a memory-backed file
+/// system such as `/dev/shm` on Linux. The writer keeps the stream offset in an atomic
+/// header, and [`ShmemReader`] uses it to discard the bytes torn by a write which is in
+/// progress, or was interrupted by a crash.
+///
+/// ```rust,no_run
+/// use circularbuf::{ShmemBuffer, ShmemReader};
+///
+/// // In the child process, the only writer of its segment
+/// let mut log = unsafe { ShmemBuffer::create("/dev/shm/worker-42.log", 64 * 1024)? };
+/// log.write(b"processing job 7\n");
+///
+/// // In the supervisor, once the child died
+/// let tail = unsafe { ShmemReader::open("/dev/shm/worker-42.log")? }.snapshot();
+/// println!("{tail}");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct ShmemBuffer {
+  buf: Buffer<Segment>,
+}
+
+impl ShmemBuffer {
+  /// Creates the segment at `path` with room for `capacity` bytes, replacing any
+  /// previous content.
+  ///
+  /// An existing segment of the same capacity, e.g. left by a previous writer which died,
+  /// is reinitialized in place, so the readers which still map it see an empty buffer
+  /// rather than a truncated file. The stream offset carries on from the previous writer.
+  /// Fails with [`io::ErrorKind::InvalidData`] if the file exists with another size,
+  /// it must be removed first.
+  ///
+  /// # Safety
+  ///
+  /// The returned buffer must be the only writer of the segment: no other `ShmemBuffer`,
+  /// in this process or another one, may map it at the same time. The file must not be
+  /// resized, truncated or modified by anything but the `ShmemBuffer`s and
+  /// [`ShmemReader`]s of this crate while mapped.
+  #[allow(unsafe_code)]
+  pub unsafe fn create(path: impl AsRef<Path>, capacity: usize) -> io::Result<Self> {
+    let file = OpenOptions::new()
+      .read(true)
+      .write(true)
+      .create(true)
+      .truncate(false)
+      .open(path)?;
+    let len = (HEADER + capacity) as u64;
+    match file.metadata()?.len() {
+      0 => file.set_len(len)?,
+      n if n == len => {}
+      _ => return Err(invalid_data()),
+    }
+
+    // SAFETY: the file has the right size and is written by this buffer only, as
+    // guaranteed by the caller.
+    let map = unsafe { MmapMut::map_mut(&file)? };
+    let segment = Segment::new(map);
+    let header = segment.header();
+    let start = if header.is_valid(capacity) {
+      // Readers of the previous writer keep their mapping. The bytes a write in progress
+      // may have torn are skipped, and those before `start` are no longer readable.
+      let start = header
+        .reserved
+        .load(Ordering::Relaxed)
+        .max(header.written.load(Ordering::Relaxed));
+      header.reserved.store(start, Ordering::Relaxed);
+      header.start.store(start, Ordering::Relaxed);
+      header.written.store(start, Ordering::Release);
+      start
+    } else {
+      header.capacity.store(capacity as u64, Ordering::Relaxed);
+      header.reserved.store(0, Ordering::Relaxed);
+      header.written.store(0, Ordering::Relaxed);
+      header.start.store(0, Ordering::Relaxed);
+      header.magic.store(MAGIC, Ordering::Release);
+      0
+    };
+
+    let written = start as usize;
+    Ok(Self {
+      buf: Buffer {
+        data: segment,
+        write_cursor: if capacity == 0 { 0 } else { written % capacity },
+        written,
+        len: 0,
+        retained: 0,
+        stats: Counters::new(),
+      },
+    })
+  }
+
+  /// Writes `buf` into the ring, overwriting the oldest data if necessary,
+  /// returns the number of bytes written.
+  pub fn write(&mut self, buf: &[u8]) -> usize {
+    let end = (self.buf.written + buf.len()) as u64;
+    let header = self.buf.data.header();
+    header.reserved.store(end, Ordering::Relaxed);
+    // Readers seeing any of the new bytes also see the reservation
+    fence(Ordering::Release);
+
+    let n = self.buf.write(buf);
+    self.buf.data.header().written.store(end, Ordering::Release);
+    n
+  }
+
+  /// Returns the total number of bytes written, including those of the previous writers
+  /// of the segment.
+  #[inline]
+  pub const fn written(&self) -> usize {
+    self.buf.written
+  }
+
+  /// Returns the capacity of the segment.
+  #[inline]
+  pub const fn capacity(&self) -> usize {
+    self.buf.data.capacity
+  }
+}
+
+impl io::Write for ShmemBuffer {
+  #[inline]
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    Ok(ShmemBuffer::write(self, buf))
+  }
+
+  #[inline]
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+/// A reader of a [`ShmemBuffer`] segment, usually in a supervisor process.
+///
+/// It does not consume anything, every [`snapshot`](ShmemReader::snapshot) copies the
+/// most recent bytes out of the segment. It keeps working when a restarted writer
+/// recreates the segment, as long as the capacity is the same.
+#[derive(Debug)]
+pub struct ShmemReader {
+  segment: Segment,
+}
+
+impl ShmemReader {
+  /// Opens the segment at `path`.
+  ///
+  /// Fails with [`io::ErrorKind::InvalidData`] if the segment was not initialized by
+  /// [`ShmemBuffer::create`].
+  ///
+  /// # Safety
+  ///
+  /// The file must not be resized, truncated or modified by anything but the
+  /// `ShmemBuffer`s and `ShmemReader`s of this crate while the reader is alive.
+  #[inline]
+  #[allow(unsafe_code)]
+  pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+    // SAFETY: guaranteed by the caller.
+    unsafe { map(path.as_ref()) }.map(|segment| Self { segment })
+  }
+
+  /// Returns the total number of bytes written by the writers so far.
+  #[inline]
+  pub fn written(&self) -> usize {
+    self.segment.header().written.load(Ordering::Acquire) as usize
+  }
+
+  /// Copies the retained bytes out of the segment into a new buffer.
+  ///
+  /// The bytes which a write in progress, or interrupted by a crash, may have torn are
+  /// left out, so the snapshot can hold less than the capacity while the writer is busy.
+  pub fn snapshot(&self) -> Buffer<Box<[u8]>> {
+    let header = self.segment.header();
+    let capacity = self.segment.capacity;
+    let written = header.written.load(Ordering::Acquire) as usize;
+
+    let mut data = vec![0u8; capacity].into_boxed_slice();
+    let src = self.segment.data();
+    for (i, dst) in data.iter_mut().enumerate() {
+      // SAFETY: the data region is `capacity` bytes long. A concurrent writer may modify
+      // it during the copy, so it is read byte by byte without ever creating a reference,
+      // and the bytes the writer touched are discarded below.
+      #[allow(unsafe_code)]
+      unsafe {
+        *dst = src.add(i).read_volatile();
+      }
+    }
+    fence(Ordering::Acquire);
+    let reserved = header.reserved.load(Ordering::Relaxed) as usize;
+    let start = header.start.load(Ordering::Relaxed) as usize;
+
+    // Only the bytes of the current writer not overwritten by any write started so far
+    // are intact
+    let oldest = reserved.max(written).saturating_sub(capacity).max(start);
+    let len = written.saturating_sub(oldest);
+    Buffer {
+      data,
+      write_cursor: if capacity == 0 { 0 } else { written % capacity },
+      written,
+      len,
+      retained: len,
+      stats: Counters::new(),
+    }
+  }
+}
@@ -0,0 +1,182 @@
+use core::marker::PhantomData;
+
+use crate::{segments, wrap};
+
+/// A fixed-capacity circular buffer of whole items, which drops the oldest item
+/// when a new one is pushed while it is full.
+///
+/// Unlike [`Buffer`](crate::Buffer), items do not need to be `Copy`, and are never split.
+/// The storage is a slice of `Option<T>` slots, e.g. `[Option<T>; N]` or
+/// `Vec<Option<T>>`, whose initial content is ignored.
+///
+/// ```rust
+/// use circularbuf::SlotBuffer;
+///
+/// let mut events = SlotBuffer::<String, [_; 3]>::new();
+/// for event in ["boot", "connect", "login", "crash"] {
+///   events.push(event.to_string());
+/// }
+///
+/// assert_eq!(events.oldest().map(String::as_str), Some("connect"));
+/// assert_eq!(events.newest().map(String::as_str), Some("crash"));
+/// assert_eq!(events.iter().collect::<Vec<_>>(), ["connect", "login", "crash"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SlotBuffer<T, B> {
+  slots: B,
+  /// The index of the slot the next item is pushed into.
+  cursor: usize,
+  len: usize,
+  _item: PhantomData<T>,
+}
+
+impl<T, const N: usize> SlotBuffer<T, [Option<T>; N]> {
+  /// Creates a new buffer of `N` items backed by an array.
+  #[inline]
+  pub const fn new() -> Self {
+    Self::from_slots([const { None }; N])
+  }
+}
+
+impl<T, const N: usize> Default for SlotBuffer<T, [Option<T>; N]> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+impl<T> SlotBuffer<T, std::vec::Vec<Option<T>>> {
+  /// Creates a new heap allocated buffer of `capacity` items.
+  pub fn with_capacity(capacity: usize) -> Self {
+    let mut slots = std::vec::Vec::with_capacity(capacity);
+    slots.resize_with(capacity, || None);
+    Self::from_slots(slots)
+  }
+}
+
+impl<T, B> SlotBuffer<T, B> {
+  /// Creates a new empty buffer with the given slots.
+  #[inline]
+  pub const fn from_slots(slots: B) -> Self {
+    Self {
+      slots,
+      cursor: 0,
+      len: 0,
+      _item: PhantomData,
+    }
+  }
+
+  /// Returns the number of items in the buffer.
+  #[inline]
+  pub const fn len(&self) -> usize {
+    self.len
+  }
+
+  /// Returns `true` if the buffer holds no items.
+  #[inline]
+  pub const fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+}
+
+impl<T, B> SlotBuffer<T, B>
+where
+  B: AsRef<[Option<T>]>,
+{
+  /// Returns the number of items the buffer can hold.
+  #[inline]
+  pub fn capacity(&self) -> usize {
+    self.slots.as_ref().len()
+  }
+
+  /// Returns `true` if the buffer is full, so the next push drops the oldest item.
+  #[inline]
+  pub fn is_full(&self) -> bool {
+    self.len == self.capacity()
+  }
+
+  /// Returns the item at the logical position `i`, from the oldest item.
+  #[inline]
+  pub fn get(&self, i: usize) -> Option<&T> {
+    if i >= self.len {
+      return None;
+    }
+
+    let size = self.capacity();
+    self.slots.as_ref()[wrap(self.cursor + size - self.len + i, size)].as_ref()
+  }
+
+  /// Returns the oldest item.
+  #[inline]
+  pub fn oldest(&self) -> Option<&T> {
+    self.get(0)
+  }
+
+  /// Returns the newest item.
+  #[inline]
+  pub fn newest(&self) -> Option<&T> {
+    self.get(self.len.checked_sub(1)?)
+  }
+
+  /// Returns an iterator over the items, from the oldest to the newest.
+  pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + '_ {
+    let (front, back) = segments(self.slots.as_ref(), self.cursor, self.len);
+    front.iter().chain(back).filter_map(Option::as_ref)
+  }
+}
+
+impl<T, B> SlotBuffer<T, B>
+where
+  B: AsRef<[Option<T>]> + AsMut<[Option<T>]>,
+{
+  /// Pushes `item` as the newest item, returns the oldest item if it was dropped
+  /// to make room for it.
+  ///
+  /// A buffer with no slots drops every item, so `item` itself is returned.
+  pub fn push(&mut self, item: T) -> Option<T> {
+    let size = self.capacity();
+    if size == 0 {
+      return Some(item);
+    }
+
+    let evicted = self.slots.as_mut()[self.cursor].replace(item);
+    self.cursor = wrap(self.cursor + 1, size);
+    if self.len < size {
+      self.len += 1;
+      // Stale items in the initial slots are not part of the buffer
+      return None;
+    }
+    evicted
+  }
+
+  /// Removes and returns the oldest item.
+  pub fn pop_oldest(&mut self) -> Option<T> {
+    if self.len == 0 {
+      return None;
+    }
+
+    let size = self.capacity();
+    let oldest = wrap(self.cursor + size - self.len, size);
+    self.len -= 1;
+    self.slots.as_mut()[oldest].take()
+  }
+
+  /// Removes and returns the newest item.
+  pub fn pop_newest(&mut self) -> Option<T> {
+    if self.len == 0 {
+      return None;
+    }
+
+    let size = self.capacity();
+    self.cursor = wrap(self.cursor + size - 1, size);
+    self.len -= 1;
+    self.slots.as_mut()[self.cursor].take()
+  }
+
+  /// Removes all the items.
+  pub fn clear(&mut self) {
+    while self.pop_oldest().is_some() {}
+  }
+}
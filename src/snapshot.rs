@@ -0,0 +1,195 @@
+use core::ops::Deref;
+use std::vec::Vec;
+
+use crate::Buffer;
+
+/// An owned, frozen copy of the unread bytes of a [`Buffer`], with some metadata about
+/// the buffer at the time it was taken.
+///
+/// Created by [`Buffer::snapshot`], it is independent of the buffer, so it can be
+/// handed to another thread while writes continue.
+///
+/// ```rust
+/// use circularbuf::Buffer;
+/// use std::io::Read;
+///
+/// let mut buf = Buffer::new([0u8; 4]);
+/// buf.write(b"hello world");
+///
+/// let mut snapshot = buf.snapshot();
+/// buf.write(b"!!!!");
+///
+/// assert_eq!(&*snapshot, b"orld");
+/// assert_eq!((snapshot.written(), snapshot.capacity(), snapshot.wraps()), (11, 4, 2));
+///
+/// let mut out = String::new();
+/// snapshot.read_to_string(&mut out).unwrap();
+/// assert_eq!(out, "orld");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+  data: Vec<u8>,
+  /// The position of the [`Read`](std::io::Read) cursor.
+  pos: usize,
+  written: usize,
+  capacity: usize,
+  wraps: usize,
+}
+
+impl Snapshot {
+  /// Returns the total number of bytes written to the buffer when the snapshot was taken.
+  #[inline]
+  pub const fn written(&self) -> usize {
+    self.written
+  }
+
+  /// Returns the capacity of the buffer.
+  #[inline]
+  pub const fn capacity(&self) -> usize {
+    self.capacity
+  }
+
+  /// Returns how many times the buffer had wrapped around when the snapshot was taken,
+  /// see [`Stats::wraps`](crate::Stats::wraps).
+  #[inline]
+  pub const fn wraps(&self) -> usize {
+    self.wraps
+  }
+
+  /// Returns the copied bytes.
+  #[inline]
+  pub fn into_vec(self) -> Vec<u8> {
+    self.data
+  }
+}
+
+impl Deref for Snapshot {
+  type Target = [u8];
+
+  #[inline]
+  fn deref(&self) -> &[u8] {
+    &self.data
+  }
+}
+
+impl AsRef<[u8]> for Snapshot {
+  #[inline]
+  fn as_ref(&self) -> &[u8] {
+    &self.data
+  }
+}
+
+/// Reads the copied bytes from the start, independently of [`Deref`], which always
+/// exposes all of them.
+#[cfg(feature = "std")]
+impl std::io::Read for Snapshot {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    let n = crate::copy(buf, &self.data[self.pos..]);
+    self.pos += n;
+    Ok(n)
+  }
+}
+
+impl<B> Buffer<B> {
+  /// Returns an owned [`Snapshot`] of the unread bytes, along with the total number of
+  /// bytes written, the capacity and the wrap count of the buffer.
+  pub fn snapshot(&self) -> Snapshot
+  where
+    B: AsRef<[u8]>,
+  {
+    Snapshot {
+      data: self.read_to_bytes().into_owned(),
+      pos: 0,
+      written: self.written,
+      capacity: self.size(),
+      wraps: self.wraps(),
+    }
+  }
+}
+
+/// A read-only buffer, normalized to contiguous order once, which can be cloned and
+/// read from many places cheaply.
+///
+/// Created by [`Buffer::freeze`], clones share the same allocation.
+///
+/// ```rust
+/// use circularbuf::Buffer;
+///
+/// let mut buf = Buffer::new([0u8; 4]);
+/// buf.write(b"hello world");
+///
+/// let frozen = buf.freeze();
+/// let handles = (0..4)
+///   .map(|_| {
+///     let frozen = frozen.clone();
+///     std::thread::spawn(move || frozen.iter().filter(|b| **b == b'o').count())
+///   })
+///   .collect::<Vec<_>>();
+///
+/// for handle in handles {
+///   assert_eq!(handle.join().unwrap(), 1);
+/// }
+/// assert_eq!(&*frozen, b"orld");
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrozenBuffer {
+  data: std::sync::Arc<[u8]>,
+  written: usize,
+  capacity: usize,
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl FrozenBuffer {
+  /// Returns the total number of bytes written to the buffer before it was frozen.
+  #[inline]
+  pub const fn written(&self) -> usize {
+    self.written
+  }
+
+  /// Returns the capacity of the buffer before it was frozen.
+  #[inline]
+  pub const fn capacity(&self) -> usize {
+    self.capacity
+  }
+
+  /// Returns the shared bytes.
+  #[inline]
+  pub fn as_arc(&self) -> &std::sync::Arc<[u8]> {
+    &self.data
+  }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl Deref for FrozenBuffer {
+  type Target = [u8];
+
+  #[inline]
+  fn deref(&self) -> &[u8] {
+    &self.data
+  }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl AsRef<[u8]> for FrozenBuffer {
+  #[inline]
+  fn as_ref(&self) -> &[u8] {
+    &self.data
+  }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<B> Buffer<B> {
+  /// Consumes the buffer and returns a [`FrozenBuffer`] holding its unread bytes in
+  /// contiguous order.
+  pub fn freeze(self) -> FrozenBuffer
+  where
+    B: AsRef<[u8]>,
+  {
+    FrozenBuffer {
+      data: self.read_to_bytes().as_ref().into(),
+      written: self.written,
+      capacity: self.size(),
+    }
+  }
+}
@@ -0,0 +1,228 @@
+use core::sync::atomic::{Ordering, fence};
+use std::sync::Arc;
+
+use crate::{Buffer, shared::Shared};
+
+/// The writing half of a buffer split by [`Buffer::split`] or [`Buffer::split_overwriting`].
+#[derive(Debug)]
+pub struct Producer {
+  shared: Arc<Shared>,
+  head: usize,
+}
+
+impl Producer {
+  /// Writes bytes from `buf` into the ring, returns the number of bytes written.
+  ///
+  /// If the ring was split by [`Buffer::split`], only as many bytes as there is free space
+  /// are written. If it was split by [`Buffer::split_overwriting`], all of `buf` is written,
+  /// overriding the oldest data if necessary.
+  pub fn write(&mut self, buf: &[u8]) -> usize {
+    let shared = &*self.shared;
+    let cap = shared.capacity();
+    if cap == 0 {
+      return if shared.overwrite { buf.len() } else { 0 };
+    }
+
+    let (n, src) = if shared.overwrite {
+      (buf.len(), &buf[buf.len().saturating_sub(cap)..])
+    } else {
+      let tail = shared.tail.load(Ordering::Acquire);
+      let free = cap - self.head.wrapping_sub(tail);
+      let n = buf.len().min(free);
+      (n, &buf[..n])
+    };
+
+    let head = self.head.wrapping_add(n);
+    // Announce the positions about to be overwritten before touching them,
+    // so a concurrent reader can detect torn reads.
+    shared.reserved.store(head, Ordering::Relaxed);
+    fence(Ordering::Release);
+    shared.store(head.wrapping_sub(src.len()), src);
+    shared.head.store(head, Ordering::Release);
+    self.head = head;
+    n
+  }
+
+  /// Returns the capacity of the ring.
+  #[inline]
+  pub fn capacity(&self) -> usize {
+    self.shared.capacity()
+  }
+
+  /// Returns an [`Observer`] which can take snapshots of the ring from any thread.
+  #[inline]
+  pub fn observer(&self) -> Observer {
+    Observer {
+      shared: self.shared.clone(),
+    }
+  }
+}
+
+/// The reading half of a buffer split by [`Buffer::split`] or [`Buffer::split_overwriting`].
+#[derive(Debug)]
+pub struct Consumer {
+  shared: Arc<Shared>,
+  tail: usize,
+}
+
+impl Consumer {
+  /// Reads up to `dst.len()` of the oldest unread bytes into `dst` and consumes them,
+  /// returns number of bytes readed.
+  ///
+  /// In overwrite mode, bytes overwritten by the producer before they could be read
+  /// are skipped.
+  pub fn read(&mut self, dst: &mut [u8]) -> usize {
+    let shared = &*self.shared;
+    let cap = shared.capacity();
+    loop {
+      let head = shared.head.load(Ordering::Acquire);
+      let mut tail = self.tail;
+      if head.wrapping_sub(tail) > cap {
+        tail = head.wrapping_sub(cap);
+      }
+
+      let n = head.wrapping_sub(tail).min(dst.len());
+      shared.load(tail, &mut dst[..n]);
+
+      if !shared.overwrite {
+        self.tail = tail.wrapping_add(n);
+        shared.tail.store(self.tail, Ordering::Release);
+        return n;
+      }
+
+      // Any byte overwritten while we were copying must have been reserved first.
+      fence(Ordering::Acquire);
+      let reserved = shared.reserved.load(Ordering::Relaxed);
+      let lost = reserved.wrapping_sub(tail).saturating_sub(cap);
+      if lost >= n && n != 0 {
+        continue;
+      }
+
+      let lost = lost.min(n);
+      dst.copy_within(lost..n, 0);
+      self.tail = tail.wrapping_add(n);
+      shared.tail.store(self.tail, Ordering::Release);
+      return n - lost;
+    }
+  }
+
+  /// Returns how many bytes are pending.
+  #[inline]
+  pub fn unread(&self) -> usize {
+    let head = self.shared.head.load(Ordering::Acquire);
+    head.wrapping_sub(self.tail).min(self.shared.capacity())
+  }
+
+  /// Returns the capacity of the ring.
+  #[inline]
+  pub fn capacity(&self) -> usize {
+    self.shared.capacity()
+  }
+
+  /// Returns an [`Observer`] which can take snapshots of the ring from any thread.
+  #[inline]
+  pub fn observer(&self) -> Observer {
+    Observer {
+      shared: self.shared.clone(),
+    }
+  }
+}
+
+/// A read-only handle to a split ring, which takes consistent snapshots
+/// of the retained bytes while the [`Producer`] is active.
+///
+/// Snapshots never consume data, and never contain bytes torn by a concurrent write:
+/// a sequence counter maintained by the producer is used to detect torn copies,
+/// which are retried.
+///
+/// ```rust
+/// use circularbuf::Buffer;
+///
+/// let (mut tx, _rx) = Buffer::new([0u8; 8]).split_overwriting();
+/// let observer = tx.observer();
+/// tx.write(b"hello world");
+///
+/// let mut dst = [0u8; 5];
+/// assert_eq!(observer.snapshot(&mut dst), 5);
+/// assert_eq!(&dst, b"world");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Observer {
+  shared: Arc<Shared>,
+}
+
+impl Observer {
+  /// Copies the newest retained bytes that fit into `dst`, returns the number of bytes copied.
+  #[inline]
+  pub fn snapshot(&self, dst: &mut [u8]) -> usize {
+    self.shared.snapshot(dst)
+  }
+
+  /// Returns a copy of all the retained bytes, from the oldest to the newest.
+  pub fn to_vec(&self) -> std::vec::Vec<u8> {
+    let mut out = std::vec![0; self.shared.capacity()];
+    let n = self.snapshot(&mut out);
+    out.truncate(n);
+    out
+  }
+
+  /// Returns the capacity of the ring.
+  #[inline]
+  pub fn capacity(&self) -> usize {
+    self.shared.capacity()
+  }
+}
+
+impl<B> Buffer<B> {
+  /// Splits the buffer into a [`Producer`] and a [`Consumer`], which can be used
+  /// from two threads concurrently without locks.
+  ///
+  /// The unread data is carried over. The producer never overwrites unread data,
+  /// writes only go through as far as there is free space.
+  ///
+  /// ```rust
+  /// use circularbuf::Buffer;
+  ///
+  /// let (mut tx, mut rx) = Buffer::new([0u8; 4]).split();
+  /// assert_eq!(tx.write(b"hello"), 4);
+  ///
+  /// let mut dst = [0u8; 8];
+  /// assert_eq!(rx.read(&mut dst), 4);
+  /// assert_eq!(&dst[..4], b"hell");
+  /// ```
+  pub fn split(self) -> (Producer, Consumer)
+  where
+    B: AsRef<[u8]>,
+  {
+    self.split_with(false)
+  }
+
+  /// Splits the buffer into a [`Producer`] and a [`Consumer`] like [`split`](Buffer::split),
+  /// but the producer keeps the overwrite-oldest semantics of [`Buffer::write`].
+  pub fn split_overwriting(self) -> (Producer, Consumer)
+  where
+    B: AsRef<[u8]>,
+  {
+    self.split_with(true)
+  }
+
+  fn split_with(self, overwrite: bool) -> (Producer, Consumer)
+  where
+    B: AsRef<[u8]>,
+  {
+    let shared = Shared::new(self.size(), self.len, overwrite);
+
+    let (front, back) = self.as_slices();
+    shared.store(0, front);
+    shared.store(front.len(), back);
+
+    let shared = Arc::new(shared);
+    (
+      Producer {
+        shared: shared.clone(),
+        head: self.len,
+      },
+      Consumer { shared, tail: 0 },
+    )
+  }
+}
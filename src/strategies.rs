@@ -0,0 +1,177 @@
+//! [proptest] strategies producing buffers in arbitrary, but internally consistent,
+//! states, and a reference [`Model`] for differential property tests.
+//!
+//! The buffers are generated by applying a random sequence of [`Op`]s to a fresh
+//! buffer, so every generated state is reachable through the public API.
+//!
+//! ```rust
+//! use circularbuf::strategies::{self, Model};
+//! use proptest::{prelude::*, test_runner::TestRunner};
+//!
+//! TestRunner::default()
+//!   .run(&(1..32usize, strategies::ops(16, 32)), |(capacity, ops)| {
+//!     let mut buf = circularbuf::Buffer::new(vec![0u8; capacity]);
+//!     let mut model = Model::new(capacity);
+//!     for op in &ops {
+//!       prop_assert_eq!(op.apply(&mut buf), model.apply(op));
+//!       prop_assert!(model.matches(&buf));
+//!     }
+//!     Ok(())
+//!   })
+//!   .unwrap();
+//! ```
+
+use std::{collections::VecDeque, vec::Vec};
+
+use proptest::{collection::vec, prelude::*};
+
+use crate::Buffer;
+
+/// An operation on a buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+  /// Writes the bytes.
+  Write(Vec<u8>),
+  /// Reads, and consumes, up to this number of bytes.
+  Read(usize),
+}
+
+impl Op {
+  /// Applies the operation to `buf`, returns the bytes read, if any.
+  pub fn apply<B>(&self, buf: &mut Buffer<B>) -> Vec<u8>
+  where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+  {
+    match self {
+      Self::Write(bytes) => {
+        buf.write(bytes);
+        Vec::new()
+      }
+      Self::Read(n) => {
+        let mut out = std::vec![0; *n];
+        let read = buf.read(&mut out);
+        out.truncate(read);
+        out
+      }
+    }
+  }
+}
+
+/// A reference model of a byte [`Buffer`], backed by a [`VecDeque`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Model {
+  capacity: usize,
+  data: VecDeque<u8>,
+  written: usize,
+}
+
+impl Model {
+  /// Creates a model of an empty buffer holding up to `capacity` bytes.
+  #[inline]
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      capacity,
+      data: VecDeque::with_capacity(capacity),
+      written: 0,
+    }
+  }
+
+  /// Writes `buf`, dropping the oldest bytes beyond the capacity.
+  pub fn write(&mut self, buf: &[u8]) -> usize {
+    self.written += buf.len();
+    self.data.extend(buf);
+    let excess = self.data.len().saturating_sub(self.capacity);
+    self.data.drain(..excess);
+    buf.len()
+  }
+
+  /// Reads, and consumes, up to `dst.len()` of the oldest bytes.
+  pub fn read(&mut self, dst: &mut [u8]) -> usize {
+    let n = dst.len().min(self.data.len());
+    for (dst, b) in dst.iter_mut().zip(self.data.drain(..n)) {
+      *dst = b;
+    }
+    n
+  }
+
+  /// Applies `op`, returns the bytes read, if any.
+  pub fn apply(&mut self, op: &Op) -> Vec<u8> {
+    match op {
+      Op::Write(bytes) => {
+        self.write(bytes);
+        Vec::new()
+      }
+      Op::Read(n) => {
+        let mut out = std::vec![0; *n];
+        let read = self.read(&mut out);
+        out.truncate(read);
+        out
+      }
+    }
+  }
+
+  /// Returns the unread bytes, from the oldest to the newest.
+  #[inline]
+  pub fn contents(&self) -> Vec<u8> {
+    self.data.iter().copied().collect()
+  }
+
+  /// Returns the number of unread bytes.
+  #[inline]
+  pub fn unread(&self) -> usize {
+    self.data.len()
+  }
+
+  /// Returns the total number of bytes written.
+  #[inline]
+  pub const fn written(&self) -> usize {
+    self.written
+  }
+
+  /// Returns `true` if `buf` is in the same observable state as the model.
+  pub fn matches<B>(&self, buf: &Buffer<B>) -> bool
+  where
+    B: AsRef<[u8]>,
+  {
+    buf.size() == self.capacity
+      && buf.written() == self.written
+      && buf.unread() == self.data.len()
+      && buf.iter::<u8>().eq(self.data.iter().copied())
+  }
+}
+
+/// Returns a strategy producing a single [`Op`], writing or reading up to `max_len` bytes.
+pub fn op(max_len: usize) -> impl Strategy<Value = Op> {
+  prop_oneof![
+    vec(any::<u8>(), 0..=max_len).prop_map(Op::Write),
+    (0..=max_len).prop_map(Op::Read),
+  ]
+}
+
+/// Returns a strategy producing up to `max_ops` [`Op`]s, each writing or reading up to
+/// `max_len` bytes.
+pub fn ops(max_len: usize, max_ops: usize) -> impl Strategy<Value = Vec<Op>> {
+  vec(op(max_len), 0..=max_ops)
+}
+
+/// Returns a strategy producing heap-backed buffers of up to `max_capacity` bytes,
+/// in the state reached after a random sequence of operations.
+pub fn buffer(max_capacity: usize) -> impl Strategy<Value = Buffer<Vec<u8>>> {
+  buffer_with_model(max_capacity).prop_map(|(buf, _)| buf)
+}
+
+/// Returns a strategy producing buffers like [`buffer`], along with a [`Model`]
+/// in the same state.
+pub fn buffer_with_model(max_capacity: usize) -> impl Strategy<Value = (Buffer<Vec<u8>>, Model)> {
+  (0..=max_capacity)
+    .prop_flat_map(|capacity| (Just(capacity), ops(capacity * 2, 16)))
+    .prop_map(|(capacity, ops)| {
+      let mut buf = Buffer::new(std::vec![0; capacity]);
+      let mut model = Model::new(capacity);
+      for op in &ops {
+        op.apply(&mut buf);
+        model.apply(op);
+      }
+      (buf, model)
+    })
+}
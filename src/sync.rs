@@ -0,0 +1,414 @@
+use std::{
+  io,
+  sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockResult},
+  vec::Vec,
+};
+
+use crate::{Buffer, Lagged, Reader};
+
+/// A thread-safe circular buffer.
+///
+/// Cloning a `SyncBuffer` is cheap and yields a handle to the same ring,
+/// so multiple threads can log into one buffer. [`io::Write`] is implemented
+/// on `&SyncBuffer`, so a shared reference is enough to write.
+///
+/// ```rust
+/// use circularbuf::SyncBuffer;
+/// use std::io::Write;
+///
+/// let buf = SyncBuffer::new([0u8; 64]);
+///
+/// std::thread::scope(|s| {
+///   for _ in 0..4 {
+///     let buf = buf.clone();
+///     s.spawn(move || (&buf).write_all(b"ok").unwrap());
+///   }
+/// });
+///
+/// assert_eq!(buf.written(), 8);
+/// ```
+#[derive(Debug)]
+pub struct SyncBuffer<B> {
+  inner: Arc<Inner<B>>,
+}
+
+#[derive(Debug)]
+struct Inner<B> {
+  buf: Mutex<Buffer<B>>,
+  #[cfg(feature = "tokio")]
+  notify: tokio::sync::Notify,
+  #[cfg(all(feature = "futures-core", feature = "bytes"))]
+  followers: Mutex<Vec<core::task::Waker>>,
+}
+
+impl<B> Clone for SyncBuffer<B> {
+  #[inline]
+  fn clone(&self) -> Self {
+    Self {
+      inner: self.inner.clone(),
+    }
+  }
+}
+
+impl<B> From<Buffer<B>> for SyncBuffer<B> {
+  #[inline]
+  fn from(buf: Buffer<B>) -> Self {
+    Self {
+      inner: Arc::new(Inner {
+        buf: Mutex::new(buf),
+        #[cfg(feature = "tokio")]
+        notify: tokio::sync::Notify::new(),
+        #[cfg(all(feature = "futures-core", feature = "bytes"))]
+        followers: Mutex::new(Vec::new()),
+      }),
+    }
+  }
+}
+
+impl<B> SyncBuffer<B> {
+  /// Creates a new thread-safe buffer with the given data.
+  #[inline]
+  pub fn new(data: B) -> Self {
+    Self::from(Buffer::new(data))
+  }
+
+  /// Locks the buffer, blocking the current thread until the lock is acquired.
+  ///
+  /// A poisoned lock is recovered, as a panic while holding the lock
+  /// cannot leave the buffer in an inconsistent state.
+  #[inline]
+  pub fn lock(&self) -> MutexGuard<'_, Buffer<B>> {
+    self
+      .inner
+      .buf
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+  }
+  /// Locks the buffer without blocking, failing if it is already locked, or if it was
+  /// poisoned by a panic while locked.
+  pub(crate) fn try_lock(&self) -> TryLockResult<MutexGuard<'_, Buffer<B>>> {
+    self.inner.buf.try_lock()
+  }
+
+  /// Writes up to len(buf) elements to the internal ring,
+  /// overriding older data if necessary.
+  #[inline]
+  pub fn write<T>(&self, buf: &[T]) -> usize
+  where
+    B: AsMut<[T]>,
+    T: Copy,
+  {
+    let n = self.lock().write(buf);
+    #[cfg(feature = "tokio")]
+    self.inner.notify.notify_waiters();
+    #[cfg(all(feature = "futures-core", feature = "bytes"))]
+    self.inner.wake_followers();
+    n
+  }
+
+  /// Reads up to `dst.len()` of the oldest unread elements into `dst` and consumes them,
+  /// returns number of elements readed.
+  #[inline]
+  pub fn read<T>(&self, dst: &mut [T]) -> usize
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    self.lock().read(dst)
+  }
+
+  /// Returns a copy of the retained data, from the oldest to the newest.
+  #[inline]
+  pub fn to_vec<T>(&self) -> Vec<T>
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    self.lock().read_to_bytes().into_owned()
+  }
+
+  /// Returns the total number of elements written to the buffer
+  #[inline]
+  pub fn written(&self) -> usize {
+    self.lock().written()
+  }
+
+  /// Subscribes to the buffer, returns a [`Subscriber`] receiving all the data
+  /// written from now on, independently of the other subscribers.
+  ///
+  /// ```rust
+  /// use circularbuf::SyncBuffer;
+  ///
+  /// let buf = SyncBuffer::new([0u8; 8]);
+  /// buf.write(b"history");
+  ///
+  /// let mut sub = buf.subscribe();
+  /// std::thread::scope(|s| {
+  ///   s.spawn(|| buf.write(b"hello"));
+  /// });
+  ///
+  /// let mut dst = [0u8; 8];
+  /// assert_eq!(sub.read(&mut dst), Ok(5));
+  /// assert_eq!(&dst[..5], b"hello");
+  /// ```
+  #[inline]
+  pub fn subscribe(&self) -> Subscriber<B> {
+    Subscriber {
+      reader: self.lock().subscribe(),
+      buf: self.clone(),
+    }
+  }
+
+  /// Waits until there is unread data in the buffer, returns the number of unread elements.
+  #[cfg(feature = "tokio")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+  #[inline]
+  pub async fn wait_for_data<T>(&self) -> usize
+  where
+    B: AsRef<[T]>,
+  {
+    self.wait_for(1).await
+  }
+
+  /// Waits until at least `n` elements are unread, returns the number of unread elements.
+  ///
+  /// As the buffer never holds more than its size, `n` is capped to the size of the buffer.
+  ///
+  /// ```rust
+  /// use circularbuf::SyncBuffer;
+  ///
+  /// # #[tokio::main(flavor = "current_thread")]
+  /// # async fn main() {
+  /// let buf = SyncBuffer::new([0u8; 16]);
+  /// let writer = buf.clone();
+  /// tokio::spawn(async move {
+  ///   writer.write(b"hello");
+  /// });
+  ///
+  /// assert_eq!(buf.wait_for(5).await, 5);
+  /// # }
+  /// ```
+  #[cfg(feature = "tokio")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+  pub async fn wait_for<T>(&self, n: usize) -> usize
+  where
+    B: AsRef<[T]>,
+  {
+    loop {
+      let notified = self.inner.notify.notified();
+      let mut notified = core::pin::pin!(notified);
+      // Register before checking, so a write in between is not missed.
+      notified.as_mut().enable();
+
+      {
+        let buf = self.lock();
+        if buf.unread() >= n.min(buf.size()) {
+          return buf.unread();
+        }
+      }
+
+      notified.await;
+    }
+  }
+}
+
+impl<B> io::Write for &SyncBuffer<B>
+where
+  B: AsMut<[u8]>,
+{
+  #[inline]
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    Ok(SyncBuffer::write(self, buf))
+  }
+
+  #[inline]
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+impl<B> io::Write for SyncBuffer<B>
+where
+  B: AsMut<[u8]>,
+{
+  #[inline]
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    Ok(SyncBuffer::write(self, buf))
+  }
+
+  #[inline]
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+/// A broadcast subscription to a [`SyncBuffer`], created by [`SyncBuffer::subscribe`].
+///
+/// A subscriber receives every element written after it subscribed, unless it falls
+/// behind by more than the buffer size: the overwritten data is then reported with
+/// a [`Lagged`] error, and the subscriber resumes from the oldest retained element.
+#[derive(Debug)]
+pub struct Subscriber<B> {
+  buf: SyncBuffer<B>,
+  reader: Reader,
+}
+
+impl<B> Subscriber<B> {
+  /// Reads up to `dst.len()` of the elements the subscriber has not received yet into
+  /// `dst`, without blocking, returns the number of elements read.
+  ///
+  /// Returns a [`Lagged`] error if some of those elements were overwritten.
+  #[inline]
+  pub fn read<T>(&mut self, dst: &mut [T]) -> Result<usize, Lagged>
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    self.reader.read(&self.buf.lock(), dst)
+  }
+
+  /// Waits until the subscriber has data to receive, then reads up to `dst.len()`
+  /// elements into `dst`, returns the number of elements read.
+  ///
+  /// Returns a [`Lagged`] error if some of those elements were overwritten.
+  ///
+  /// ```rust
+  /// use circularbuf::SyncBuffer;
+  ///
+  /// # #[tokio::main(flavor = "current_thread")]
+  /// # async fn main() {
+  /// let buf = SyncBuffer::new([0u8; 16]);
+  /// let mut sub = buf.subscribe();
+  /// let writer = buf.clone();
+  /// tokio::spawn(async move {
+  ///   writer.write(b"hello");
+  /// });
+  ///
+  /// let mut dst = [0u8; 16];
+  /// assert_eq!(sub.recv(&mut dst).await, Ok(5));
+  /// # }
+  /// ```
+  #[cfg(feature = "tokio")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+  pub async fn recv<T>(&mut self, dst: &mut [T]) -> Result<usize, Lagged>
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    loop {
+      let notified = self.buf.inner.notify.notified();
+      let mut notified = core::pin::pin!(notified);
+      // Register before checking, so a write in between is not missed.
+      notified.as_mut().enable();
+
+      match self.reader.read(&self.buf.lock(), dst) {
+        Ok(0) if !dst.is_empty() => {}
+        result => return result,
+      }
+
+      notified.await;
+    }
+  }
+}
+
+/// A [`Stream`](futures_core::Stream) of the data written to a [`SyncBuffer`],
+/// created by [`SyncBuffer::follow`].
+#[cfg(all(feature = "futures-core", feature = "bytes"))]
+#[derive(Debug)]
+pub struct Follow<B> {
+  buf: SyncBuffer<B>,
+  pos: usize,
+}
+
+#[cfg(all(feature = "futures-core", feature = "bytes"))]
+const _: () = {
+  use core::{
+    pin::Pin,
+    task::{Context, Poll, Waker},
+  };
+  use futures_core::Stream;
+
+  use crate::Lagged;
+
+  impl<B> Inner<B> {
+    fn wake_followers(&self) {
+      let wakers = core::mem::take(
+        &mut *self
+          .followers
+          .lock()
+          .unwrap_or_else(PoisonError::into_inner),
+      );
+      wakers.into_iter().for_each(Waker::wake);
+    }
+
+    fn register_follower(&self, waker: &Waker) {
+      let mut followers = self
+        .followers
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+      if !followers.iter().any(|w| w.will_wake(waker)) {
+        followers.push(waker.clone());
+      }
+    }
+  }
+
+  impl<B> SyncBuffer<B> {
+    /// Returns a [`Stream`] yielding the data written to the buffer from now on,
+    /// in chunks, like `tail -f`.
+    ///
+    /// If the stream is polled too slowly and some data is overwritten before it could
+    /// be yielded, a [`Lagged`] error reporting the amount of lost data is yielded,
+    /// and the stream resumes from the oldest retained byte.
+    ///
+    /// ```rust
+    /// use circularbuf::SyncBuffer;
+    /// use futures_util::StreamExt;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let buf = SyncBuffer::new([0u8; 16]);
+    /// buf.write(b"history");
+    ///
+    /// let mut follow = buf.follow();
+    /// buf.write(b"hello");
+    /// assert_eq!(follow.next().await.unwrap().unwrap(), &b"hello"[..]);
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "future", feature = "bytes"))))]
+    pub fn follow(&self) -> Follow<B> {
+      Follow {
+        pos: self.written(),
+        buf: self.clone(),
+      }
+    }
+  }
+
+  impl<B> Stream for Follow<B>
+  where
+    B: AsRef<[u8]>,
+  {
+    type Item = Result<bytes::Bytes, Lagged>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+      let this = self.get_mut();
+      let buf = this.buf.lock();
+      let (missed, front, back) = buf.history(this.pos);
+      if missed != 0 {
+        this.pos = buf.written() - front.len() - back.len();
+        return Poll::Ready(Some(Err(Lagged(missed))));
+      }
+
+      if front.is_empty() {
+        // Registered while holding the lock, so the next write is guaranteed to wake us.
+        this.buf.inner.register_follower(cx.waker());
+        return Poll::Pending;
+      }
+
+      let mut out = bytes::BytesMut::with_capacity(front.len() + back.len());
+      out.extend_from_slice(front);
+      out.extend_from_slice(back);
+      this.pos = buf.written();
+      Poll::Ready(Some(Ok(out.freeze())))
+    }
+  }
+};
@@ -0,0 +1,151 @@
+use std::io;
+
+use crate::Buffer;
+
+/// A writer adapter which forwards every write to an inner writer, while retaining the
+/// most recent bytes in a [`Buffer`].
+///
+/// Only the bytes accepted by the inner writer are retained, so the buffer always holds
+/// the tail of what was actually written. [`io::Write`] is implemented, as well as
+/// `AsyncWrite` with the `tokio` or `future` features.
+///
+/// ```rust
+/// use circularbuf::{Buffer, TeeWriter};
+/// use std::io::Write;
+///
+/// let mut out = TeeWriter::new(Vec::new(), Buffer::new([0u8; 16]));
+/// writeln!(out, "starting").unwrap();
+/// writeln!(out, "something went wrong").unwrap();
+///
+/// // Keep the tail of the output for the crash report
+/// let (log, tail) = out.into_parts();
+/// assert_eq!(log.len(), 30);
+/// assert_eq!(tail.read_to_bytes().as_ref(), b"hing went wrong\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TeeWriter<W, B> {
+  inner: W,
+  buf: Buffer<B>,
+}
+
+impl<W, B> TeeWriter<W, B> {
+  /// Creates a new tee writer forwarding to `inner`, and retaining the tail in `buf`.
+  #[inline]
+  pub const fn new(inner: W, buf: Buffer<B>) -> Self {
+    Self { inner, buf }
+  }
+
+  /// Returns a reference to the inner writer.
+  #[inline]
+  pub const fn get_ref(&self) -> &W {
+    &self.inner
+  }
+
+  /// Returns a mutable reference to the inner writer.
+  #[inline]
+  pub const fn get_mut(&mut self) -> &mut W {
+    &mut self.inner
+  }
+
+  /// Returns the buffer retaining the tail of the output.
+  #[inline]
+  pub const fn as_buffer(&self) -> &Buffer<B> {
+    &self.buf
+  }
+
+  /// Consumes the tee writer and returns the inner writer and the buffer.
+  #[inline]
+  pub fn into_parts(self) -> (W, Buffer<B>) {
+    (self.inner, self.buf)
+  }
+}
+
+impl<W, B> io::Write for TeeWriter<W, B>
+where
+  W: io::Write,
+  B: AsMut<[u8]>,
+{
+  #[inline]
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    let n = self.inner.write(buf)?;
+    self.buf.write(&buf[..n]);
+    Ok(n)
+  }
+
+  #[inline]
+  fn flush(&mut self) -> io::Result<()> {
+    self.inner.flush()
+  }
+}
+
+#[cfg(feature = "tokio")]
+const _: () = {
+  use core::{
+    pin::Pin,
+    task::{Context, Poll, ready},
+  };
+  use tokio::io::AsyncWrite;
+
+  impl<W, B> AsyncWrite for TeeWriter<W, B>
+  where
+    W: AsyncWrite + Unpin,
+    B: AsMut<[u8]> + Unpin,
+  {
+    fn poll_write(
+      self: Pin<&mut Self>,
+      cx: &mut Context<'_>,
+      buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+      let this = self.get_mut();
+      let n = ready!(Pin::new(&mut this.inner).poll_write(cx, buf))?;
+      this.buf.write(&buf[..n]);
+      Poll::Ready(Ok(n))
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+      Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+      Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+  }
+};
+
+#[cfg(feature = "futures-io")]
+const _: () = {
+  use core::{
+    pin::Pin,
+    task::{Context, Poll, ready},
+  };
+  use futures_io::AsyncWrite;
+
+  impl<W, B> AsyncWrite for TeeWriter<W, B>
+  where
+    W: AsyncWrite + Unpin,
+    B: AsMut<[u8]> + Unpin,
+  {
+    fn poll_write(
+      self: Pin<&mut Self>,
+      cx: &mut Context<'_>,
+      buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+      let this = self.get_mut();
+      let n = ready!(Pin::new(&mut this.inner).poll_write(cx, buf))?;
+      this.buf.write(&buf[..n]);
+      Poll::Ready(Ok(n))
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+      Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+      Pin::new(&mut self.get_mut().inner).poll_close(cx)
+    }
+  }
+};
@@ -0,0 +1,246 @@
+use core::time::Duration;
+use std::collections::VecDeque;
+
+use crate::{Buffer, View};
+
+/// A source of monotonic timestamps for [`TimedBuffer`].
+///
+/// Timestamps are measured from an arbitrary, fixed origin, which makes it possible
+/// to plug in a hardware timer on `no_std` targets. Any `Fn() -> Duration` is a clock.
+pub trait Clock {
+  /// Returns the time elapsed since the origin of the clock.
+  fn now(&self) -> Duration;
+}
+
+impl<F> Clock for F
+where
+  F: Fn() -> Duration,
+{
+  #[inline]
+  fn now(&self) -> Duration {
+    self()
+  }
+}
+
+/// A [`Clock`] backed by [`std::time::Instant`].
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Copy, Clone)]
+pub struct StdClock {
+  origin: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl StdClock {
+  /// Creates a new clock whose origin is the current instant.
+  #[inline]
+  pub fn new() -> Self {
+    Self {
+      origin: std::time::Instant::now(),
+    }
+  }
+}
+
+#[cfg(feature = "std")]
+impl Default for StdClock {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+  #[inline]
+  fn now(&self) -> Duration {
+    self.origin.elapsed()
+  }
+}
+
+/// A contiguous run of data written at the same timestamp.
+#[derive(Debug, Copy, Clone)]
+struct Segment {
+  at: Duration,
+  /// The absolute stream offset right after the last byte of the segment.
+  end: usize,
+}
+
+/// A circular buffer which remembers when its data was written, and expires data
+/// older than its retention window.
+///
+/// Created by [`Buffer::with_retention`] or [`Buffer::with_retention_clock`].
+///
+/// ```rust
+/// use std::{cell::Cell, rc::Rc, time::Duration};
+/// use circularbuf::Buffer;
+///
+/// let now = Rc::new(Cell::new(Duration::ZERO));
+/// let clock = {
+///   let now = now.clone();
+///   move || now.get()
+/// };
+///
+/// let mut buf = Buffer::new([0u8; 64]).with_retention_clock(Duration::from_secs(30), clock);
+/// buf.write(b"boot;");
+/// now.set(Duration::from_secs(20));
+/// buf.write(b"tick;");
+/// now.set(Duration::from_secs(40));
+/// buf.write(b"crash;");
+///
+/// assert_eq!(buf.recent(), b"tick;crash;");
+/// assert_eq!(buf.since(Duration::from_secs(5)), b"crash;");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TimedBuffer<B, C> {
+  buf: Buffer<B>,
+  clock: C,
+  retention: Duration,
+  segments: VecDeque<Segment>,
+}
+
+impl<B> Buffer<B> {
+  /// Turns the buffer into a [`TimedBuffer`] which only retains the data written
+  /// during the last `retention`, timestamped with a [`StdClock`].
+  ///
+  /// The data already in the buffer is considered written now.
+  #[cfg(feature = "std")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+  #[inline]
+  pub fn with_retention(self, retention: Duration) -> TimedBuffer<B, StdClock> {
+    self.with_retention_clock(retention, StdClock::new())
+  }
+
+  /// Turns the buffer into a [`TimedBuffer`] which only retains the data written
+  /// during the last `retention`, timestamped with the given [`Clock`].
+  ///
+  /// The data already in the buffer is considered written now.
+  pub fn with_retention_clock<C: Clock>(self, retention: Duration, clock: C) -> TimedBuffer<B, C> {
+    let mut segments = VecDeque::new();
+    if self.len != 0 {
+      segments.push_back(Segment {
+        at: clock.now(),
+        end: self.written,
+      });
+    }
+
+    TimedBuffer {
+      buf: self,
+      clock,
+      retention,
+      segments,
+    }
+  }
+}
+
+impl<B, C: Clock> TimedBuffer<B, C> {
+  /// Writes `buf` timestamped with the current time, and expires the data older
+  /// than the retention window.
+  pub fn write(&mut self, buf: &[u8]) -> usize
+  where
+    B: AsRef<[u8]> + AsMut<[u8]>,
+  {
+    let now = self.clock.now();
+    let n = self.buf.write(buf);
+    if n != 0 {
+      let end = self.buf.written();
+      match self.segments.back_mut() {
+        Some(last) if last.at == now => last.end = end,
+        _ => self.segments.push_back(Segment { at: now, end }),
+      }
+    }
+
+    self.expire_at(now);
+    n
+  }
+
+  /// Reads up to `dst.len()` of the oldest unexpired bytes into `dst` and consumes them,
+  /// returns the number of bytes read.
+  pub fn read(&mut self, dst: &mut [u8]) -> usize
+  where
+    B: AsRef<[u8]>,
+  {
+    self.expire();
+    let n = self.buf.read(dst);
+    self.prune();
+    n
+  }
+
+  /// Returns the unread data written during the retention window.
+  #[inline]
+  pub fn recent(&self) -> View<'_, u8>
+  where
+    B: AsRef<[u8]>,
+  {
+    self.since(self.retention)
+  }
+
+  /// Returns the unread data written during the last `age`.
+  pub fn since(&self, age: Duration) -> View<'_, u8>
+  where
+    B: AsRef<[u8]>,
+  {
+    let cutoff = self.clock.now().saturating_sub(age);
+    let start = self.start();
+    let from = self
+      .segments
+      .iter()
+      .take_while(|segment| segment.at < cutoff)
+      .last()
+      .map_or(start, |segment| segment.end.max(start));
+
+    let (front, back) = self.buf.slices_from(from - start);
+    View::new(front, back)
+  }
+
+  /// Discards the data older than the retention window.
+  #[inline]
+  pub fn expire(&mut self) {
+    self.expire_at(self.clock.now());
+  }
+
+  /// Returns the retention window.
+  #[inline]
+  pub const fn retention(&self) -> Duration {
+    self.retention
+  }
+
+  /// Returns a reference to the underlying buffer.
+  #[inline]
+  pub const fn as_buffer(&self) -> &Buffer<B> {
+    &self.buf
+  }
+
+  /// Returns the underlying buffer, dropping the timestamps.
+  #[inline]
+  pub fn into_buffer(self) -> Buffer<B> {
+    self.buf
+  }
+
+  fn expire_at(&mut self, now: Duration) {
+    let cutoff = now.saturating_sub(self.retention);
+    while let Some(segment) = self.segments.front() {
+      if segment.at >= cutoff {
+        break;
+      }
+
+      let end = segment.end;
+      self.buf.discard_oldest(end.saturating_sub(self.start()));
+      self.segments.pop_front();
+    }
+    self.prune();
+  }
+
+  /// Drops the timestamps of the data which was overwritten or consumed.
+  fn prune(&mut self) {
+    let start = self.start();
+    while self.segments.front().is_some_and(|s| s.end <= start) {
+      self.segments.pop_front();
+    }
+  }
+
+  /// Returns the absolute stream offset of the oldest unread byte.
+  #[inline]
+  fn start(&self) -> usize {
+    self.buf.written - self.buf.len
+  }
+}
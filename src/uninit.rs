@@ -0,0 +1,168 @@
+use core::mem::MaybeUninit;
+
+use crate::{Buffer, copy_segments};
+
+mod sealed {
+  pub trait Sealed {}
+}
+
+/// The storage of an [`UninitBuffer`].
+///
+/// This trait is sealed, as the buffer relies on the storage returning the same memory
+/// on every access to know which elements are initialized.
+pub trait UninitStorage<T>:
+  sealed::Sealed + AsRef<[MaybeUninit<T>]> + AsMut<[MaybeUninit<T>]>
+{
+}
+
+impl<T, const N: usize> sealed::Sealed for [MaybeUninit<T>; N] {}
+impl<T, const N: usize> UninitStorage<T> for [MaybeUninit<T>; N] {}
+
+impl<T> sealed::Sealed for &mut [MaybeUninit<T>] {}
+impl<T> UninitStorage<T> for &mut [MaybeUninit<T>] {}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<T> sealed::Sealed for std::boxed::Box<[MaybeUninit<T>]> {}
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<T> UninitStorage<T> for std::boxed::Box<[MaybeUninit<T>]> {}
+
+/// A circular buffer over uninitialized storage.
+///
+/// The storage is never zeroed: the buffer tracks the prefix of the storage which has
+/// been written so far, and only ever reads from it. This avoids initializing large
+/// rings up front.
+///
+/// ```rust
+/// use circularbuf::UninitBuffer;
+///
+/// let mut buf = UninitBuffer::<[_; 8]>::uninit();
+/// buf.write(b"hello");
+/// assert_eq!(buf.initialized(), 5);
+///
+/// buf.write(b" world");
+/// assert_eq!(buf.initialized(), 8);
+///
+/// let mut dst = [0u8; 8];
+/// assert_eq!(buf.read(&mut dst), 8);
+/// assert_eq!(&dst, b"lo world");
+/// ```
+#[derive(Debug)]
+pub struct UninitBuffer<B> {
+  buf: Buffer<B>,
+  init: usize,
+}
+
+impl<T, const N: usize> UninitBuffer<[MaybeUninit<T>; N]> {
+  /// Creates a new buffer backed by an uninitialized `[MaybeUninit<T>; N]` array.
+  #[inline]
+  pub const fn uninit() -> Self {
+    Self::new([const { MaybeUninit::uninit() }; N])
+  }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+impl<T> UninitBuffer<std::boxed::Box<[MaybeUninit<T>]>> {
+  /// Creates a new heap allocated buffer with an uninitialized storage of `capacity`
+  /// elements.
+  #[inline]
+  pub fn with_capacity(capacity: usize) -> Self {
+    Self::new(std::boxed::Box::new_uninit_slice(capacity))
+  }
+}
+
+impl<B> UninitBuffer<B> {
+  /// Creates a new buffer with the given storage, whose content is ignored.
+  #[inline]
+  pub const fn new(data: B) -> Self {
+    Self {
+      buf: Buffer::new(data),
+      init: 0,
+    }
+  }
+
+  /// Writes up to len(buf) elements to the internal ring,
+  /// overriding older data if necessary.
+  pub fn write<T>(&mut self, buf: &[T]) -> usize
+  where
+    B: UninitStorage<T>,
+    T: Copy,
+  {
+    let size = self.size::<T>();
+    let cursor = self.buf.write_cursor;
+    // SAFETY: `MaybeUninit<T>` has the same layout as `T`, and the slice is shared,
+    // so no uninitialized value can be written through it.
+    #[allow(unsafe_code)]
+    let src = unsafe { &*(buf as *const [T] as *const [MaybeUninit<T>]) };
+    let n = self.buf.write(src);
+
+    // Writes are contiguous from the start of the storage, so the initialized
+    // region is always a prefix, until the first wrap makes it the whole storage.
+    self.init = self.init.max((cursor + n.min(size)).min(size));
+    n
+  }
+
+  /// Reads up to `dst.len()` of the oldest unread elements into `dst` and consumes them,
+  /// returns number of elements readed.
+  pub fn read<T>(&mut self, dst: &mut [T]) -> usize
+  where
+    B: UninitStorage<T>,
+    T: Copy,
+  {
+    let (front, back) = self.as_slices();
+    let n = copy_segments(dst, front, back);
+    self.buf.discard_oldest(n);
+    n
+  }
+
+  /// Returns the unread data as (older segment, newer segment) without any allocation.
+  pub fn as_slices<T>(&self) -> (&[T], &[T])
+  where
+    B: UninitStorage<T>,
+  {
+    let (front, back) = self.buf.as_slices();
+    debug_assert!(front.len() + back.len() <= self.init);
+    // SAFETY: the unread elements were all written, so they lie in the initialized
+    // prefix of the storage, and `MaybeUninit<T>` has the same layout as `T`.
+    #[allow(unsafe_code)]
+    unsafe {
+      (
+        &*(front as *const [MaybeUninit<T>] as *const [T]),
+        &*(back as *const [MaybeUninit<T>] as *const [T]),
+      )
+    }
+  }
+
+  /// Returns the number of elements of the storage which have been initialized.
+  #[inline]
+  pub const fn initialized(&self) -> usize {
+    self.init
+  }
+
+  /// Returns the size of the buffer
+  #[inline]
+  pub fn size<T>(&self) -> usize
+  where
+    B: UninitStorage<T>,
+  {
+    self.buf.size::<MaybeUninit<T>>()
+  }
+
+  /// Returns how many elements are pending.
+  #[inline]
+  pub const fn unread(&self) -> usize {
+    self.buf.len
+  }
+
+  /// Returns the total number of elements written to the buffer
+  #[inline]
+  pub const fn written(&self) -> usize {
+    self.buf.written()
+  }
+
+  /// Resets the buffer so it has no content, the storage stays initialized.
+  #[inline]
+  pub const fn reset(&mut self) {
+    self.buf.reset();
+  }
+}
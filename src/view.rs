@@ -0,0 +1,386 @@
+/// A lightweight read-only view over a logical range of a circular buffer.
+///
+/// The range may wrap around the end of the storage, so it is made of up to two
+/// contiguous segments. Equality is based on the logical content, regardless of
+/// where the wrap point is.
+#[derive(Debug, Copy, Clone)]
+pub struct View<'a, T> {
+  front: &'a [T],
+  back: &'a [T],
+}
+
+impl<T> Default for View<'_, T> {
+  #[inline]
+  fn default() -> Self {
+    Self::new(&[], &[])
+  }
+}
+
+impl<'a, T> View<'a, T> {
+  #[inline]
+  pub(crate) const fn new(front: &'a [T], back: &'a [T]) -> Self {
+    // Keeps the second segment empty whenever the first one is.
+    if front.is_empty() {
+      Self {
+        front: back,
+        back: &[],
+      }
+    } else {
+      Self { front, back }
+    }
+  }
+
+  /// Returns the view as (older segment, newer segment).
+  #[inline]
+  pub const fn as_slices(&self) -> (&'a [T], &'a [T]) {
+    (self.front, self.back)
+  }
+
+  /// Returns the number of elements in the view.
+  #[inline]
+  pub const fn len(&self) -> usize {
+    self.front.len() + self.back.len()
+  }
+
+  /// Returns `true` if the view contains no elements.
+  #[inline]
+  pub const fn is_empty(&self) -> bool {
+    self.front.is_empty()
+  }
+
+  /// Returns a reference to the element at the logical position `i`.
+  #[inline]
+  pub fn get(&self, i: usize) -> Option<&'a T> {
+    match i.checked_sub(self.front.len()) {
+      None => self.front.get(i),
+      Some(i) => self.back.get(i),
+    }
+  }
+
+  /// Returns an iterator over the elements of the view, in logical order.
+  #[inline]
+  pub fn iter(&self) -> core::iter::Chain<core::slice::Iter<'a, T>, core::slice::Iter<'a, T>> {
+    self.front.iter().chain(self.back)
+  }
+
+  /// Divides the view into two at the logical position `mid`.
+  ///
+  /// ## Panics
+  ///
+  /// Panics if `mid > len`.
+  pub fn split_at(&self, mid: usize) -> (Self, Self) {
+    match mid.checked_sub(self.front.len()) {
+      None => {
+        let (a, b) = self.front.split_at(mid);
+        (Self::new(a, &[]), Self::new(b, self.back))
+      }
+      Some(mid) => {
+        let (a, b) = self.back.split_at(mid);
+        (Self::new(self.front, a), Self::new(b, &[]))
+      }
+    }
+  }
+
+  /// Returns the logical position of the first occurrence of `needle` in the view,
+  /// including occurrences spanning the two segments.
+  pub fn find(&self, needle: &[T]) -> Option<usize>
+  where
+    T: PartialEq,
+  {
+    let (front, back) = (self.front, self.back);
+    if needle.is_empty() {
+      return Some(0);
+    }
+
+    if let Some(i) = front.windows(needle.len()).position(|w| w == needle) {
+      return Some(i);
+    }
+
+    // Occurrences starting in the front segment and ending in the back one
+    let start = front.len().saturating_sub(needle.len() - 1);
+    for i in start..front.len() {
+      let (head, tail) = needle.split_at(front.len() - i);
+      if front[i..] == *head && back.starts_with(tail) {
+        return Some(i);
+      }
+    }
+
+    back
+      .windows(needle.len())
+      .position(|w| w == needle)
+      .map(|i| i + front.len())
+  }
+
+  /// Returns `true` if `needle` occurs in the view, including across the two segments.
+  #[inline]
+  pub fn contains(&self, needle: &[T]) -> bool
+  where
+    T: PartialEq,
+  {
+    self.find(needle).is_some()
+  }
+
+  /// Copies the elements of the view into `dst`, returns the number of elements copied,
+  /// which is the minimum of the view's and `dst`'s length.
+  #[inline]
+  pub fn copy_to_slice(&self, dst: &mut [T]) -> usize
+  where
+    T: Copy,
+  {
+    crate::copy_segments(dst, self.front, self.back)
+  }
+
+  /// Copies the elements of the view into a new vector.
+  #[cfg(any(feature = "std", feature = "alloc"))]
+  #[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+  pub fn to_vec(&self) -> std::vec::Vec<T>
+  where
+    T: Clone,
+  {
+    let mut out = std::vec::Vec::with_capacity(self.len());
+    out.extend_from_slice(self.front);
+    out.extend_from_slice(self.back);
+    out
+  }
+}
+
+impl<'a, T> View<'a, T> {
+  /// Returns a [`ViewReader`] reading the view from its start.
+  #[inline]
+  pub const fn reader(self) -> ViewReader<'a, T> {
+    ViewReader { view: self, pos: 0 }
+  }
+}
+
+/// A cursor over a [`View`], created by [`View::reader`].
+///
+/// With the `std` feature, it implements [`Read`](std::io::Read),
+/// [`BufRead`](std::io::BufRead) and [`Seek`](std::io::Seek) over byte views, so parsers
+/// can skip around the retained data unmodified. Positions are logical:
+/// [`SeekFrom::Start`](std::io::SeekFrom::Start) is relative to the oldest element of the
+/// view, and [`SeekFrom::End`](std::io::SeekFrom::End) to the newest one.
+///
+/// ```rust
+/// use circularbuf::Buffer;
+/// use std::io::{Read, Seek, SeekFrom};
+///
+/// let mut buf = Buffer::new([0u8; 8]);
+/// buf.write(b"hello world");
+///
+/// let mut reader = buf.range(..).reader();
+/// reader.seek(SeekFrom::End(-5)).unwrap();
+/// let mut dst = String::new();
+/// reader.read_to_string(&mut dst).unwrap();
+/// assert_eq!(dst, "world");
+///
+/// reader.seek(SeekFrom::Start(1)).unwrap();
+/// let mut dst = [0u8; 3];
+/// reader.read_exact(&mut dst).unwrap();
+/// assert_eq!(&dst, b"o w");
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct ViewReader<'a, T> {
+  view: View<'a, T>,
+  pos: usize,
+}
+
+impl<'a, T> ViewReader<'a, T> {
+  /// Returns the logical position of the cursor, relative to the start of the view.
+  #[inline]
+  pub const fn position(&self) -> usize {
+    self.pos
+  }
+
+  /// Moves the cursor to the logical position `pos`, which may be past the end of the view.
+  #[inline]
+  pub const fn set_position(&mut self, pos: usize) {
+    self.pos = pos;
+  }
+
+  /// Returns the part of the view following the cursor.
+  #[inline]
+  pub fn remaining_view(&self) -> View<'a, T> {
+    self.view.split_at(self.pos.min(self.view.len())).1
+  }
+
+  /// Returns the whole view, regardless of the cursor.
+  #[inline]
+  pub const fn view(&self) -> View<'a, T> {
+    View::new(self.view.front, self.view.back)
+  }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+  use std::io;
+
+  impl io::Read for ViewReader<'_, u8> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+      let n = self.remaining_view().copy_to_slice(buf);
+      self.pos += n;
+      Ok(n)
+    }
+  }
+
+  impl io::BufRead for ViewReader<'_, u8> {
+    #[inline]
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+      Ok(self.remaining_view().front)
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+      self.pos += amt;
+    }
+  }
+
+  impl io::Seek for ViewReader<'_, u8> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+      let relative = |base: usize, n: i64| base.checked_add_signed(isize::try_from(n).ok()?);
+      let pos = match pos {
+        io::SeekFrom::Start(n) => usize::try_from(n).ok(),
+        io::SeekFrom::End(n) => relative(self.view.len(), n),
+        io::SeekFrom::Current(n) => relative(self.pos, n),
+      };
+      match pos {
+        Some(pos) => {
+          self.pos = pos;
+          Ok(pos as u64)
+        }
+        None => Err(io::Error::new(
+          io::ErrorKind::InvalidInput,
+          "invalid seek to a negative or overflowing position",
+        )),
+      }
+    }
+  }
+};
+
+#[cfg(feature = "bytes")]
+const _: () = {
+  /// The chunks are the two segments of the view, so the data is consumed without copies.
+  ///
+  /// ```rust
+  /// use bytes::Buf;
+  /// use circularbuf::Buffer;
+  ///
+  /// let mut buf = Buffer::new([0u8; 6]);
+  /// buf.write(b"\x00\x05");
+  /// buf.write(b"hello");
+  ///
+  /// let mut reader = buf.range(..).reader();
+  /// assert_eq!(reader.get_u8(), 5);
+  /// assert_eq!(reader.chunk(), b"hell");
+  /// assert_eq!(reader.copy_to_bytes(5), &b"hello"[..]);
+  /// assert!(!reader.has_remaining());
+  /// ```
+  impl bytes::Buf for ViewReader<'_, u8> {
+    #[inline]
+    fn remaining(&self) -> usize {
+      self.view.len().saturating_sub(self.pos)
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+      self.remaining_view().front
+    }
+
+    #[inline]
+    fn advance(&mut self, cnt: usize) {
+      assert!(
+        cnt <= self.remaining(),
+        "cannot advance past the end of the view"
+      );
+      self.pos += cnt;
+    }
+  }
+};
+
+impl<'a> View<'a, u8> {
+  /// Returns an iterator over the newline-delimited lines of the view, in logical order.
+  ///
+  /// Lines may span the two segments. Like [`BufRead::lines`](std::io::BufRead::lines),
+  /// the trailing `\n` or `\r\n` is stripped, and the last line is yielded even if
+  /// it is not terminated. The newline search uses `memchr` when the `memchr` feature
+  /// is enabled.
+  pub fn lines(self) -> impl Iterator<Item = View<'a, u8>> + 'a {
+    let mut rest = self;
+    core::iter::from_fn(move || {
+      if rest.is_empty() {
+        return None;
+      }
+
+      let (mut line, tail) = match rest.position_of(b'\n') {
+        Some(pos) => {
+          let (line, tail) = rest.split_at(pos);
+          (line, tail.split_at(1).1)
+        }
+        None => (rest, View::default()),
+      };
+      rest = tail;
+
+      if line.len().checked_sub(1).and_then(|i| line.get(i)) == Some(&b'\r') {
+        line = line.split_at(line.len() - 1).0;
+      }
+      Some(line)
+    })
+  }
+
+  /// Returns the logical position of the first occurrence of `byte`.
+  pub(crate) fn position_of(&self, byte: u8) -> Option<usize> {
+    #[cfg(feature = "memchr")]
+    let find = |s: &[u8]| memchr::memchr(byte, s);
+    #[cfg(not(feature = "memchr"))]
+    let find = |s: &[u8]| s.iter().position(|b| *b == byte);
+
+    find(self.front).or_else(|| find(self.back).map(|i| i + self.front.len()))
+  }
+}
+
+impl<'a, T> IntoIterator for View<'a, T> {
+  type Item = &'a T;
+  type IntoIter = core::iter::Chain<core::slice::Iter<'a, T>, core::slice::Iter<'a, T>>;
+
+  #[inline]
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl<T: PartialEq> PartialEq for View<'_, T> {
+  #[inline]
+  fn eq(&self, other: &Self) -> bool {
+    self.len() == other.len() && self.iter().eq(other.iter())
+  }
+}
+
+impl<T: Eq> Eq for View<'_, T> {}
+
+impl<T: PartialEq> PartialEq<[T]> for View<'_, T> {
+  #[inline]
+  fn eq(&self, other: &[T]) -> bool {
+    self.len() == other.len() && self.iter().eq(other)
+  }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq<[T; N]> for View<'_, T> {
+  #[inline]
+  fn eq(&self, other: &[T; N]) -> bool {
+    self == other.as_slice()
+  }
+}
+
+impl<T: PartialEq> PartialEq<&[T]> for View<'_, T> {
+  #[inline]
+  fn eq(&self, other: &&[T]) -> bool {
+    self == *other
+  }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq<&[T; N]> for View<'_, T> {
+  #[inline]
+  fn eq(&self, other: &&[T; N]) -> bool {
+    self == other.as_slice()
+  }
+}
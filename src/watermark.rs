@@ -0,0 +1,148 @@
+use crate::Buffer;
+
+/// A fill threshold crossed by a [`WatermarkBuffer`], as a percentage of its size.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Crossing {
+  /// The fill level rose to or above the threshold.
+  Above(u8),
+  /// The fill level fell below the threshold.
+  Below(u8),
+}
+
+/// A circular buffer which tracks its high-water mark, and reports when its fill level
+/// crosses configured thresholds.
+///
+/// Created by [`Buffer::with_watermarks`]. The callback is invoked after the write or read
+/// which crossed the thresholds, once per threshold, in the order they were crossed.
+/// This makes it possible to alert before data starts being overwritten.
+///
+/// ```rust
+/// use circularbuf::{Buffer, Crossing};
+///
+/// let mut crossings = Vec::new();
+/// let mut buf = Buffer::new([0u8; 10]).with_watermarks(&[50, 80], |c| crossings.push(c));
+///
+/// buf.write(b"hello");
+/// buf.write(b"wor");
+/// buf.read(&mut [0u8; 6]);
+/// assert_eq!(buf.high_water_mark(), 8);
+///
+/// drop(buf);
+/// assert_eq!(
+///   crossings,
+///   [Crossing::Above(50), Crossing::Above(80), Crossing::Below(80), Crossing::Below(50)],
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct WatermarkBuffer<B, F> {
+  buf: Buffer<B>,
+  /// Bit `i` is set if `i`% is a threshold.
+  thresholds: u128,
+  high_water: usize,
+  on_cross: F,
+}
+
+impl<B> Buffer<B> {
+  /// Turns the buffer into a [`WatermarkBuffer`] which invokes `on_cross` whenever
+  /// its fill level crosses one of the `thresholds`, given as percentages of its size.
+  ///
+  /// ## Panics
+  ///
+  /// Panics if a threshold is `0`, which an empty buffer is already at, or greater
+  /// than `100`.
+  pub fn with_watermarks<F>(self, thresholds: &[u8], on_cross: F) -> WatermarkBuffer<B, F>
+  where
+    F: FnMut(Crossing),
+  {
+    let thresholds = thresholds.iter().fold(0u128, |mask, &t| {
+      assert!(t != 0, "threshold 0% can never be crossed");
+      assert!(t <= 100, "threshold {t}% is greater than 100%");
+      mask | (1 << t)
+    });
+
+    WatermarkBuffer {
+      high_water: self.len,
+      buf: self,
+      thresholds,
+      on_cross,
+    }
+  }
+}
+
+impl<B, F> WatermarkBuffer<B, F>
+where
+  F: FnMut(Crossing),
+{
+  /// Writes up to len(buf) elements to the internal ring,
+  /// overriding older data if necessary.
+  pub fn write<T>(&mut self, buf: &[T]) -> usize
+  where
+    B: AsRef<[T]> + AsMut<[T]>,
+    T: Copy,
+  {
+    let before = self.buf.len;
+    let n = self.buf.write(buf);
+    self.high_water = self.high_water.max(self.buf.len);
+    self.notify(before, self.buf.size());
+    n
+  }
+
+  /// Reads up to `dst.len()` of the oldest unread elements into `dst` and consumes them,
+  /// returns number of elements readed.
+  pub fn read<T>(&mut self, dst: &mut [T]) -> usize
+  where
+    B: AsRef<[T]>,
+    T: Copy,
+  {
+    let before = self.buf.len;
+    let n = self.buf.read(dst);
+    self.notify(before, self.buf.size());
+    n
+  }
+
+  /// Invokes the callback for every threshold between the fill levels `before` and now.
+  fn notify(&mut self, before: usize, size: usize) {
+    let after = self.buf.len;
+    let level = |t: u32| ((size as u128 * t as u128).div_ceil(100)) as usize;
+    let thresholds = (0..=100).filter(|t| self.thresholds & (1 << t) != 0);
+
+    if after > before {
+      for t in thresholds.filter(|&t| (before + 1..=after).contains(&level(t))) {
+        (self.on_cross)(Crossing::Above(t as u8));
+      }
+    } else if after < before {
+      for t in thresholds
+        .rev()
+        .filter(|&t| (after + 1..=before).contains(&level(t)))
+      {
+        (self.on_cross)(Crossing::Below(t as u8));
+      }
+    }
+  }
+}
+
+impl<B, F> WatermarkBuffer<B, F> {
+  /// Returns the maximum number of unread elements the buffer has held.
+  #[inline]
+  pub const fn high_water_mark(&self) -> usize {
+    self.high_water
+  }
+
+  /// Resets the high-water mark to the current number of unread elements.
+  #[inline]
+  pub const fn reset_high_water_mark(&mut self) {
+    self.high_water = self.buf.len;
+  }
+
+  /// Returns the underlying buffer.
+  #[inline]
+  pub const fn as_buffer(&self) -> &Buffer<B> {
+    &self.buf
+  }
+
+  /// Consumes the watermark buffer and returns the underlying buffer.
+  #[inline]
+  pub fn into_buffer(self) -> Buffer<B> {
+    self.buf
+  }
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "std")]
+
+use std::io::{self, Write};
+
+use circularbuf::Buffer;
+
+#[test]
+fn archive_is_complete() {
+  let mut buf = Buffer::new([0u8; 7]).with_audit_sink(Vec::new());
+  let mut expected = Vec::new();
+  for i in 0..50u8 {
+    let chunk = vec![i; usize::from(i % 11)];
+    buf.write_all(&chunk).unwrap();
+    expected.extend_from_slice(&chunk);
+  }
+
+  let (buf, mut archive) = buf.into_parts();
+  archive.extend_from_slice(&buf.read_to_bytes());
+  assert_eq!(archive, expected);
+}
+
+#[test]
+fn consumed_bytes_are_not_archived() {
+  let mut buf = Buffer::new([0u8; 4]).with_audit_sink(Vec::new());
+  buf.write(b"abc").unwrap();
+  assert_eq!(buf.read(&mut [0u8; 2]), 2);
+  buf.write(b"def").unwrap();
+  assert_eq!(buf.sink(), b"");
+  buf.write(b"g").unwrap();
+  assert_eq!(buf.sink(), b"c");
+}
+
+/// A sink which fails after accepting `limit` bytes.
+struct Failing {
+  limit: usize,
+}
+
+impl Write for Failing {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    match self.limit {
+      0 => Err(io::Error::other("disk full")),
+      limit => {
+        let n = buf.len().min(limit);
+        self.limit -= n;
+        Ok(n)
+      }
+    }
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+#[test]
+fn sink_errors_leave_the_ring_untouched() {
+  let mut buf = Buffer::new([0u8; 4]).with_audit_sink(Failing { limit: 1 });
+  buf.write(b"abcd").unwrap();
+  assert!(buf.write(b"ef").is_err());
+  assert_eq!(buf.as_buffer().read_to_bytes().as_ref(), b"abcd");
+
+  buf.sink_mut().limit = 2;
+  assert_eq!(buf.write(b"ef").unwrap(), 2);
+  assert_eq!(buf.as_buffer().read_to_bytes().as_ref(), b"cdef");
+}
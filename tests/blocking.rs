@@ -0,0 +1,122 @@
+#![cfg(feature = "std")]
+
+use std::io::{Read, Write};
+
+use circularbuf::BlockingBuffer;
+
+#[test]
+fn pipe() {
+  let pipe = BlockingBuffer::new([0u8; 7]);
+  let data = (0..10_000u32)
+    .flat_map(u32::to_le_bytes)
+    .collect::<Vec<_>>();
+
+  std::thread::scope(|s| {
+    let writer = pipe.clone();
+    let data = &data;
+    s.spawn(move || {
+      for chunk in data.chunks(13) {
+        assert_eq!(writer.write(chunk), chunk.len());
+      }
+      writer.close();
+    });
+
+    let mut out = Vec::new();
+    (&pipe).read_to_end(&mut out).unwrap();
+    assert_eq!(&out, data);
+  });
+
+  assert_eq!(pipe.written(), data.len());
+  assert_eq!(pipe.unread(), 0);
+}
+
+#[test]
+fn never_overwrites() {
+  let pipe = BlockingBuffer::new([0u8; 4]);
+  std::thread::scope(|s| {
+    let writer = pipe.clone();
+    let handle = s.spawn(move || writer.write(b"abcdef"));
+
+    while pipe.unread() < 4 {
+      std::thread::yield_now();
+    }
+    let mut dst = [0u8; 4];
+    assert_eq!(pipe.read(&mut dst), 4);
+    assert_eq!(&dst, b"abcd");
+
+    assert_eq!(handle.join().unwrap(), 6);
+    assert_eq!(pipe.read(&mut dst), 2);
+    assert_eq!(&dst[..2], b"ef");
+  });
+}
+
+#[test]
+fn close() {
+  let mut pipe = BlockingBuffer::new([0u8; 2]);
+  std::thread::scope(|s| {
+    let writer = pipe.clone();
+    let handle = s.spawn(move || writer.write(b"abc"));
+
+    while pipe.unread() < 2 {
+      std::thread::yield_now();
+    }
+    pipe.close();
+    assert_eq!(handle.join().unwrap(), 2);
+  });
+
+  assert!(pipe.is_closed());
+  assert_eq!(
+    Write::write(&mut pipe, b"x").unwrap_err().kind(),
+    std::io::ErrorKind::BrokenPipe
+  );
+
+  let mut dst = [0u8; 4];
+  assert_eq!(BlockingBuffer::read(&pipe, &mut dst), 2);
+  assert_eq!(BlockingBuffer::read(&pipe, &mut dst), 0);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(flavor = "current_thread")]
+async fn tokio_backpressure() {
+  use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+  let pipe = BlockingBuffer::new([0u8; 7]);
+  let data = (0..1000u32).flat_map(u32::to_le_bytes).collect::<Vec<_>>();
+
+  let mut writer = pipe.clone();
+  let task = tokio::spawn({
+    let data = data.clone();
+    async move {
+      AsyncWriteExt::write_all(&mut writer, &data).await.unwrap();
+      writer.shutdown().await.unwrap();
+    }
+  });
+
+  let mut out = Vec::new();
+  AsyncReadExt::read_to_end(&mut &pipe, &mut out)
+    .await
+    .unwrap();
+  task.await.unwrap();
+  assert_eq!(out, data);
+}
+
+#[cfg(feature = "futures-io")]
+#[tokio::test(flavor = "current_thread")]
+async fn futures_backpressure() {
+  use futures_util::{AsyncReadExt, AsyncWriteExt};
+
+  let pipe = BlockingBuffer::new([0u8; 3]);
+  let mut writer = pipe.clone();
+  let write = async {
+    AsyncWriteExt::write_all(&mut writer, b"hello world")
+      .await
+      .unwrap();
+    AsyncWriteExt::close(&mut writer).await.unwrap();
+  };
+
+  let (mut reader, mut out) = (&pipe, String::new());
+  let read = AsyncReadExt::read_to_string(&mut reader, &mut out);
+  let ((), n) = futures_util::join!(write, read);
+  assert_eq!(n.unwrap(), 11);
+  assert_eq!(out, "hello world");
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "deflate")]
+
+use circularbuf::Buffer;
+
+fn log_line(i: usize) -> String {
+  format!("{i:06} GET /index.html 200\n")
+}
+
+#[test]
+fn retains_history() {
+  let mut buf = Buffer::new([0u8; 512]).with_compressed_history(4096);
+  let mut expected = Vec::new();
+  for i in 0..200 {
+    let line = log_line(i);
+    assert_eq!(buf.write(line.as_bytes()), line.len());
+    expected.extend_from_slice(line.as_bytes());
+  }
+
+  // Nothing was dropped yet
+  assert_eq!(buf.len(), expected.len());
+  assert_eq!(buf.to_vec(), expected);
+  assert!(buf.compressed_len() < expected.len() - 512);
+
+  let mut out = Vec::new();
+  assert_eq!(buf.copy_to_writer(&mut out).unwrap(), expected.len());
+  assert_eq!(out, expected);
+}
+
+#[test]
+fn drops_oldest_blocks() {
+  let mut buf = Buffer::new([0u8; 256]).with_compressed_history(2048);
+  let mut expected = Vec::new();
+  for i in 0..10_000 {
+    let line = log_line(i);
+    buf.write(line.as_bytes());
+    expected.extend_from_slice(line.as_bytes());
+  }
+
+  assert!(buf.compressed_len() <= 2048);
+  let history = buf.to_vec();
+  assert_eq!(history.len(), buf.len());
+  assert!(history.len() > 4 * 2048);
+  assert!(expected.ends_with(&history));
+}
+
+#[test]
+fn zero_budget() {
+  let mut buf = Buffer::new([0u8; 4]).with_compressed_history(0);
+  assert!(buf.is_empty());
+  buf.write(b"hello world");
+  assert_eq!(buf.to_vec(), b"orld");
+  assert_eq!(buf.compressed_len(), 0);
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "std")]
+
+use std::{fs, io::ErrorKind, path::PathBuf};
+
+use circularbuf::FileBuffer;
+
+fn path(name: &str) -> PathBuf {
+  let path = std::env::temp_dir().join(format!("circularbuf-file-{}-{name}", std::process::id()));
+  let _ = fs::remove_file(&path);
+  path
+}
+
+#[test]
+fn write_read_recover() {
+  let path = path("recover");
+  {
+    let mut buf = FileBuffer::open(&path, 8).unwrap();
+    assert_eq!(buf.write(b"hello").unwrap(), 5);
+    assert_eq!(buf.write(b" world").unwrap(), 6);
+    assert_eq!(buf.to_vec().unwrap(), b"lo world");
+
+    let mut dst = [0u8; 3];
+    assert_eq!(buf.read(&mut dst).unwrap(), 3);
+    assert_eq!(&dst, b"lo ");
+  }
+
+  let mut buf = FileBuffer::open(&path, 8).unwrap();
+  assert_eq!((buf.written(), buf.unread()), (11, 5));
+  assert_eq!(buf.to_vec().unwrap(), b"world");
+
+  buf.write(b"!!!!").unwrap();
+  assert_eq!(buf.to_vec().unwrap(), b"orld!!!!");
+  buf.reset().unwrap();
+  assert!(buf.to_vec().unwrap().is_empty());
+  drop(buf);
+
+  let err = FileBuffer::open(&path, 4).unwrap_err();
+  assert_eq!(err.kind(), ErrorKind::InvalidData);
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn crash_before_header_update() {
+  let path = path("crash");
+  {
+    let mut buf = FileBuffer::open(&path, 8).unwrap();
+    buf.write(b"abcdefgh").unwrap();
+  }
+
+  // Simulate a crash after the data of a write went through, but before the final
+  // header update: only the write-ahead header shrinking the buffer made it.
+  let before = fs::read(&path).unwrap();
+  {
+    let mut buf = FileBuffer::open(&path, 8).unwrap();
+    buf.write(b"XYZ").unwrap();
+  }
+  let mut after = fs::read(&path).unwrap();
+  let newest = (0..2)
+    .max_by_key(|i| u64::from_le_bytes(after[i * 64 + 8..i * 64 + 16].try_into().unwrap()))
+    .unwrap();
+  after[newest * 64..newest * 64 + 64].copy_from_slice(&before[newest * 64..newest * 64 + 64]);
+  fs::write(&path, after).unwrap();
+
+  let buf = FileBuffer::open(&path, 8).unwrap();
+  assert_eq!(buf.to_vec().unwrap(), b"defgh");
+  drop(buf);
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn crash_before_first_header() {
+  let path = path("unwritten");
+  // Simulate a crash after the file was sized, but before its first header was stored.
+  fs::write(&path, [0u8; 128 + 8]).unwrap();
+
+  let mut buf = FileBuffer::open(&path, 8).unwrap();
+  assert_eq!((buf.written(), buf.unread()), (0, 0));
+  buf.write(b"abc").unwrap();
+  drop(buf);
+
+  let buf = FileBuffer::open(&path, 8).unwrap();
+  assert_eq!(buf.to_vec().unwrap(), b"abc");
+  drop(buf);
+  fs::remove_file(&path).unwrap();
+}
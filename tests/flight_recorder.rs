@@ -0,0 +1,48 @@
+#![cfg(feature = "std")]
+
+use std::{fs, io::Write, panic};
+
+use circularbuf::{SyncBuffer, flight_recorder};
+
+#[test]
+fn dumps_on_panic() {
+  let path = std::env::temp_dir().join(format!("circularbuf-flight-{}", std::process::id()));
+  let buf = SyncBuffer::new([0u8; 16]);
+  flight_recorder::install(buf.clone(), path.as_path());
+
+  (&buf).write_all(b"step 1;step 2;step 3;").unwrap();
+  assert!(panic::catch_unwind(|| panic!("boom")).is_err());
+
+  assert_eq!(fs::read(&path).unwrap(), b"1;step 2;step 3;");
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn poisoned_buffer_is_dumped_with_a_note() {
+  let path = std::env::temp_dir().join(format!(
+    "circularbuf-flight-poisoned-{}",
+    std::process::id()
+  ));
+  let buf = SyncBuffer::new([0u8; 8]);
+  flight_recorder::install(buf.clone(), path.as_path());
+  (&buf).write_all(b"last op").unwrap();
+
+  let poisoner = buf.clone();
+  std::thread::spawn(move || {
+    let _guard = poisoner.lock();
+    panic!("poisoned");
+  })
+  .join()
+  .unwrap_err();
+  assert_eq!(
+    fs::read(&path).unwrap(),
+    b"<flight recorder: buffer locked while panicking>\n"
+  );
+
+  assert!(panic::catch_unwind(|| panic!("boom")).is_err());
+  assert_eq!(
+    fs::read(&path).unwrap(),
+    b"<flight recorder: buffer poisoned by an earlier panic>\nlast op"
+  );
+  fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "critical-section")]
+
+use circularbuf::IsrBuffer;
+
+static RX: IsrBuffer<[u8; 8]> = IsrBuffer::new([0; 8]);
+
+#[test]
+fn shared_static() {
+  std::thread::scope(|s| {
+    s.spawn(|| {
+      for chunk in [&b"hello"[..], b" world"] {
+        RX.write(chunk);
+      }
+    });
+  });
+
+  assert_eq!(RX.unread(), 8);
+  let mut tail = [0u8; 5];
+  assert_eq!(RX.tail(5, &mut tail), 5);
+  assert_eq!(&tail, b"world");
+
+  let mut dst = [0u8; 8];
+  assert_eq!(RX.read(&mut dst), 8);
+  assert_eq!(&dst, b"lo world");
+  assert_eq!(RX.with(|b| b.written()), 11);
+}
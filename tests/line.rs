@@ -0,0 +1,49 @@
+use circularbuf::LineBuffer;
+
+fn lines<B: AsRef<[u8]>>(buf: &LineBuffer<B>) -> Vec<String> {
+  buf
+    .lines()
+    .map(|l| String::from_utf8(l.to_vec()).unwrap())
+    .collect()
+}
+
+#[test]
+fn max_lines() {
+  let mut buf = LineBuffer::new([0u8; 64], 3);
+  for i in 0..10 {
+    buf.write(format!("line {i}\n").as_bytes());
+  }
+  assert_eq!(buf.len(), 3);
+  assert_eq!(lines(&buf), ["line 7", "line 8", "line 9"]);
+
+  // A pending line is retained but not exposed
+  buf.write(b"partial");
+  assert_eq!(lines(&buf), ["line 7", "line 8", "line 9"]);
+  buf.write(b"\n");
+  assert_eq!(lines(&buf), ["line 8", "line 9", "partial"]);
+}
+
+#[test]
+fn byte_overflow_never_tears() {
+  let mut buf = LineBuffer::new([0u8; 16], 100);
+  buf.write(b"aaaa\nbbbb\ncccc\n");
+  buf.write(b"dddd\n");
+  // "aaaa\n" was partially overwritten, so it is dropped as a whole
+  assert_eq!(lines(&buf), ["bbbb", "cccc", "dddd"]);
+
+  // The evicted data ends exactly on a line boundary
+  let mut buf = LineBuffer::new([0u8; 10], 100);
+  buf.write(b"aaaa\nbbbb\n");
+  buf.write(b"cccc\n");
+  assert_eq!(lines(&buf), ["bbbb", "cccc"]);
+}
+
+#[test]
+fn line_longer_than_storage() {
+  let mut buf = LineBuffer::new([0u8; 8], 10);
+  buf.write(b"ok\n");
+  buf.write(b"this line is way too long");
+  assert!(buf.is_empty());
+  buf.write(b" still\nnext\n");
+  assert_eq!(lines(&buf), ["next"]);
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "log")]
+
+use circularbuf::RingLogger;
+use log::LevelFilter;
+
+static LOGGER: RingLogger<[u8; 64]> = RingLogger::new([0; 64], LevelFilter::Debug);
+
+#[test]
+fn tail() {
+  log::set_logger(&LOGGER).unwrap();
+  log::set_max_level(LevelFilter::Trace);
+
+  for i in 0..10 {
+    log::debug!(target: "t", "message {i}");
+  }
+  log::trace!(target: "t", "ignored");
+  assert_eq!(LOGGER.lines(), ["DEBUG t: message 8", "DEBUG t: message 9"]);
+
+  log::warn!(target: "t", "{}", "é".repeat(64));
+  let dump = LOGGER.dump();
+  assert_eq!(dump.len(), 60 + 1);
+  assert!(dump.starts_with("WARN t: é"));
+
+  LOGGER.clear();
+  assert!(LOGGER.lines().is_empty());
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "metrics")]
+
+use circularbuf::Buffer;
+use metrics_util::{
+  MetricKind,
+  debugging::{DebugValue, DebuggingRecorder},
+};
+
+#[test]
+fn metered() {
+  let recorder = DebuggingRecorder::new();
+  let snapshotter = recorder.snapshotter();
+
+  metrics::with_local_recorder(&recorder, || {
+    let mut buf = Buffer::new([0u8; 8]).with_metrics("test");
+    buf.write(b"hello");
+    buf.write(b" world");
+    buf.read(&mut [0u8; 2]);
+  });
+
+  let mut metrics = snapshotter
+    .snapshot()
+    .into_vec()
+    .into_iter()
+    .map(|(key, _, _, value)| {
+      let (kind, key) = key.into_parts();
+      let labels = key
+        .labels()
+        .map(|l| (l.key(), l.value()))
+        .collect::<Vec<_>>();
+      assert_eq!(labels, [("buffer", "test")]);
+      (kind, key.name().to_string(), value)
+    })
+    .collect::<Vec<_>>();
+  metrics.sort_by(|a, b| a.1.cmp(&b.1));
+
+  assert_eq!(
+    metrics,
+    [
+      (
+        MetricKind::Counter,
+        "circularbuf_bytes_overwritten".to_string(),
+        DebugValue::Counter(3)
+      ),
+      (
+        MetricKind::Counter,
+        "circularbuf_bytes_written".to_string(),
+        DebugValue::Counter(11)
+      ),
+      (
+        MetricKind::Gauge,
+        "circularbuf_fill_ratio".to_string(),
+        DebugValue::Gauge(0.75.into())
+      ),
+    ]
+  );
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "mmap")]
+
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use circularbuf::MmapBuffer;
+
+fn open(path: &Path, capacity: usize) -> std::io::Result<MmapBuffer> {
+  // SAFETY: every test uses its own file, only mapped by one buffer at a time
+  unsafe { MmapBuffer::open(path, capacity) }
+}
+
+fn path(name: &str) -> PathBuf {
+  let path = std::env::temp_dir().join(format!("circularbuf-{}-{name}", std::process::id()));
+  let _ = fs::remove_file(&path);
+  path
+}
+
+#[test]
+fn recover() {
+  let path = path("recover");
+  {
+    let mut buf = open(&path, 8).unwrap();
+    buf.write(b"hello world");
+    let mut dst = [0u8; 2];
+    assert_eq!(buf.read(&mut dst), 2);
+  }
+
+  let buf = open(&path, 8).unwrap();
+  assert_eq!(buf.as_buffer().written(), 11);
+  assert_eq!(buf.as_buffer().read_to_bytes().as_ref(), b" world");
+  drop(buf);
+
+  let err = open(&path, 16).unwrap_err();
+  assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn torn_header() {
+  let path = path("torn");
+  {
+    let mut buf = open(&path, 8).unwrap();
+    buf.write(b"abc");
+    buf.write(b"def");
+  }
+
+  // Corrupt the slot holding the most recent header, the previous one takes over.
+  let mut raw = fs::read(&path).unwrap();
+  let newest = (0..2)
+    .max_by_key(|i| u64::from_le_bytes(raw[i * 64 + 8..i * 64 + 16].try_into().unwrap()))
+    .unwrap();
+  raw[newest * 64 + 20] ^= 0xff;
+  fs::write(&path, raw).unwrap();
+
+  let buf = open(&path, 8).unwrap();
+  assert_eq!(buf.as_buffer().read_to_bytes().as_ref(), b"abc");
+  drop(buf);
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn crash_before_first_header() {
+  let path = path("unwritten");
+  // Simulate a crash after the file was sized, but before its first header was stored.
+  fs::write(&path, [0u8; 128 + 8]).unwrap();
+
+  let mut buf = open(&path, 8).unwrap();
+  assert_eq!(buf.as_buffer().written(), 0);
+  buf.write(b"abc");
+  drop(buf);
+
+  let buf = open(&path, 8).unwrap();
+  assert_eq!(buf.as_buffer().read_to_bytes().as_ref(), b"abc");
+  drop(buf);
+  fs::remove_file(&path).unwrap();
+}
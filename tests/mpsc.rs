@@ -0,0 +1,80 @@
+#![cfg(feature = "std")]
+
+use circularbuf::{Buffer, MpscBuffer};
+use std::io::Write;
+
+#[test]
+fn from_buffer() {
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"hello world");
+
+  let buf = MpscBuffer::from(buf);
+  assert_eq!(buf.to_vec(), b" world");
+  buf.write(b"!");
+  assert_eq!(buf.to_vec(), b"world!");
+}
+
+#[test]
+fn concurrent_appends() {
+  let buf = MpscBuffer::with_capacity(256);
+
+  std::thread::scope(|s| {
+    for i in 0..8u8 {
+      let buf = buf.clone();
+      s.spawn(move || {
+        for _ in 0..256 {
+          (&buf).write_all(&[b'a' + i; 8]).unwrap();
+        }
+      });
+    }
+
+    for _ in 0..64 {
+      // Writes never tear, every 8 byte record is intact in a snapshot
+      let out = buf.to_vec();
+      for chunk in out.chunks(8) {
+        assert!(chunk.iter().all(|b| *b == chunk[0]));
+      }
+      std::thread::yield_now();
+    }
+  });
+
+  assert_eq!(buf.written(), 8 * 256 * 8);
+  let out = buf.to_vec();
+  assert_eq!(out.len(), 256);
+  for chunk in out.chunks(8) {
+    assert!(chunk.iter().all(|b| *b == chunk[0]));
+  }
+}
+
+#[test]
+fn reservations_beyond_capacity() {
+  // Many more bytes are in flight than the ring holds, and some writes do not even fit
+  let buf = MpscBuffer::with_capacity(16);
+
+  std::thread::scope(|s| {
+    for i in 0..8u8 {
+      let buf = buf.clone();
+      s.spawn(move || {
+        let record = vec![b'a' + i; if i % 2 == 0 { 8 } else { 24 }];
+        for _ in 0..1024 {
+          buf.write(&record);
+        }
+      });
+    }
+
+    for _ in 0..256 {
+      let out = buf.to_vec();
+      for chunk in out.chunks(8) {
+        assert!(chunk.iter().all(|b| *b == chunk[0]), "{out:?}");
+      }
+      std::thread::yield_now();
+    }
+  });
+
+  assert_eq!(buf.written(), 4 * 1024 * (8 + 24));
+  let out = buf.to_vec();
+  assert_eq!(out.len(), 16);
+  for chunk in out.chunks(8) {
+    assert!(chunk.iter().all(|b| *b == chunk[0]), "{out:?}");
+  }
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "std")]
+
+use circularbuf::{Buffer, BufferPool};
+
+#[test]
+fn reuse() {
+  let pool = BufferPool::new(8, 2);
+  let mut a = pool.get();
+  a.write(b"hello");
+  drop(a);
+  assert_eq!(pool.idle(), 1);
+
+  let b = pool.get();
+  assert_eq!(pool.idle(), 0);
+  assert!(b.is_empty());
+  assert_eq!(b.written(), 0);
+  assert_eq!(b.size(), 8);
+}
+
+#[test]
+fn reused_storage_is_zeroed() {
+  let pool = BufferPool::new(8, 2);
+  let mut a = pool.get();
+  a.write(b"password");
+  drop(a);
+
+  let mut b = pool.get();
+  let (grant, rest) = b.write_grant(8);
+  assert_eq!(grant, [0u8; 8]);
+  assert!(rest.is_none());
+  assert_eq!(*b.into_inner().into_inner(), [0u8; 8]);
+}
+
+#[test]
+fn max_idle() {
+  let pool = BufferPool::new(8, 2);
+  let buffers = (0..3).map(|_| pool.get()).collect::<Vec<_>>();
+  drop(buffers);
+  assert_eq!(pool.idle(), 2);
+}
+
+#[test]
+fn foreign_storage_is_not_reclaimed() {
+  let pool = BufferPool::new(8, 2);
+
+  let detached = pool.get().into_inner();
+  assert_eq!(detached.size(), 8);
+  assert_eq!(pool.idle(), 0);
+
+  let mut resized = pool.get();
+  resized.resize(16);
+  drop(resized);
+  let mut replaced = pool.get();
+  *replaced = Buffer::with_capacity(4);
+  drop(replaced);
+  assert_eq!(pool.idle(), 0);
+}
+
+#[test]
+fn across_threads() {
+  let pool = BufferPool::new(64, 4);
+  std::thread::scope(|s| {
+    for i in 0..4u8 {
+      let pool = pool.clone();
+      s.spawn(move || {
+        let mut buf = pool.get();
+        buf.write(&[i; 100]);
+        assert_eq!(buf.len(), 64);
+      });
+    }
+  });
+  assert!(pool.idle() >= 1);
+}
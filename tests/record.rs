@@ -0,0 +1,44 @@
+use circularbuf::RecordBuffer;
+
+#[test]
+fn whole_records_are_evicted() {
+  let mut buf = RecordBuffer::new([0u8; 32]);
+  for i in 0..10u8 {
+    buf.write(&[i; 5]).unwrap();
+  }
+
+  // Each record occupies 9 bytes, so only 3 fit
+  assert_eq!(buf.len(), 3);
+  let records = buf.records().map(|r| r.to_vec()).collect::<Vec<_>>();
+  assert_eq!(records, [[7; 5], [8; 5], [9; 5]]);
+}
+
+#[test]
+fn read() {
+  let mut buf = RecordBuffer::new([0u8; 16]);
+  assert_eq!(buf.read(&mut [0u8; 4]), Ok(None));
+
+  buf.write(b"").unwrap();
+  buf.write(b"abcdef").unwrap();
+  buf.write(b"xy").unwrap();
+
+  let mut dst = [0u8; 4];
+  // "" was evicted, "abcdef" does not fit
+  let err = buf.read(&mut dst).unwrap_err();
+  assert_eq!(err.required(), 6);
+  assert!(buf.discard_oldest());
+
+  assert_eq!(buf.read(&mut dst), Ok(Some(2)));
+  assert_eq!(&dst[..2], b"xy");
+  assert!(buf.is_empty());
+}
+
+#[test]
+fn too_large() {
+  let mut buf = RecordBuffer::new([0u8; 8]);
+  buf.write(b"ok").unwrap();
+
+  let err = buf.write(b"too large").unwrap_err();
+  assert_eq!((err.required(), err.capacity()), (13, 8));
+  assert_eq!(buf.oldest().unwrap(), b"ok");
+}
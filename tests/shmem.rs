@@ -0,0 +1,97 @@
+#![cfg(feature = "shmem")]
+
+use std::{
+  fs,
+  io::{Seek, SeekFrom, Write},
+  path::{Path, PathBuf},
+};
+
+use circularbuf::{ShmemBuffer, ShmemReader};
+
+// SAFETY: every test uses its own file, only written by one buffer at a time
+fn create(path: &Path, capacity: usize) -> std::io::Result<ShmemBuffer> {
+  unsafe { ShmemBuffer::create(path, capacity) }
+}
+
+// SAFETY: as above
+fn open(path: &Path) -> std::io::Result<ShmemReader> {
+  unsafe { ShmemReader::open(path) }
+}
+
+fn path(name: &str) -> PathBuf {
+  let path = std::env::temp_dir().join(format!("circularbuf-{}-{name}", std::process::id()));
+  let _ = fs::remove_file(&path);
+  path
+}
+
+#[test]
+fn survives_the_writer() {
+  let path = path("shmem");
+  let mut buf = create(&path, 8).unwrap();
+  buf.write(b"hello");
+
+  let reader = open(&path).unwrap();
+  assert_eq!(reader.snapshot().read_to_bytes().as_ref(), b"hello");
+
+  buf.write(b" world");
+  drop(buf);
+  assert_eq!(reader.written(), 11);
+  let snapshot = reader.snapshot();
+  assert_eq!(snapshot.written(), 11);
+  assert_eq!(snapshot.read_to_bytes().as_ref(), b"lo world");
+  drop(reader);
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn torn_write() {
+  let path = path("shmem-torn");
+  let mut buf = create(&path, 8).unwrap();
+  buf.write(b"abcdefgh");
+  drop(buf);
+
+  // A writer reserving 3 more bytes died before completing the write
+  let mut file = fs::OpenOptions::new().write(true).open(&path).unwrap();
+  file.seek(SeekFrom::Start(16)).unwrap();
+  file.write_all(&11u64.to_le_bytes()).unwrap();
+  drop(file);
+
+  let snapshot = open(&path).unwrap().snapshot();
+  assert_eq!(snapshot.written(), 8);
+  assert_eq!(snapshot.read_to_bytes().as_ref(), b"defgh");
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn invalid_segment() {
+  let path = path("shmem-invalid");
+  fs::write(&path, [0u8; 100]).unwrap();
+  let err = open(&path).unwrap_err();
+  assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn recreated_in_place() {
+  let path = path("shmem-recreated");
+  let mut buf = create(&path, 8).unwrap();
+  buf.write(b"hello");
+  let reader = open(&path).unwrap();
+  drop(buf);
+
+  // A restarted writer carries on in the same mapping, without the previous content
+  let mut buf = create(&path, 8).unwrap();
+  assert_eq!(buf.written(), 5);
+  assert!(reader.snapshot().is_empty());
+  buf.write(b"abc");
+  let snapshot = reader.snapshot();
+  assert_eq!(snapshot.written(), 8);
+  assert_eq!(snapshot.read_to_bytes().as_ref(), b"abc");
+  drop(buf);
+
+  let err = create(&path, 16).unwrap_err();
+  assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+  assert_eq!(reader.snapshot().read_to_bytes().as_ref(), b"abc");
+  drop(reader);
+  fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,79 @@
+use circularbuf::SlotBuffer;
+
+#[test]
+fn push_overwrites_oldest() {
+  let mut buf = SlotBuffer::<u32, [_; 3]>::new();
+  assert!(buf.is_empty());
+  assert_eq!(buf.oldest(), None);
+  assert_eq!(buf.newest(), None);
+
+  assert_eq!(buf.push(1), None);
+  assert_eq!(buf.push(2), None);
+  assert_eq!(buf.push(3), None);
+  assert!(buf.is_full());
+  assert_eq!(buf.push(4), Some(1));
+  assert_eq!(buf.push(5), Some(2));
+
+  assert_eq!(buf.len(), 3);
+  assert_eq!(buf.capacity(), 3);
+  assert_eq!(buf.oldest(), Some(&3));
+  assert_eq!(buf.newest(), Some(&5));
+  assert_eq!(buf.get(1), Some(&4));
+  assert_eq!(buf.get(3), None);
+  assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [3, 4, 5]);
+  assert_eq!(buf.iter().rev().copied().collect::<Vec<_>>(), [5, 4, 3]);
+}
+
+#[test]
+fn pop() {
+  let mut buf = SlotBuffer::<String, _>::with_capacity(2);
+  buf.push("a".to_string());
+  buf.push("b".to_string());
+  buf.push("c".to_string());
+
+  assert_eq!(buf.pop_oldest().as_deref(), Some("b"));
+  buf.push("d".to_string());
+  assert_eq!(buf.pop_newest().as_deref(), Some("d"));
+  assert_eq!(buf.pop_newest().as_deref(), Some("c"));
+  assert_eq!(buf.pop_newest(), None);
+  assert_eq!(buf.pop_oldest(), None);
+
+  buf.push("e".to_string());
+  buf.push("f".to_string());
+  assert_eq!(buf.iter().collect::<Vec<_>>(), ["e", "f"]);
+  buf.clear();
+  assert!(buf.is_empty());
+  assert_eq!(buf.iter().count(), 0);
+}
+
+#[test]
+fn drops_items() {
+  use std::rc::Rc;
+
+  let item = Rc::new(());
+  let mut buf = SlotBuffer::<_, [_; 2]>::new();
+  for _ in 0..5 {
+    buf.push(item.clone());
+  }
+  assert_eq!(Rc::strong_count(&item), 3);
+  drop(buf);
+  assert_eq!(Rc::strong_count(&item), 1);
+}
+
+#[test]
+fn stale_slots_are_ignored() {
+  let mut buf = SlotBuffer::from_slots([Some(7), Some(8)]);
+  assert!(buf.is_empty());
+  assert_eq!(buf.push(1), None);
+  assert_eq!(buf.iter().copied().collect::<Vec<_>>(), [1]);
+  assert_eq!(buf.push(2), None);
+  assert_eq!(buf.push(3), Some(1));
+}
+
+#[test]
+fn zero_capacity() {
+  let mut buf = SlotBuffer::<u8, [_; 0]>::new();
+  assert_eq!(buf.push(1), Some(1));
+  assert!(buf.is_empty());
+  assert!(buf.is_full());
+}
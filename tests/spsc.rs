@@ -0,0 +1,117 @@
+#![cfg(feature = "std")]
+
+use circularbuf::Buffer;
+
+#[test]
+fn carries_over_unread() {
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"hello world");
+  buf.read(&mut [0u8; 2]);
+
+  let (mut tx, mut rx) = buf.split();
+  assert_eq!(rx.unread(), 4);
+  assert_eq!(tx.write(b"abc"), 2);
+
+  let mut dst = [0u8; 8];
+  assert_eq!(rx.read(&mut dst), 6);
+  assert_eq!(&dst[..6], b"orldab");
+}
+
+#[test]
+fn overwriting() {
+  let (mut tx, mut rx) = Buffer::new([0u8; 4]).split_overwriting();
+  assert_eq!(tx.write(b"hello"), 5);
+  assert_eq!(tx.write(b"!"), 1);
+
+  let mut dst = [0u8; 8];
+  assert_eq!(rx.read(&mut dst), 4);
+  assert_eq!(&dst[..4], b"llo!");
+  assert_eq!(rx.read(&mut dst), 0);
+}
+
+#[test]
+fn threaded() {
+  const TOTAL: usize = 1 << 16;
+
+  let (mut tx, mut rx) = Buffer::new([0u8; 64]).split();
+  std::thread::scope(|s| {
+    s.spawn(move || {
+      let mut sent = 0;
+      while sent < TOTAL {
+        let chunk = (sent..TOTAL).take(7).map(|i| i as u8).collect::<Vec<_>>();
+        match tx.write(&chunk) {
+          0 => std::thread::yield_now(),
+          n => sent += n,
+        }
+      }
+    });
+
+    let mut received = 0;
+    let mut dst = [0u8; 13];
+    while received < TOTAL {
+      let n = rx.read(&mut dst);
+      if n == 0 {
+        std::thread::yield_now();
+      }
+      for (i, b) in dst[..n].iter().enumerate() {
+        assert_eq!(*b, (received + i) as u8);
+      }
+      received += n;
+    }
+  });
+}
+
+#[test]
+fn threaded_overwriting() {
+  let (mut tx, mut rx) = Buffer::new([0u8; 64]).split_overwriting();
+  std::thread::scope(|s| {
+    s.spawn(move || {
+      for i in 0..1 << 16 {
+        // Every byte of a chunk carries its absolute position modulo 251.
+        tx.write(&[(i % 251) as u8]);
+      }
+    });
+
+    let mut dst = [0u8; 16];
+    for _ in 0..1 << 12 {
+      let n = rx.read(&mut dst);
+      // Whatever is returned is contiguous in the stream, never torn.
+      for w in dst[..n].windows(2) {
+        assert_eq!(w[1], ((w[0] as usize + 1) % 251) as u8);
+      }
+    }
+  });
+}
+
+#[test]
+fn snapshot() {
+  let (mut tx, mut rx) = Buffer::new([0u8; 8]).split();
+  let observer = rx.observer();
+  assert!(observer.to_vec().is_empty());
+
+  tx.write(b"hello");
+  rx.read(&mut [0u8; 2]);
+  assert_eq!(observer.to_vec(), b"llo");
+  assert_eq!(rx.unread(), 3);
+}
+
+#[test]
+fn threaded_snapshot() {
+  let (mut tx, _rx) = Buffer::new([0u8; 64]).split_overwriting();
+  let observer = tx.observer();
+  std::thread::scope(|s| {
+    s.spawn(move || {
+      for i in 0..1 << 16 {
+        tx.write(&[(i % 251) as u8]);
+      }
+    });
+
+    for _ in 0..1 << 10 {
+      let out = observer.to_vec();
+      for w in out.windows(2) {
+        assert_eq!(w[1], ((w[0] as usize + 1) % 251) as u8);
+      }
+      std::thread::yield_now();
+    }
+  });
+}
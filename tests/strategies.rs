@@ -0,0 +1,32 @@
+#![cfg(feature = "proptest")]
+
+use circularbuf::{Buffer, strategies};
+use proptest::prelude::*;
+
+proptest! {
+  #[test]
+  fn differential(
+    (mut buf, mut model) in strategies::buffer_with_model(32),
+    ops in strategies::ops(48, 32),
+  ) {
+    prop_assert!(model.matches(&buf));
+    for op in &ops {
+      prop_assert_eq!(op.apply(&mut buf), model.apply(op));
+      prop_assert!(model.matches(&buf));
+    }
+  }
+
+  #[test]
+  fn logical_views(buf in strategies::buffer(32)) {
+    let contents = buf.read_to_bytes().into_owned();
+    prop_assert_eq!(buf.iter::<u8>().collect::<Vec<_>>(), contents.clone());
+    let mut fresh = Buffer::new(vec![0u8; contents.len()]);
+    fresh.write(&contents);
+    prop_assert_eq!(fresh.content_hash(), buf.content_hash());
+    prop_assert_eq!(&fresh, &buf);
+
+    let mut copy = buf.clone();
+    prop_assert_eq!(copy.make_contiguous(), &contents[..]);
+    prop_assert_eq!(copy, buf);
+  }
+}
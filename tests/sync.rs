@@ -0,0 +1,110 @@
+#![cfg(feature = "std")]
+
+use circularbuf::SyncBuffer;
+use std::io::Write;
+
+#[test]
+fn concurrent_writes() {
+  let buf = SyncBuffer::new(vec![0u8; 1024]);
+
+  std::thread::scope(|s| {
+    for i in 0..8u8 {
+      let buf = buf.clone();
+      s.spawn(move || {
+        for _ in 0..16 {
+          (&buf).write_all(&[b'a' + i; 4]).unwrap();
+        }
+      });
+    }
+  });
+
+  assert_eq!(buf.written(), 8 * 16 * 4);
+
+  // Every write is applied atomically
+  let out = buf.to_vec();
+  assert_eq!(out.len(), 512);
+  for chunk in out.chunks(4) {
+    assert!(chunk.iter().all(|b| *b == chunk[0]));
+  }
+}
+
+#[test]
+fn read() {
+  let buf = SyncBuffer::new([0u8; 4]);
+  buf.write(b"hello");
+
+  let mut dst = [0u8; 4];
+  assert_eq!(buf.read(&mut dst), 4);
+  assert_eq!(&dst, b"ello");
+  assert_eq!(buf.lock().unread(), 0);
+}
+
+#[tokio::test]
+#[cfg(feature = "tokio")]
+async fn wait_for_data() {
+  let buf = SyncBuffer::new([0u8; 8]);
+
+  let writer = buf.clone();
+  let handle = tokio::spawn(async move {
+    for chunk in [&b"ab"[..], b"cd", b"efghij"] {
+      tokio::task::yield_now().await;
+      writer.write(chunk);
+    }
+  });
+
+  assert!(buf.wait_for_data().await >= 2);
+  // Capped to the size of the buffer
+  assert_eq!(buf.wait_for(100).await, 8);
+  handle.await.unwrap();
+}
+
+#[tokio::test]
+#[cfg(all(feature = "future", feature = "bytes"))]
+async fn follow() {
+  use futures_util::StreamExt;
+
+  let buf = SyncBuffer::new([0u8; 8]);
+  buf.write(b"old");
+
+  let mut follow = buf.follow();
+  let writer = buf.clone();
+  let handle = tokio::spawn(async move {
+    tokio::task::yield_now().await;
+    writer.write(b"new");
+  });
+  assert_eq!(follow.next().await.unwrap().unwrap(), &b"new"[..]);
+  handle.await.unwrap();
+
+  // Falling behind is reported, then the stream resumes from the oldest retained byte
+  buf.write(b"0123456789");
+  assert_eq!(follow.next().await.unwrap().unwrap_err().missed(), 2);
+  assert_eq!(follow.next().await.unwrap().unwrap(), &b"23456789"[..]);
+}
+
+#[test]
+fn subscribe() {
+  let buf = SyncBuffer::new([0u8; 8]);
+  buf.write(b"old");
+
+  let mut a = buf.subscribe();
+  let mut dst = [0u8; 8];
+  assert_eq!(a.read(&mut dst), Ok(0));
+
+  buf.write(b"abc");
+  let mut b = buf.subscribe();
+  buf.write(b"de");
+
+  assert_eq!(a.read(&mut dst), Ok(5));
+  assert_eq!(&dst[..5], b"abcde");
+  assert_eq!(b.read(&mut dst), Ok(2));
+  assert_eq!(&dst[..2], b"de");
+
+  // b falls behind by more than the buffer size
+  buf.write(b"0123456789");
+  assert_eq!(a.read(&mut dst).unwrap_err().missed(), 2);
+  assert_eq!(a.read(&mut dst), Ok(8));
+  assert_eq!(&dst, b"23456789");
+  assert_eq!(b.read(&mut dst[..4]).unwrap_err().missed(), 2);
+  assert_eq!(b.read(&mut dst[..4]), Ok(4));
+  assert_eq!(&dst[..4], b"2345");
+}
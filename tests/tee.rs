@@ -0,0 +1,76 @@
+#![cfg(feature = "std")]
+
+use std::io::{self, Write};
+
+use circularbuf::{Buffer, TeeWriter};
+
+/// A writer accepting at most `limit` bytes per call.
+struct Short {
+  out: Vec<u8>,
+  limit: usize,
+}
+
+impl Write for Short {
+  fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    let n = buf.len().min(self.limit);
+    self.out.extend_from_slice(&buf[..n]);
+    Ok(n)
+  }
+
+  fn flush(&mut self) -> io::Result<()> {
+    Ok(())
+  }
+}
+
+#[test]
+fn retains_accepted_bytes() {
+  let inner = Short {
+    out: Vec::new(),
+    limit: 3,
+  };
+  let mut tee = TeeWriter::new(inner, Buffer::new([0u8; 4]));
+
+  assert_eq!(tee.write(b"hello").unwrap(), 3);
+  assert_eq!(tee.as_buffer().read_to_bytes().as_ref(), b"hel");
+
+  tee.write_all(b"lo world").unwrap();
+  tee.flush().unwrap();
+  assert_eq!(tee.get_ref().out, b"hello world");
+  assert_eq!(tee.as_buffer().read_to_bytes().as_ref(), b"orld");
+  assert_eq!(tee.as_buffer().written(), 11);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(flavor = "current_thread")]
+async fn tokio_write() {
+  use tokio::io::AsyncWriteExt;
+
+  let mut tee = TeeWriter::new(Vec::new(), Buffer::new([0u8; 5]));
+  AsyncWriteExt::write_all(&mut tee, b"hello world")
+    .await
+    .unwrap();
+  tee.shutdown().await.unwrap();
+
+  let (out, buf) = tee.into_parts();
+  assert_eq!(out, b"hello world");
+  assert_eq!(buf.read_to_bytes().as_ref(), b"world");
+}
+
+#[cfg(feature = "futures-io")]
+#[tokio::test(flavor = "current_thread")]
+async fn futures_write() {
+  use futures_util::AsyncWriteExt;
+
+  let mut tee = TeeWriter::new(
+    futures_util::io::Cursor::new(Vec::new()),
+    Buffer::new([0u8; 5]),
+  );
+  AsyncWriteExt::write_all(&mut tee, b"hello world")
+    .await
+    .unwrap();
+  AsyncWriteExt::close(&mut tee).await.unwrap();
+
+  let (out, buf) = tee.into_parts();
+  assert_eq!(out.into_inner(), b"hello world");
+  assert_eq!(buf.read_to_bytes().as_ref(), b"world");
+}
@@ -1,4 +1,4 @@
-use circularbuf::Buffer;
+use circularbuf::{Buffer, InlineBuffer};
 
 #[test]
 fn api() {
@@ -8,6 +8,27 @@ fn api() {
   assert_eq!([0u8], buf.into_inner());
 }
 
+#[test]
+fn inline() {
+  const EMPTY: InlineBuffer<4> = InlineBuffer::new_zeroed();
+
+  let mut buf = EMPTY;
+  assert_eq!(buf.size(), 4);
+  assert_eq!(buf.read_hint(), 0);
+
+  buf.write(b"hello");
+  assert_eq!(buf.read_to_bytes().as_ref(), b"ello");
+}
+
+#[test]
+fn with_capacity() {
+  let mut buf = Buffer::with_capacity(4);
+  assert_eq!(buf.size(), 4);
+
+  buf.write(b"hello");
+  assert_eq!(buf.read_to_bytes().as_ref(), b"ello");
+}
+
 #[test]
 fn short_write() {
   let mut buf = Buffer::new([0u8; 1024]);
@@ -159,6 +180,426 @@ fn reset() {
   assert_eq!(n, expect.len());
 }
 
+#[test]
+fn try_read_into() {
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"hello world");
+
+  let mut small = [0u8; 4];
+  let err = buf.try_read_into(&mut small).unwrap_err();
+  assert_eq!(err.required(), 6);
+  assert_eq!(err.available(), 4);
+  assert_eq!(small, [0; 4]);
+
+  let mut dst = [0u8; 8];
+  assert_eq!(buf.try_read_into(&mut dst), Ok(6));
+  assert_eq!(&dst[..6], b" world");
+}
+
+#[test]
+fn read_at() {
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"xxxx");
+  buf.write(b"header:body");
+
+  let mut dst = [0u8; 6];
+  assert_eq!(buf.read_at(0, &mut dst), 6);
+  assert_eq!(&dst, b"der:bo");
+
+  // Spans the wrap point
+  let mut dst = [0u8; 3];
+  assert_eq!(buf.read_at(3, &mut dst), 3);
+  assert_eq!(&dst, b":bo");
+
+  let mut dst = [0u8; 8];
+  assert_eq!(buf.read_at(5, &mut dst), 3);
+  assert_eq!(&dst[..3], b"ody");
+  assert_eq!(buf.read_at(8, &mut dst), 0);
+  assert_eq!(buf.read_at(100, &mut dst), 0);
+  assert_eq!(buf.unread(), 8);
+}
+
+#[test]
+fn tail() {
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"xxxx");
+  buf.write(b"header:body");
+
+  let mut dst = [0u8; 16];
+  assert_eq!(buf.tail(6, &mut dst), 6);
+  assert_eq!(&dst[..6], b"r:body");
+  assert_eq!(buf.tail(100, &mut dst), 8);
+  assert_eq!(&dst[..8], b"der:body");
+
+  let mut small = [0u8; 2];
+  assert_eq!(buf.tail(6, &mut small), 2);
+  assert_eq!(&small, b"dy");
+
+  assert_eq!(buf.tail_vec(5), b":body");
+  assert_eq!(buf.tail_vec(0), b"");
+}
+
+#[test]
+fn head() {
+  let mut buf = Buffer::new([0u8; 8]);
+  assert_eq!(buf.head(4), (&[][..], &[][..]));
+
+  buf.write(b"xxxx");
+  buf.write(b"header:body");
+  assert_eq!(buf.head(2), (&b"de"[..], &[][..]));
+  assert_eq!(buf.head(6), (&b"der:"[..], &b"bo"[..]));
+  assert_eq!(buf.head(100), (&b"der:"[..], &b"body"[..]));
+}
+
+#[test]
+fn write_with_evict() {
+  let mut buf = Buffer::new([0u8; 4]);
+  let mut evicted = Vec::new();
+
+  buf.write_with_evict(b"abc", |e| evicted.push(e.to_vec()));
+  assert!(evicted.is_empty());
+
+  buf.write_with_evict(b"de", |e| evicted.push(e.to_vec()));
+  assert_eq!(evicted, [b"a".to_vec()]);
+
+  // Evicts across the wrap point
+  evicted.clear();
+  buf.write_with_evict(b"fghi", |e| evicted.push(e.to_vec()));
+  assert_eq!(evicted, [b"bcd".to_vec(), b"e".to_vec()]);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"fghi");
+
+  // Oversized writes also report the skipped part of the input
+  evicted.clear();
+  buf.write_with_evict(b"123456", |e| evicted.push(e.to_vec()));
+  assert_eq!(evicted, [b"fgh".to_vec(), b"i".to_vec(), b"12".to_vec()]);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"3456");
+
+  // Consumed data is never reported
+  evicted.clear();
+  buf.read(&mut [0u8; 2]);
+  buf.write_with_evict(b"789", |e| evicted.push(e.to_vec()));
+  assert_eq!(evicted, [b"5".to_vec()]);
+}
+
+#[test]
+fn write_with_report() {
+  let mut buf = Buffer::new([0u8; 4]);
+
+  let report = buf.write_with_report(b"abc");
+  assert_eq!((report.written(), report.overwritten()), (3, 0));
+
+  let report = buf.write_with_report(b"defgh");
+  assert_eq!((report.written(), report.overwritten()), (5, 3));
+
+  // Consumed data is not counted as lost
+  buf.read(&mut [0u8; 4]);
+  let report = buf.write_with_report(b"ij");
+  assert_eq!((report.written(), report.overwritten()), (2, 0));
+}
+
+#[test]
+fn stats() {
+  let mut buf = Buffer::new([0u8; 4]);
+  buf.write(b"ab");
+  buf.write(b"cd");
+  buf.write_vectored(&[b"e", b"fghij"]);
+  buf.read(&mut [0u8; 2]);
+  buf.extend(b"k");
+
+  let stats = buf.stats();
+  assert_eq!(stats.writes(), 4);
+  assert_eq!(stats.written(), 11);
+  // "abcd" were overwritten by the vectored write, nothing unread was lost by "k"
+  assert_eq!(stats.overwritten(), 4);
+  assert_eq!(stats.wraps(), 2);
+  assert_eq!(stats.largest_write(), 6);
+
+  buf.reset();
+  assert_eq!(buf.stats(), Default::default());
+}
+
+#[test]
+fn checkpoint() {
+  use circularbuf::Checkpoint;
+
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"hello world");
+  buf.read(&mut [0u8; 2]);
+
+  let checkpoint = Checkpoint::from_bytes(buf.checkpoint().as_bytes()).unwrap();
+  assert_eq!(checkpoint.data(), b"orld");
+
+  // Same size: identical state, including the cursor
+  let mut restored = Buffer::restore([0u8; 6], &checkpoint).unwrap();
+  assert_eq!(restored.written(), 11);
+  restored.write(b"!!");
+  buf.write(b"!!");
+  assert_eq!(restored.as_slices(), buf.as_slices());
+
+  // The consumed bytes are not part of the checkpoint, the history starts at the unread ones
+  let restored = Buffer::restore([b'X'; 6], &checkpoint).unwrap();
+  let mut dst = [0u8; 8];
+  let gap = restored.read_since(0, &mut dst).unwrap_err();
+  assert_eq!((gap.missed(), gap.oldest()), (7, 7));
+  assert_eq!(restored.read_since(7, &mut dst), Ok(4));
+  assert_eq!(&dst[..4], b"orld");
+
+  // Other sizes keep the unread bytes
+  let restored = Buffer::restore(vec![0u8; 16], &checkpoint).unwrap();
+  assert_eq!(restored.read_to_bytes().as_ref(), b"orld");
+  let err = Buffer::restore([0u8; 3], &checkpoint).unwrap_err();
+  assert_eq!((err.required(), err.capacity()), (4, 3));
+
+  let bytes = checkpoint.into_bytes();
+  assert!(Checkpoint::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+  assert!(Checkpoint::from_bytes(&[]).is_none());
+}
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn rkyv() {
+  use circularbuf::ArchivedBuffer;
+  use rkyv::rancor::Error;
+
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"hello world");
+  buf.read(&mut [0u8; 2]);
+
+  let bytes = rkyv::to_bytes::<Error>(&buf).unwrap();
+  let archived = rkyv::access::<ArchivedBuffer<[u8; 8]>, Error>(&bytes).unwrap();
+  assert_eq!(archived.as_slices(), Some((&b" world"[..], &[][..])));
+  assert_eq!((archived.unread(), archived.written()), (6, 11));
+
+  let mut restored = rkyv::deserialize::<Buffer<[u8; 8]>, Error>(archived).unwrap();
+  assert_eq!(restored, buf);
+  assert_eq!(restored.stats(), buf.stats());
+  restored.write(b"!");
+  assert_eq!(restored.read_to_bytes().as_ref(), b" world!");
+
+  // The archive is the 8 bytes of storage followed by the cursor, written, unread and
+  // retained counts, and cursors pointing outside of the storage fail the validation
+  let cursor = size_of::<rkyv::primitive::ArchivedUsize>();
+  assert_eq!(bytes.len(), 8 + 8 * cursor);
+  for (field, value) in [(0, 8), (0, 100), (2, 9), (3, 9), (1, 5)] {
+    let mut bytes = bytes.clone();
+    let at = 8 + field * cursor;
+    bytes[at] = value;
+    assert!(
+      rkyv::access::<ArchivedBuffer<[u8; 8]>, Error>(&bytes).is_err(),
+      "{field} = {value}"
+    );
+  }
+}
+
+#[cfg(feature = "postcard")]
+#[test]
+fn postcard() {
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"hello");
+  buf.write(b" world");
+  buf.read(&mut [0u8; 1]);
+
+  let mut out = [0u8; 32];
+  let bytes = buf.to_postcard(&mut out).unwrap();
+  let mut restored = Buffer::<[u8; 8]>::from_postcard(bytes).unwrap();
+  assert_eq!(restored, buf);
+  assert_eq!(restored.stats(), buf.stats());
+
+  restored.write(b"!");
+  buf.write(b"!");
+  assert_eq!(restored.as_slices(), buf.as_slices());
+
+  // Slices can be borrowed straight from the serialized bytes
+  let mut buf = Buffer::new(vec![0u8; 8]);
+  buf.write(b"hello world");
+  let bytes = buf.to_postcard(&mut out).unwrap();
+  let borrowed = Buffer::<&[u8]>::from_postcard(bytes).unwrap();
+  assert_eq!(borrowed.read_to_bytes().as_ref(), b"lo world");
+
+  assert!(buf.to_postcard(&mut [0u8; 4]).is_err());
+
+  // Inline storages larger than serde's array implementations work too
+  let mut buf = InlineBuffer::<64>::new_zeroed();
+  buf.write(&[7u8; 100]);
+  let mut out = [0u8; 128];
+  let bytes = buf.to_postcard(&mut out).unwrap();
+  let restored = InlineBuffer::<64>::from_postcard(bytes).unwrap();
+  assert_eq!(restored, buf);
+  assert!(Buffer::<[u8; 32]>::from_postcard(bytes).is_err());
+
+  // Cursors pointing outside of the storage are rejected, rather than panicking later:
+  // the length and the 8 bytes of storage are followed by the cursor, written, unread
+  // and retained counts
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"hello");
+  let bytes = buf.to_postcard(&mut out).unwrap().to_vec();
+  assert_eq!(bytes[..9], *b"\x08hello\0\0\0");
+  assert_eq!(bytes[9..13], [5, 5, 5, 5]);
+  for (i, value) in [(9, 8), (9, 100), (11, 9), (11, 6), (12, 9), (10, 4), (0, 7)] {
+    let mut bytes = bytes.clone();
+    bytes[i] = value;
+    assert!(
+      Buffer::<[u8; 8]>::from_postcard(&bytes).is_err(),
+      "{i} = {value}"
+    );
+  }
+}
+
+#[test]
+fn try_write() {
+  let mut buf = Buffer::new([0u8; 4]);
+  assert_eq!(buf.try_write(b"abc"), Ok(3));
+
+  let err = buf.try_write(b"def").unwrap_err();
+  assert_eq!((err.written(), err.rejected()), (1, 2));
+  assert_eq!(buf.try_write(b"x").unwrap_err().written(), 0);
+
+  // Consumed data frees room again
+  let mut dst = [0u8; 2];
+  buf.read(&mut dst);
+  assert_eq!(&dst, b"ab");
+  assert_eq!(buf.try_write(b"ef"), Ok(2));
+  assert_eq!(buf.read_to_bytes().as_ref(), b"cdef");
+  assert_eq!(buf.try_write(b""), Ok(0));
+}
+
+#[test]
+fn read() {
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"hello");
+  assert_eq!(buf.unread(), 5);
+
+  let mut dst = [0u8; 3];
+  assert_eq!(buf.read(&mut dst), 3);
+  assert_eq!(&dst, b"hel");
+  assert_eq!(buf.unread(), 2);
+
+  // Wraps around the end of the ring
+  buf.write(b" world");
+  assert_eq!(buf.unread(), 8);
+
+  let mut dst = [0u8; 16];
+  assert_eq!(buf.read(&mut dst), 8);
+  assert_eq!(&dst[..8], b"lo world");
+  assert_eq!(buf.unread(), 0);
+  assert_eq!(buf.read(&mut dst), 0);
+
+  // Overwritten bytes are never returned
+  buf.write(b"0123456789");
+  assert_eq!(buf.read(&mut dst), 8);
+  assert_eq!(&dst[..8], b"23456789");
+}
+
+#[test]
+fn iter() {
+  let mut buf = Buffer::new([0u8; 6]);
+  assert_eq!(buf.iter().count(), 0);
+
+  buf.write(b"hello");
+  buf.write(b" world");
+  assert_eq!(buf.iter().collect::<Vec<_>>(), b" world");
+  assert_eq!(buf.iter().rev().collect::<Vec<_>>(), b"dlrow ");
+}
+
+#[test]
+#[cfg(feature = "bytes")]
+fn bytes_shared() {
+  let mut buf = Buffer::new(bytes::BytesMut::zeroed(6));
+  buf.write(b"hello");
+  buf.write(b" world");
+
+  let out = buf.read_to_bytes_shared();
+  assert_eq!(out, b" world"[..]);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde() {
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"hello");
+  buf.write(b" world");
+  buf.read(&mut [0u8; 2]);
+
+  let json = serde_json::to_string(&buf).unwrap();
+  let mut restored: Buffer<[u8; 6]> = serde_json::from_str(&json).unwrap();
+  assert_eq!(restored, buf);
+  assert_eq!(restored.read_to_bytes().as_ref(), b"orld");
+
+  restored.write(b"!");
+  assert_eq!(restored.read_to_bytes().as_ref(), b"orld!");
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde_human_readable() {
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"hello");
+  buf.write(b" world");
+  buf.read(&mut [0u8; 1]);
+
+  let json = serde_json::to_value(buf).unwrap();
+  assert_eq!(json["capacity"], 6);
+  assert_eq!(json["written"], 11);
+  assert_eq!(json["contents"], "d29ybGQ=");
+  // The written count is only carried once, by the buffer itself
+  assert_eq!(json["stats"]["writes"], 2);
+  assert!(json["stats"].get("written").is_none());
+
+  let restored: Buffer<Box<[u8]>> =
+    serde_json::from_str(r#"{"capacity":6,"written":11,"contents":"d29ybGQ"}"#).unwrap();
+  assert_eq!(restored.read_to_bytes().as_ref(), b"world");
+  assert_eq!(restored.size(), 6);
+
+  // Only the unread bytes are serialized, the history starts at them
+  let mut dst = [0u8; 8];
+  let gap = restored.read_since(0, &mut dst).unwrap_err();
+  assert_eq!((gap.missed(), gap.oldest()), (6, 6));
+  assert_eq!(restored.read_since(6, &mut dst), Ok(5));
+  assert_eq!(&dst[..5], b"world");
+
+  for json in [
+    r#"{"capacity":4,"written":11,"contents":"d29ybGQ="}"#,
+    r#"{"capacity":6,"written":11,"contents":"d29y!GQ="}"#,
+    r#"{"capacity":6,"written":4,"contents":"d29ybGQ="}"#,
+    r#"{"capacity":1000000000000,"written":0,"contents":""}"#,
+  ] {
+    assert!(serde_json::from_str::<Buffer<[u8; 6]>>(json).is_err());
+  }
+
+  // A capacity from the input which cannot be allocated is an error, not an abort
+  for capacity in [1_000_000_000_000u64, u64::MAX] {
+    let json = format!(r#"{{"capacity":{capacity},"written":0,"contents":""}}"#);
+    let err = serde_json::from_str::<Buffer<Box<[u8]>>>(&json).unwrap_err();
+    assert!(err.to_string().contains("can be allocated"), "{err}");
+  }
+  assert!(
+    serde_json::from_str::<Buffer<Vec<u8>>>(r#"{"capacity":2,"written":5,"contents":"d29ybGQ="}"#)
+      .is_err()
+  );
+}
+
+#[test]
+fn generic_element() {
+  #[derive(Debug, Copy, Clone, PartialEq)]
+  struct Sample(u32);
+
+  let mut buf = Buffer::new([Sample(0); 3]);
+  assert_eq!(buf.size(), 3);
+
+  for i in 1..=5 {
+    buf.write(&[Sample(i)]);
+  }
+  assert_eq!(buf.written(), 5);
+
+  let mut out = [Sample(0); 3];
+  assert_eq!(buf.read_into(&mut out), 3);
+  assert_eq!(out, [Sample(3), Sample(4), Sample(5)]);
+
+  let mut buf = Buffer::new(vec![0i16; 4]);
+  buf.write(&[-1, -2, -3, -4, -5]);
+  assert_eq!(buf.read_to_bytes().as_ref(), [-2, -3, -4, -5]);
+}
+
 #[test]
 #[cfg(feature = "std")]
 fn io_write() {
@@ -174,6 +615,48 @@ fn io_write() {
   assert_eq!(out.as_ref(), inp);
 }
 
+#[test]
+#[cfg(feature = "std")]
+fn io_read() {
+  use std::io::Read;
+
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"hello world");
+
+  let mut out = Vec::new();
+  buf.read_to_end(&mut out).unwrap();
+  assert_eq!(out, b" world");
+  assert_eq!(buf.unread(), 0);
+
+  buf.write(b"foo");
+  let mut out = Vec::new();
+  std::io::copy(&mut buf, &mut out).unwrap();
+  assert_eq!(out, b"foo");
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn io_buf_read() {
+  use std::io::BufRead;
+
+  let mut buf = Buffer::new([0u8; 12]);
+  buf.write(b"drop\n");
+  buf.write(b"first\nsecond\n");
+
+  // The wrapped case yields the older segment first, then the newer one.
+  assert_eq!(buf.fill_buf().unwrap(), b"irst\nse");
+  buf.consume(5);
+  assert_eq!(buf.fill_buf().unwrap(), b"se");
+  buf.consume(2);
+  assert_eq!(buf.fill_buf().unwrap(), b"cond\n");
+  buf.consume(5);
+  assert!(buf.fill_buf().unwrap().is_empty());
+
+  buf.write(b"a\nb\nc");
+  let lines = BufRead::lines(buf).collect::<Result<Vec<_>, _>>().unwrap();
+  assert_eq!(lines, ["a", "b", "c"]);
+}
+
 #[tokio::test]
 #[cfg(feature = "tokio")]
 async fn tokio_io_write() {
@@ -190,6 +673,44 @@ async fn tokio_io_write() {
   assert_eq!(out.as_ref(), inp);
 }
 
+#[tokio::test]
+#[cfg(feature = "tokio")]
+async fn tokio_io_write_vectored() {
+  use std::io::IoSlice;
+  use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+  let mut buf = Buffer::new([0u8; 4]);
+  assert!(buf.is_write_vectored());
+
+  let bufs = [IoSlice::new(b"hello"), IoSlice::new(b" world")];
+  assert_eq!(
+    AsyncWriteExt::write_vectored(&mut buf, &bufs)
+      .await
+      .unwrap(),
+    11
+  );
+  assert_eq!(buf.read_to_bytes().as_ref(), b"orld");
+}
+
+#[tokio::test]
+#[cfg(feature = "tokio")]
+async fn tokio_io_read() {
+  use tokio::io::AsyncReadExt;
+
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"hello");
+  buf.write(b" world");
+
+  let mut out = Vec::new();
+  buf.read_to_end(&mut out).await.unwrap();
+  assert_eq!(out, b" world");
+
+  buf.write(b"tail");
+  let mut out = Vec::new();
+  tokio::io::copy(&mut buf, &mut out).await.unwrap();
+  assert_eq!(out, b"tail");
+}
+
 #[tokio::test]
 #[cfg(feature = "future")]
 async fn futures_io_write() {
@@ -205,3 +726,1119 @@ async fn futures_io_write() {
   let out = buf.read_to_bytes();
   assert_eq!(out.as_ref(), inp);
 }
+
+#[tokio::test]
+#[cfg(feature = "future")]
+async fn futures_io_write_vectored() {
+  use futures_util::AsyncWriteExt;
+  use std::io::IoSlice;
+
+  let mut buf = Buffer::new([0u8; 4]);
+  let bufs = [IoSlice::new(b"hello"), IoSlice::new(b" world")];
+  assert_eq!(
+    AsyncWriteExt::write_vectored(&mut buf, &bufs)
+      .await
+      .unwrap(),
+    11
+  );
+  assert_eq!(buf.read_to_bytes().as_ref(), b"orld");
+}
+
+#[tokio::test]
+#[cfg(feature = "future")]
+async fn futures_io_read() {
+  use futures_util::AsyncReadExt;
+
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"hello");
+  buf.write(b" world");
+
+  let mut out = Vec::new();
+  buf.read_to_end(&mut out).await.unwrap();
+  assert_eq!(out, b" world");
+}
+
+#[tokio::test]
+#[cfg(all(feature = "future", feature = "bytes"))]
+async fn futures_sink() {
+  use futures_util::{SinkExt, StreamExt, stream};
+
+  let mut buf = Buffer::new([0u8; 8]);
+  let chunks = ["hello ", "circular ", "world"].map(|s| bytes::Bytes::from_static(s.as_bytes()));
+  stream::iter(chunks)
+    .map(Ok)
+    .forward(&mut buf)
+    .await
+    .unwrap();
+
+  assert_eq!(buf.written(), 20);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"ar world");
+
+  buf.send(b"!").await.unwrap();
+  assert_eq!(buf.read_to_bytes().as_ref(), b"r world!");
+}
+
+#[test]
+#[cfg(feature = "embedded-io")]
+fn embedded_io() {
+  use embedded_io::{Read, Write};
+
+  fn log(w: &mut impl Write, msg: &[u8]) {
+    w.write_all(msg).unwrap();
+    w.flush().unwrap();
+  }
+
+  fn drain(r: &mut impl Read, dst: &mut [u8]) -> usize {
+    r.read(dst).unwrap()
+  }
+
+  let mut buf = Buffer::new([0u8; 6]);
+  log(&mut buf, b"hello world");
+
+  let mut dst = [0u8; 8];
+  assert_eq!(drain(&mut buf, &mut dst), 6);
+  assert_eq!(&dst[..6], b" world");
+}
+
+#[tokio::test]
+#[cfg(feature = "embedded-io-async")]
+async fn embedded_io_async() {
+  use embedded_io_async::{Read, Write};
+
+  let mut buf = Buffer::new([0u8; 6]);
+  Write::write_all(&mut buf, b"hello world").await.unwrap();
+  Write::flush(&mut buf).await.unwrap();
+
+  let mut dst = [0u8; 8];
+  assert_eq!(Read::read(&mut buf, &mut dst).await.unwrap(), 6);
+  assert_eq!(&dst[..6], b" world");
+}
+
+#[test]
+fn hexdump() {
+  let mut buf = Buffer::new([0u8; 16]);
+  assert_eq!(buf.hexdump().to_string(), "");
+
+  buf.write(&[0xff; 8]);
+  buf.write(b"abcdefghijklmnop");
+  assert_eq!(
+    buf.hexdump().to_string(),
+    "00000000  61 62 63 64 65 66 67 68  69 6a 6b 6c 6d 6e 6f 70  |abcdefghijklmnop|\n"
+  );
+}
+
+#[test]
+fn write_vectored() {
+  use std::io::{IoSlice, Write};
+
+  let mut buf = Buffer::new([0u8; 4]);
+  buf.write(b"xy");
+  assert_eq!(buf.write_vectored(&[b"a", b"", b"bc"]), 3);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"yabc");
+  assert_eq!((buf.written(), buf.unread()), (5, 4));
+
+  let bufs = [
+    IoSlice::new(b"0123"),
+    IoSlice::new(b"45"),
+    IoSlice::new(b"6"),
+  ];
+  assert_eq!(Write::write_vectored(&mut buf, &bufs).unwrap(), 7);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"3456");
+  assert_eq!(buf.written(), 12);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn read_from() {
+  use std::io::{self, Read};
+
+  struct Flaky<'a>(&'a [u8], bool);
+
+  impl Read for Flaky<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+      self.1 = !self.1;
+      if self.1 {
+        return Err(io::ErrorKind::Interrupted.into());
+      }
+      self.0.read(buf)
+    }
+  }
+
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"abcde");
+  let mut r = Flaky(b"0123456789", false);
+  // Fills the 3 bytes after the cursor, then the 5 bytes before it
+  assert_eq!(buf.read_from(&mut r).unwrap(), 8);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"01234567");
+  assert_eq!(buf.read_from(&mut r).unwrap(), 2);
+  assert_eq!(buf.read_from(&mut r).unwrap(), 0);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"23456789");
+  assert_eq!(buf.written(), 15);
+}
+
+#[tokio::test]
+#[cfg(all(feature = "tokio", feature = "future"))]
+async fn fill_from() {
+  let (mut tx, rx) = tokio::io::duplex(4);
+  let writer = tokio::spawn(async move {
+    use tokio::io::AsyncWriteExt;
+
+    for i in 0..100u8 {
+      tx.write_all(&[i]).await.unwrap();
+    }
+  });
+
+  let mut buf = Buffer::new([0u8; 8]);
+  assert_eq!(buf.fill_from_tokio(rx).await.unwrap(), 100);
+  writer.await.unwrap();
+  assert_eq!(
+    buf.read_to_bytes().as_ref(),
+    &[92, 93, 94, 95, 96, 97, 98, 99]
+  );
+
+  let mut buf = Buffer::new([0u8; 3]);
+  let src = futures_util::io::Cursor::new(b"futures".to_vec());
+  assert_eq!(buf.fill_from_futures(src).await.unwrap(), 7);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"res");
+}
+
+#[test]
+fn extend() {
+  let mut buf = Buffer::new([0u8; 5]);
+  buf.write(b"ab");
+  buf.extend(b"cdefg");
+  assert_eq!(buf.read_to_bytes().as_ref(), b"cdefg");
+  assert_eq!((buf.written(), buf.unread()), (7, 5));
+
+  buf.extend((0..3).map(|i| b'0' + i));
+  assert_eq!(buf.read_to_bytes().as_ref(), b"fg012");
+}
+
+#[test]
+fn read_to_string_lossy() {
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write("hello".as_bytes());
+  assert!(matches!(
+    buf.read_to_string_lossy(),
+    std::borrow::Cow::Borrowed("hello")
+  ));
+
+  // "€" is 3 bytes long, only its last byte is retained
+  buf.write("€abcdefg".as_bytes());
+  assert_eq!(buf.read_to_string_lossy(), "\u{FFFD}abcdefg");
+
+  buf.write(b"\xffxyz");
+  assert_eq!(buf.read_to_string_lossy(), "defg\u{FFFD}xyz");
+}
+
+#[test]
+fn find() {
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"abcdef");
+  buf.write(b"ghij");
+  // Stored as "ij" + "cdefgh"
+  assert_eq!(buf.as_slices(), (&b"cdefgh"[..], &b"ij"[..]));
+
+  assert_eq!(buf.find(b""), Some(0));
+  assert_eq!(buf.find(b"cd"), Some(0));
+  assert_eq!(buf.find(b"hi"), Some(5));
+  assert_eq!(buf.find(b"fghij"), Some(3));
+  assert_eq!(buf.find(b"ij"), Some(6));
+  assert_eq!(buf.find(b"cdefghij"), Some(0));
+  assert_eq!(buf.find(b"cdefghijk"), None);
+  assert_eq!(buf.find(b"ab"), None);
+  assert!(buf.contains(b"j"));
+
+  let mut dst = [0u8; 4];
+  buf.read(&mut dst);
+  assert_eq!(buf.find(b"cd"), None);
+  assert_eq!(buf.find(b"hi"), Some(1));
+}
+
+#[test]
+fn lines() {
+  let mut buf = Buffer::new([0u8; 8]);
+  assert_eq!(buf.lines().count(), 0);
+
+  buf.write(b"xxxxx");
+  buf.write(b"\nab\r\ncd\n");
+  // Stored as "b\r\ncd\n" + "\na"
+  assert_eq!(buf.lines().collect::<Vec<_>>(), [&b""[..], b"ab", b"cd"]);
+
+  buf.write(b"\n\n");
+  assert_eq!(
+    buf.lines().collect::<Vec<_>>(),
+    [&b"b"[..], b"cd", b"", b""]
+  );
+}
+
+#[test]
+fn drain() {
+  let mut buf = Buffer::new([0u8; 4]);
+  assert!(buf.drain::<u8>().is_empty());
+
+  buf.write(b"abcdef");
+  let mut dst = [0u8; 1];
+  buf.read(&mut dst);
+  assert_eq!(buf.drain(), b"def");
+
+  buf.write(b"xy");
+  assert_eq!(buf.drain(), b"xy");
+}
+
+#[test]
+fn snapshot() {
+  use std::io::Read;
+
+  let mut buf = Buffer::new([0u8; 4]);
+  let empty = buf.snapshot();
+  assert!(empty.is_empty());
+  assert_eq!(empty.wraps(), 0);
+
+  buf.write(b"abcdef");
+  let mut dst = [0u8; 1];
+  buf.read(&mut dst);
+  let mut snapshot = buf.snapshot();
+  let handle = std::thread::spawn(move || {
+    let mut out = Vec::new();
+    snapshot.read_to_end(&mut out).unwrap();
+    assert_eq!(snapshot.len(), 3);
+    (out, snapshot.wraps())
+  });
+  buf.write(b"ghij");
+
+  assert_eq!(handle.join().unwrap(), (b"def".to_vec(), 1));
+}
+
+#[test]
+fn freeze() {
+  use std::io::Read;
+
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"hello");
+  buf.write(b" world");
+
+  let frozen = buf.freeze();
+  let clone = frozen.clone();
+  assert!(std::sync::Arc::ptr_eq(frozen.as_arc(), clone.as_arc()));
+  assert_eq!((clone.written(), clone.capacity()), (11, 6));
+
+  let mut out = String::new();
+  (&clone[..]).read_to_string(&mut out).unwrap();
+  assert_eq!(out, " world");
+}
+
+#[test]
+fn resize() {
+  let mut buf = Buffer::new(vec![0u16; 3]);
+  buf.write(&[1, 2, 3, 4]);
+  buf.resize(3);
+  assert_eq!(buf.iter().collect::<Vec<u16>>(), [2, 3, 4]);
+
+  buf.resize(0);
+  assert_eq!(buf.unread(), 0);
+  buf.resize(2);
+  buf.write(&[5, 6, 7]);
+  assert_eq!(buf.iter().collect::<Vec<u16>>(), [6, 7]);
+  assert_eq!(buf.written(), 7);
+}
+
+#[test]
+fn make_contiguous() {
+  let mut buf = Buffer::new([0u8; 5]);
+  assert!(buf.make_contiguous().is_empty());
+
+  buf.write(b"abcdefg");
+  let mut dst = [0u8; 1];
+  buf.read(&mut dst);
+  assert_eq!(buf.make_contiguous(), b"defg");
+  assert_eq!(buf.as_slices(), (&b"defg"[..], &b""[..]));
+
+  buf.write(b"hi");
+  assert_eq!(buf.read_to_bytes().as_ref(), b"efghi");
+  assert_eq!(buf.written(), 9);
+}
+
+#[test]
+fn hash() {
+  use std::hash::{BuildHasher, RandomState};
+
+  let mut a = Buffer::new([0u8; 4]);
+  a.write(b"xx");
+  a.write(b"abcd");
+  let mut b = Buffer::new([0u8; 4]);
+  b.write(b"abcd");
+  assert_ne!(a.as_slices(), b.as_slices());
+
+  let state = RandomState::new();
+  assert_eq!(state.hash_one(a), state.hash_one(b));
+  assert_eq!(a.content_hash(), b.content_hash());
+
+  b.write(b"e");
+  assert_ne!(state.hash_one(a), state.hash_one(b));
+  assert_ne!(a.content_hash(), b.content_hash());
+  // FNV-1a of the empty input
+  assert_eq!(Buffer::new([0u8; 0]).content_hash(), 0xcbf2_9ce4_8422_2325);
+}
+
+#[test]
+fn content_eq() {
+  let mut a = Buffer::new([0u8; 4]);
+  a.write(b"xx");
+  a.write(b"abcd");
+  let mut b = Buffer::new(vec![0u8; 6]);
+  b.write(b"zabcd");
+  b.read(&mut [0u8; 1]);
+
+  assert_eq!(a, b);
+  assert!(a.content_eq(&b));
+
+  b.write(b"e");
+  assert_ne!(a, b);
+  assert_ne!(Buffer::new([0u8; 1]), a);
+}
+
+#[test]
+fn index() {
+  let mut buf = Buffer::new([0u8; 5]);
+  buf.write(b"abc");
+  buf.write(b"defg");
+  // Stored as "fg" + "cde"
+  assert_eq!(buf.as_slices(), (&b"cde"[..], &b"fg"[..]));
+  assert_eq!((buf[0], buf[2], buf[3], buf[4]), (b'c', b'e', b'f', b'g'));
+  assert_eq!(buf.get(5), None::<u8>);
+
+  assert_eq!(buf.range(..), b"cdefg");
+  assert_eq!(buf.range(2..4).as_slices(), (&b"e"[..], &b"f"[..]));
+  assert_eq!(buf.range(3..=4), b"fg");
+  assert!(buf.range::<u8>(5..).is_empty());
+
+  buf.read(&mut [0u8; 2]);
+  assert_eq!(buf[0], b'e');
+}
+
+#[test]
+#[should_panic]
+fn index_out_of_bounds() {
+  let buf = Buffer::new([0u8; 5]);
+  let _ = buf[0];
+}
+
+#[test]
+#[cfg(feature = "arbitrary")]
+fn arbitrary() {
+  use arbitrary::{Arbitrary, Unstructured};
+
+  let mut seed = 0x2545_f491_4f6c_dd1du64;
+  for _ in 0..256 {
+    let raw = (0..64)
+      .map(|_| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as u8
+      })
+      .collect::<Vec<_>>();
+
+    let mut buf = Buffer::<Vec<u8>>::arbitrary(&mut Unstructured::new(&raw)).unwrap();
+    assert!(buf.unread() <= buf.size::<u8>());
+    assert!(buf.unread() <= buf.written());
+    assert_eq!(buf.read_to_bytes().len(), buf.unread());
+
+    buf.write(b"fuzz");
+    let tail = b"fuzz".len().min(buf.size::<u8>());
+    assert_eq!(buf.tail_vec::<u8>(tail), b"fuzz"[4 - tail..]);
+  }
+}
+
+#[test]
+fn pow2() {
+  let mut buf = Buffer::with_capacity_pow2(5);
+  assert_eq!(buf.size::<u8>(), 8);
+  assert_eq!(Buffer::with_capacity_pow2(0).size::<u8>(), 1);
+
+  for chunk in b"the quick brown fox jumps over the lazy dog".chunks(3) {
+    buf.write(chunk);
+  }
+  assert_eq!(buf.read_to_bytes().as_ref(), b"lazy dog");
+  assert_eq!(buf.make_contiguous(), b"lazy dog");
+}
+
+#[test]
+fn readers() {
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"old");
+  let mut a = buf.reader();
+  let mut b = buf.reader();
+  assert_eq!(a.position(), 0);
+
+  let mut dst = [0u8; 8];
+  assert_eq!(a.read(&buf, &mut dst[..2]), Ok(2));
+  assert_eq!(&dst[..2], b"ol");
+  assert_eq!(a.position(), 2);
+
+  // Readers don't consume the buffer's own unread data
+  assert_eq!(buf.unread(), 3);
+
+  buf.write(b"abcdefg");
+  assert_eq!(a.read(&buf, &mut dst), Ok(8));
+  assert_eq!(&dst, b"dabcdefg");
+  assert_eq!(a.read(&buf, &mut dst), Ok(0));
+
+  // Lapped by the writer
+  assert_eq!(b.read(&buf, &mut dst).unwrap_err().missed(), 2);
+  assert_eq!(b.position(), 2);
+  assert_eq!(b.read(&buf, &mut dst), Ok(8));
+  assert_eq!(a, b);
+}
+
+#[test]
+fn read_since() {
+  let mut buf = Buffer::new([0u8; 4]);
+  let mut dst = [0u8; 4];
+  assert_eq!(buf.read_since(0, &mut dst), Ok(0));
+
+  buf.write(b"abc");
+  let mut dst2 = [0u8; 1];
+  assert_eq!(buf.read_since(1, &mut dst2), Ok(1));
+  assert_eq!(&dst2, b"b");
+  assert_eq!(buf.read_since(3, &mut dst), Ok(0));
+
+  // Consuming doesn't affect the stream offsets
+  buf.read(&mut dst);
+  assert_eq!(buf.read_since(0, &mut dst), Ok(3));
+
+  buf.write(b"defgh");
+  let gap = buf.read_since(1, &mut dst).unwrap_err();
+  assert_eq!(gap.missed(), 3);
+  assert_eq!(gap.oldest(), 4);
+  assert_eq!(buf.read_since(6, &mut dst), Ok(2));
+  assert_eq!(&dst[..2], b"gh");
+
+  // Offsets past the end of the stream restart from its beginning
+  buf.reset();
+  buf.write(b"xy");
+  assert_eq!(buf.read_since(8, &mut dst), Ok(2));
+  assert_eq!(&dst[..2], b"xy");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn view_reader_seek() {
+  use std::io::{BufRead, Read, Seek, SeekFrom};
+
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"012345");
+  buf.write(b"6789");
+  let mut reader = buf.range(..).reader();
+
+  // The view wraps: "234567" then "89"
+  assert_eq!(reader.fill_buf().unwrap(), b"234567");
+  assert_eq!(reader.seek(SeekFrom::End(-3)).unwrap(), 5);
+  let mut dst = Vec::new();
+  reader.read_to_end(&mut dst).unwrap();
+  assert_eq!(dst, b"789");
+
+  assert_eq!(reader.seek(SeekFrom::Current(-6)).unwrap(), 2);
+  let mut dst = [0u8; 2];
+  reader.read_exact(&mut dst).unwrap();
+  assert_eq!(&dst, b"45");
+  assert_eq!(reader.position(), 4);
+  assert_eq!(reader.remaining_view(), b"6789");
+
+  assert!(reader.seek(SeekFrom::Current(-5)).is_err());
+  assert_eq!(reader.position(), 4);
+
+  // Past the end, like io::Cursor
+  assert_eq!(reader.seek(SeekFrom::Start(20)).unwrap(), 20);
+  assert_eq!(reader.read(&mut dst).unwrap(), 0);
+  assert!(reader.fill_buf().unwrap().is_empty());
+}
+
+#[test]
+fn write_at() {
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"hello");
+  buf.write(b" world");
+
+  // The retained " world" wraps after " "
+  assert_eq!(buf.write_at(0, b"_W"), 2);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"_World");
+  assert_eq!(buf.write_at(4, b"LDS"), 2);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"_WorLD");
+  assert_eq!(buf.write_at(6, b"!"), 0);
+  assert_eq!(buf.written(), 11);
+
+  buf.read(&mut [0u8; 2]);
+  assert_eq!(buf.write_at(0, b"OR"), 2);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"ORLD");
+
+  let mut empty = Buffer::new([0u8; 0]);
+  assert_eq!(empty.write_at(0, b"x"), 0);
+}
+
+#[test]
+fn frames() {
+  use circularbuf::LengthPrefix;
+
+  let mut buf = Buffer::new([0u8; 16]);
+  assert_eq!(buf.frames(LengthPrefix::U32Le).count(), 0);
+
+  buf.write(b"\x02\x00\x00\x00hi");
+  buf.write(b"\x00\x00\x00\x00");
+  let frames = buf.frames(LengthPrefix::U32Le).collect::<Vec<_>>();
+  assert_eq!(frames, [&b"hi"[..], b""]);
+
+  // The frames wrap, and the oldest one is torn
+  buf.write(b"\x06\x00\x00\x00hello!");
+  buf.write(b"\x03\x00\x00\x00abc");
+  assert_eq!(buf.as_slices().1.len(), 11);
+  let frames = buf.frames(LengthPrefix::U32Le).collect::<Vec<_>>();
+  assert_eq!(frames, [&b"abc"[..]]);
+
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"\x00\x01a\x00\x02bc");
+  let frames = buf.frames(LengthPrefix::U16Be).collect::<Vec<_>>();
+  assert_eq!(frames, [&b"a"[..], b"bc"]);
+
+  // A partially written frame ends the parsing, the complete ones are still yielded
+  let mut buf = Buffer::new([0u8; 16]);
+  buf.write(b"\x00\x05hello\x00\x03abc");
+  buf.write(b"\x00\x09ab");
+  let frames = buf.frames(LengthPrefix::U16Be).collect::<Vec<_>>();
+  assert_eq!(frames, [&b"hello"[..], b"abc"]);
+  buf.write(b"cdefghi");
+  let frames = buf.frames(LengthPrefix::U16Be).collect::<Vec<_>>();
+  assert_eq!(frames, [&b"abc"[..], b"abcdefghi"]);
+
+  // Parsing a large ring ending with a partial frame stays linear
+  let mut buf = Buffer::new(vec![0u8; 1 << 20]);
+  let frames = (1 << 20) / 6;
+  for _ in 0..frames {
+    buf.write(b"\x00\x04ping");
+  }
+  buf.write(b"\xff\xff");
+  assert_eq!(buf.frames(LengthPrefix::U16Be).count(), frames);
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn buf_mut() {
+  use bytes::BufMut;
+
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.put_slice(b"hell");
+  buf.put_u32(u32::from_be_bytes(*b"o wo"));
+  assert_eq!(buf.read_to_bytes().as_ref(), b"llo wo");
+  assert_eq!(buf.written(), 8);
+
+  // Writes in place wrap around the end of the storage
+  assert_eq!(buf.chunk_mut().len(), 4);
+  buf.put_bytes(b'!', 6);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"!!!!!!");
+  assert_eq!(buf.written(), 14);
+  // `put_bytes` fills the two chunks in place, one write each
+  assert_eq!(buf.stats().writes(), 4);
+
+  let buf = Buffer::new([0u8; 0]);
+  assert_eq!(buf.remaining_mut(), 0);
+  assert!(!buf.has_remaining_mut());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn view_reader_buf() {
+  use bytes::Buf;
+
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"012345");
+  buf.write(b"6789");
+  let mut reader = buf.range(..).reader();
+
+  assert_eq!(reader.remaining(), 8);
+  assert_eq!(reader.chunk(), b"234567");
+  reader.advance(5);
+  assert_eq!(reader.chunk(), b"7");
+  reader.advance(1);
+  assert_eq!(reader.chunk(), b"89");
+  assert_eq!(reader.get_u16(), u16::from_be_bytes(*b"89"));
+  assert!(reader.chunk().is_empty());
+
+  let mut reader = buf.range(..).reader();
+  let mut dst = [0u8; 8];
+  reader.copy_to_slice(&mut dst);
+  assert_eq!(&dst, b"23456789");
+}
+
+#[test]
+fn read_to_array() {
+  let mut buf = Buffer::new([0u8; 6]);
+  assert_eq!(buf.read_to_array::<4>(), ([0; 4], 0));
+
+  buf.write(b"hello");
+  buf.write(b" world");
+  assert_eq!(buf.read_to_array::<8>(), (*b" world\0\0", 6));
+  assert_eq!(buf.read_to_array::<3>(), (*b" wo", 3));
+  assert_eq!(buf.read_to_array::<0>(), ([], 0));
+  assert_eq!(buf.unread(), 6);
+}
+
+#[test]
+fn container_api() {
+  let mut buf = Buffer::new([0u8; 4]);
+  assert!(buf.is_empty());
+  assert!(!buf.is_full());
+  assert_eq!(buf.capacity(), buf.size());
+
+  buf.write(b"abcdef");
+  assert_eq!(buf.len(), 4);
+  assert_eq!(buf.len(), buf.read_hint());
+  assert!(buf.is_full());
+
+  buf.read(&mut [0u8; 1]);
+  assert_eq!(buf.len(), 3);
+  assert!(!buf.is_full());
+  assert!(!buf.is_empty());
+
+  // A zero-sized buffer is both empty and full
+  let buf = Buffer::new([0u8; 0]);
+  assert!(buf.is_empty() && buf.is_full());
+}
+
+#[test]
+fn remaining_until_wrap() {
+  let mut buf = Buffer::new([0u8; 4]);
+  assert_eq!(buf.remaining_until_wrap(), 4);
+
+  buf.write(b"abcdef");
+  assert_eq!(buf.remaining_until_wrap(), 0);
+
+  buf.read(&mut [0u8; 3]);
+  // 3 bytes are free, from the start of the storage
+  assert_eq!(buf.remaining_until_wrap(), 3);
+  buf.write(b"gh");
+  assert_eq!(buf.remaining_until_wrap(), 1);
+  buf.read(&mut [0u8; 3]);
+  // 4 bytes are free, but the cursor sits at 2
+  assert_eq!(buf.remaining_until_wrap(), 2);
+
+  assert_eq!(Buffer::new([0u8; 0]).remaining_until_wrap(), 0);
+}
+
+#[test]
+fn wraps_and_overwritten() {
+  let mut buf = Buffer::new([0u8; 4]);
+  buf.write(b"ab");
+  assert_eq!((buf.wraps(), buf.overwritten()), (0, 0));
+
+  buf.write(b"cd");
+  assert_eq!((buf.wraps(), buf.overwritten()), (1, 0));
+
+  // Consumed data is not lost when overwritten
+  buf.read(&mut [0u8; 2]);
+  buf.write(b"efg");
+  assert_eq!((buf.wraps(), buf.overwritten()), (1, 1));
+  assert_eq!(buf.overwritten(), buf.stats().overwritten());
+
+  // A write spanning several capacities wraps once per capacity, wherever it is counted
+  buf.write(b"0123456789");
+  assert_eq!(buf.wraps(), 4);
+  assert_eq!(buf.snapshot().wraps(), buf.wraps());
+  assert_eq!(buf.stats().wraps(), buf.written() / buf.size());
+
+  buf.reset();
+  assert_eq!((buf.wraps(), buf.overwritten()), (0, 0));
+}
+
+#[test]
+fn discard_oldest() {
+  let mut buf = Buffer::new([0u8; 4]);
+  buf.write(b"abcdef");
+  assert_eq!(buf.discard_oldest(1), 1);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"def");
+
+  // Dropped bytes are not reported as overwritten
+  buf.write(b"g");
+  assert_eq!(buf.overwritten(), 0);
+  assert_eq!(buf.discard_oldest(10), 4);
+  assert!(buf.is_empty());
+  assert_eq!(buf.discard_oldest(1), 0);
+  assert_eq!(buf.written(), 7);
+}
+
+#[test]
+fn split_at() {
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"hello");
+  buf.write(b" world");
+
+  // The unread data wraps after " "
+  for mid in 0..=6 {
+    let (a, b) = buf.split_at(mid);
+    assert_eq!(a, &b" world"[..mid]);
+    assert_eq!(b, &b" world"[mid..]);
+  }
+}
+
+#[test]
+#[should_panic]
+fn split_at_out_of_bounds() {
+  let mut buf = Buffer::new([0u8; 6]);
+  buf.write(b"abc");
+  let _ = buf.split_at::<u8>(4);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn copy_to_writer() {
+  let mut buf = Buffer::new([0u8; 6]);
+  let mut out = Vec::new();
+  assert_eq!(buf.copy_to_writer(&mut out).unwrap(), 0);
+
+  buf.write(b"hello");
+  buf.write(b" world");
+  assert_eq!(buf.copy_to_writer(&mut out).unwrap(), 6);
+  assert_eq!(out, b" world");
+  assert_eq!(buf.unread(), 6);
+
+  // Errors of the writer are propagated
+  let mut full = [0u8; 4];
+  let err = buf.copy_to_writer(&mut &mut full[..]).unwrap_err();
+  assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(flavor = "current_thread")]
+async fn copy_to() {
+  use circularbuf::BlockingBuffer;
+  use tokio::io::AsyncReadExt;
+
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"0123");
+  buf.write(b"456789");
+
+  // The pipe is smaller than the data, so the copy must wait for the reader
+  let pipe = BlockingBuffer::new([0u8; 3]);
+  let copy = async {
+    let n = buf.copy_to(pipe.clone()).await.unwrap();
+    pipe.close();
+    n
+  };
+  let read = async {
+    let mut out = Vec::new();
+    AsyncReadExt::read_to_end(&mut &pipe, &mut out)
+      .await
+      .unwrap();
+    out
+  };
+  let (n, out) = futures_util::join!(copy, read);
+  assert_eq!(n, 8);
+  assert_eq!(out, b"23456789");
+  assert_eq!(buf.unread(), 8);
+}
+
+#[cfg(feature = "futures-io")]
+#[tokio::test(flavor = "current_thread")]
+async fn copy_to_futures() {
+  use circularbuf::BlockingBuffer;
+  use futures_util::AsyncReadExt;
+
+  let mut buf = Buffer::new([0u8; 8]);
+  buf.write(b"0123");
+  buf.write(b"456789");
+
+  let pipe = BlockingBuffer::new([0u8; 3]);
+  let copy = async {
+    let n = buf.copy_to_futures(pipe.clone()).await.unwrap();
+    pipe.close();
+    n
+  };
+  let read = async {
+    let mut out = Vec::new();
+    AsyncReadExt::read_to_end(&mut &pipe, &mut out)
+      .await
+      .unwrap();
+    out
+  };
+  let (n, out) = futures_util::join!(copy, read);
+  assert_eq!(n, 8);
+  assert_eq!(out, b"23456789");
+
+  // Writers accepting nothing are reported
+  let err = buf
+    .copy_to_futures(futures_util::io::Cursor::new(&mut [0u8; 4][..]))
+    .await
+    .unwrap_err();
+  assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+}
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn zeroize() {
+  use zeroize::{Zeroize, Zeroizing};
+
+  let mut buf = Buffer::new(vec![0u8; 6]);
+  buf.write(b"secret key");
+  buf.reset_secure();
+  assert!(buf.is_empty());
+  assert_eq!(buf.written(), 0);
+  assert_eq!(buf.stats().writes(), 0);
+  buf.write(b"ab");
+  assert_eq!(buf.into_inner(), b"ab\0\0\0\0");
+
+  let mut buf = Buffer::new(vec![0u8; 4]);
+  buf.write(b"key");
+  buf.zeroize();
+  assert!(buf.is_empty());
+  assert_eq!(buf.into_inner(), [0; 4]);
+
+  let mut buf = Zeroizing::new(Buffer::new([0u8; 4]));
+  buf.write(b"key");
+  assert_eq!(buf.read_to_bytes().as_ref(), b"key");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn vecdeque() {
+  use std::collections::VecDeque;
+
+  let mut deque = VecDeque::with_capacity(4);
+  deque.extend(*b"cd");
+  deque.push_front(b'b');
+  deque.push_front(b'a');
+
+  let mut buf = Buffer::from_vecdeque(deque);
+  assert_eq!(buf.capacity(), 4);
+  assert!(buf.is_full());
+  assert_eq!(buf.written(), 4);
+  assert_eq!(buf.read_to_bytes().as_ref(), b"abcd");
+
+  buf.write(b"ef");
+  assert_eq!(buf.read_to_bytes().as_ref(), b"cdef");
+  buf.read(&mut [0u8; 1]);
+  assert_eq!(buf.into_vecdeque(), *b"def");
+
+  let buf: Buffer<Box<[u32]>> = VecDeque::new().into();
+  assert!(buf.is_empty());
+  assert!(buf.into_vecdeque().is_empty());
+}
+
+#[test]
+fn display() {
+  let mut buf = Buffer::new([0u8; 4]);
+  buf.write(b"xxx");
+  buf.read(&mut [0u8; 3]);
+  // "é" spans the wrap point
+  buf.write("é!".as_bytes());
+  assert_eq!(buf.to_string(), "é!");
+
+  buf.write(b"\xff");
+  assert_eq!(buf.to_string(), "é!\u{fffd}");
+
+  // The cap never splits a character
+  assert_eq!(format!("{buf:.3}"), "…!\u{fffd}");
+  assert_eq!(format!("{buf:.0}"), "…");
+
+  let mut buf = Buffer::with_capacity(4096);
+  buf.write(&[b'a'; 2048]);
+  let s = buf.to_string();
+  assert!(s.starts_with("…a"));
+  assert_eq!(s.len(), "…".len() + 1024);
+}
+
+#[test]
+fn new_full() {
+  let mut buf = Buffer::new_full([1u32, 2, 3]);
+  assert!(buf.is_full());
+  assert_eq!(buf.written(), 3);
+  let mut reader = buf.reader();
+
+  buf.write(&[4]);
+  let mut dst = [0u32; 3];
+  assert_eq!(reader.read(&buf, &mut dst).unwrap_err().missed(), 1);
+  assert_eq!(buf.read(&mut dst), 3);
+  assert_eq!(dst, [2, 3, 4]);
+
+  let mut buf = Buffer::from_filled([7u8, 0, 0], 1);
+  assert_eq!(buf.len(), 1);
+  buf.write(b"ab");
+  assert_eq!(buf.read_to_bytes().as_ref(), [7, b'a', b'b']);
+
+  let buf = Buffer::from_filled([0u8; 0], 0);
+  assert!(buf.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn from_filled_out_of_bounds() {
+  Buffer::from_filled([0u8; 2], 3);
+}
+
+#[test]
+fn default() {
+  let mut buf = Buffer::<[u32; 64]>::default();
+  assert_eq!(buf.capacity(), 64);
+  assert!(buf.is_empty());
+  buf.write(&[1, 2]);
+  assert_eq!(buf.len(), 2);
+
+  assert_eq!(InlineBuffer::<8>::default().size(), 8);
+}
+
+#[test]
+#[cfg(feature = "hex")]
+fn hex() {
+  let mut buf = Buffer::new([0u8; 3]);
+  assert_eq!(buf.to_hex(), "");
+  buf.write(b"\x0a\xbc\xde\xf0");
+
+  let restored = Buffer::from_hex(&buf.to_hex()).unwrap();
+  assert_eq!(restored, buf);
+  assert_eq!(restored.size(), 3);
+
+  assert_eq!(Buffer::from_hex("abc").unwrap_err().position(), 3);
+  assert_eq!(Buffer::from_hex("zz").unwrap_err().position(), 0);
+  assert!(Buffer::from_hex("").unwrap().is_empty());
+}
+
+#[test]
+#[cfg(feature = "allocator-api2")]
+fn with_capacity_in() {
+  use allocator_api2::alloc::{AllocError, Allocator, Global, Layout};
+  use core::{cell::Cell, ptr::NonNull};
+
+  struct Counting(Cell<usize>);
+
+  unsafe impl Allocator for &Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+      self.0.set(self.0.get() + layout.size());
+      Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+      unsafe { Global.deallocate(ptr, layout) }
+    }
+  }
+
+  let alloc = Counting(Cell::new(0));
+  let mut buf = Buffer::with_capacity_in(64, &alloc);
+  assert_eq!(alloc.0.get(), 64);
+  assert_eq!(buf.size(), 64);
+
+  buf.write(b"hello");
+  assert_eq!(buf.read_to_bytes().as_ref(), b"hello");
+}
+
+#[test]
+fn write_grant() {
+  let mut buf = Buffer::new([0u32; 4]);
+  let (first, second) = buf.write_grant(3);
+  assert!(second.is_none());
+  first.copy_from_slice(&[1, 2, 3]);
+  buf.commit(2);
+  assert_eq!(buf.read_to_bytes().as_ref(), [1, 2]);
+
+  // Larger grants are capped to the size, and may cover unread elements
+  let (first, second) = buf.write_grant(10);
+  assert_eq!(first, [3, 0]);
+  assert_eq!(second.unwrap(), [1, 2]);
+  buf.commit(4);
+  assert_eq!(buf.len(), 4);
+  assert_eq!(buf.written(), 6);
+  assert_eq!(buf.stats().overwritten(), 2);
+  assert_eq!(buf.read_to_bytes().as_ref(), [3, 0, 1, 2]);
+
+  buf.commit(0);
+  assert_eq!(buf.stats().writes(), 2);
+
+  let mut empty = Buffer::new([0u8; 0]);
+  assert_eq!(empty.write_grant(4), (&mut [][..], None));
+}
+
+#[test]
+#[should_panic]
+fn commit_out_of_bounds() {
+  Buffer::new([0u8; 4]).commit(5);
+}
+
+#[test]
+fn read_grant() {
+  let mut buf = Buffer::new([0u8; 4]);
+  assert!(buf.read_grant().is_empty());
+
+  buf.write(b"abc");
+  buf.release(2);
+  buf.write(b"def");
+  assert_eq!(buf.read_grant(), b"cd");
+  buf.release(1);
+  assert_eq!(buf.read_grant(), b"d");
+  buf.release(1);
+  assert_eq!(buf.read_grant(), b"ef");
+
+  // Releasing does not interfere with writes
+  buf.write(b"g");
+  assert_eq!(buf.read_grant(), b"efg");
+  buf.release(3);
+  assert!(buf.is_empty());
+  assert_eq!(buf.written(), 7);
+}
+
+#[test]
+#[should_panic]
+fn release_out_of_bounds() {
+  let mut buf = Buffer::new([0u8; 4]);
+  buf.write(b"ab");
+  buf.release(3);
+}
+
+#[test]
+fn inconsistent_storage() {
+  // A safe `AsMut` impl returning a shorter slice on every call but the first
+  struct Shrinking {
+    data: [u8; 10],
+    calls: usize,
+  }
+
+  impl AsMut<[u8]> for Shrinking {
+    fn as_mut(&mut self) -> &mut [u8] {
+      self.calls += 1;
+      if self.calls == 1 {
+        &mut self.data
+      } else {
+        &mut self.data[..1]
+      }
+    }
+  }
+
+  let mut buf = Buffer::new(Shrinking {
+    data: [0; 10],
+    calls: 0,
+  });
+  assert_eq!(buf.write(b"0123456789"), 10);
+  assert_eq!(buf.write_vectored(&[b"ab", b"cd"]), 4);
+}
+
+#[test]
+fn history_after_resize() {
+  let mut buf = Buffer::with_capacity(8);
+  buf.write(b"hello");
+  buf.read(&mut [0u8; 3]);
+  buf.resize(16);
+
+  // The consumed bytes are kept as history
+  let mut dst = [0u8; 8];
+  assert_eq!(buf.read_since(0, &mut dst), Ok(5));
+  assert_eq!(&dst[..5], b"hello");
+  assert_eq!(buf.read_to_bytes().as_ref(), b"lo");
+
+  buf.write(b"world");
+  buf.resize(4);
+  let gap = buf.read_since(0, &mut dst).unwrap_err();
+  assert_eq!((gap.missed(), gap.oldest()), (6, 6));
+  assert_eq!(buf.read_since(6, &mut dst), Ok(4));
+  assert_eq!(&dst[..4], b"orld");
+
+  let mut reader = buf.reader();
+  assert_eq!(reader.position(), 6);
+  assert_eq!(reader.read(&buf, &mut dst), Ok(4));
+}
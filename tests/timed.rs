@@ -0,0 +1,56 @@
+#![cfg(feature = "std")]
+
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use circularbuf::Buffer;
+
+fn clock() -> (Rc<Cell<Duration>>, impl Fn() -> Duration) {
+  let now = Rc::new(Cell::new(Duration::ZERO));
+  let clock = {
+    let now = now.clone();
+    move || now.get()
+  };
+  (now, clock)
+}
+
+#[test]
+fn expires_old_data() {
+  let (now, clock) = clock();
+  let mut buf = Buffer::new([0u8; 16]).with_retention_clock(Duration::from_secs(10), clock);
+  buf.write(b"aaa");
+  now.set(Duration::from_secs(5));
+  buf.write(b"bbb");
+  now.set(Duration::from_secs(12));
+  buf.write(b"ccc");
+
+  assert_eq!(buf.as_buffer().unread(), 6);
+  let mut dst = [0u8; 16];
+  assert_eq!(buf.read(&mut dst), 6);
+  assert_eq!(&dst[..6], b"bbbccc");
+
+  now.set(Duration::from_secs(30));
+  buf.write(b"d");
+  buf.expire();
+  assert_eq!(buf.recent(), b"d");
+}
+
+#[test]
+fn since_across_overwrites() {
+  let (now, clock) = clock();
+  let mut buf = Buffer::new([0u8; 8]).with_retention_clock(Duration::from_secs(60), clock);
+  for i in 0..10u8 {
+    now.set(Duration::from_secs(u64::from(i)));
+    buf.write(&[b'0' + i; 2]);
+  }
+
+  assert_eq!(buf.recent(), b"66778899");
+  assert_eq!(buf.since(Duration::from_secs(1)), b"8899");
+  assert_eq!(buf.since(Duration::ZERO), b"99");
+}
+
+#[test]
+fn std_clock() {
+  let mut buf = Buffer::new([0u8; 8]).with_retention(Duration::from_secs(3600));
+  buf.write(b"hello");
+  assert_eq!(buf.recent(), b"hello");
+}
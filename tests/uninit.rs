@@ -0,0 +1,48 @@
+#![cfg(feature = "uninit")]
+
+use circularbuf::UninitBuffer;
+
+#[test]
+fn initialized_prefix() {
+  let mut buf = UninitBuffer::with_capacity(8);
+  assert_eq!(buf.initialized(), 0);
+  assert_eq!(buf.as_slices::<u8>(), (&[][..], &[][..]));
+
+  buf.write(b"abc");
+  assert_eq!(buf.initialized(), 3);
+
+  let mut dst = [0u8; 2];
+  assert_eq!(buf.read(&mut dst), 2);
+  assert_eq!(&dst, b"ab");
+
+  buf.reset();
+  buf.write(b"x");
+  assert_eq!(buf.initialized(), 3);
+  assert_eq!(buf.as_slices(), (&b"x"[..], &[][..]));
+}
+
+#[test]
+fn wrap() {
+  let mut buf = UninitBuffer::<[_; 4]>::uninit();
+  buf.write(b"abc");
+  buf.write(b"de");
+  assert_eq!(buf.initialized(), 4);
+  assert_eq!(buf.as_slices(), (&b"bcd"[..], &b"e"[..]));
+
+  buf.write(b"0123456789");
+  assert_eq!(buf.written(), 15);
+  assert_eq!(buf.unread(), 4);
+
+  let mut dst = [0u8; 4];
+  assert_eq!(buf.read(&mut dst), 4);
+  assert_eq!(&dst, b"6789");
+}
+
+#[test]
+fn borrowed() {
+  let mut storage = [core::mem::MaybeUninit::<u32>::uninit(); 3];
+  let mut buf = UninitBuffer::new(&mut storage[..]);
+  buf.write(&[1, 2, 3, 4]);
+  assert_eq!(buf.size::<u32>(), 3);
+  assert_eq!(buf.as_slices(), (&[2, 3, 4][..], &[][..]));
+}
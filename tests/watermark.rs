@@ -0,0 +1,49 @@
+use circularbuf::{Buffer, Crossing};
+
+#[test]
+fn crossings() {
+  let mut crossings = Vec::new();
+  {
+    let mut buf = Buffer::new([0u8; 8]).with_watermarks(&[25, 75, 100], |c| crossings.push(c));
+
+    // 2 of 8 reaches 25%
+    buf.write(b"ab");
+    // Overwriting at full capacity crosses nothing
+    buf.write(b"cdefghijk");
+    buf.write(b"l");
+    assert_eq!(buf.high_water_mark(), 8);
+
+    buf.read(&mut [0u8; 1]);
+    buf.read(&mut [0u8; 7]);
+    assert_eq!(buf.as_buffer().unread(), 0);
+
+    buf.reset_high_water_mark();
+    assert_eq!(buf.high_water_mark(), 0);
+    buf.write(b"a");
+    assert_eq!(buf.high_water_mark(), 1);
+  }
+
+  assert_eq!(
+    crossings,
+    [
+      Crossing::Above(25),
+      Crossing::Above(75),
+      Crossing::Above(100),
+      Crossing::Below(100),
+      Crossing::Below(75),
+      Crossing::Below(25),
+    ]
+  );
+}
+
+#[test]
+#[should_panic(expected = "greater than 100%")]
+fn invalid_threshold() {
+  Buffer::new([0u8; 8]).with_watermarks(&[101], |_| {});
+}
+
+#[test]
+#[should_panic(expected = "0% can never be crossed")]
+fn zero_threshold() {
+  Buffer::new([0u8; 8]).with_watermarks(&[0, 50], |_| {});
+}